@@ -0,0 +1,13 @@
+% Horn clauses of a small diagnosis, run with `swipl examples/prolog/prolog_1.pl`
+:- dynamic fever/0, cough/0, rash/0, vaccinated/0.
+
+flu :- fever, cough, \+ vaccinated.
+measles :- fever, rash.
+rest :- flu.
+rest :- measles.
+doctor :- not(rest), cough.
+
+fever.
+cough.
+
+?- forall(member(Query, [flu, measles, rest, doctor]), (call(Query) -> format("~w true~n", [Query]) ; format("~w false~n", [Query]))).
@@ -0,0 +1,396 @@
+use crate::{
+    error::{Error, ErrorKind},
+    input::Input,
+    node::Operator,
+    parser::Expression,
+};
+use std::{cell::RefCell, collections::HashMap};
+
+// S-expression with the offset of its first character in the content
+enum Sexp<'a> {
+    List(usize, Vec<Sexp<'a>>),
+    Atom(usize, &'a str),
+}
+
+impl<'a> Sexp<'a> {
+    fn offset(&self) -> usize {
+        match self {
+            Sexp::List(offset, _) | Sexp::Atom(offset, _) => *offset,
+        }
+    }
+
+    fn atom(&self) -> Option<&'a str> {
+        match self {
+            Sexp::Atom(_, atom) => Some(atom),
+            Sexp::List(_, _) => None,
+        }
+    }
+}
+
+// Conditional element of the left side of a rule
+enum Pattern<'a> {
+    Fact(&'a str),
+    Not(Box<Pattern<'a>>),
+    And(Vec<Pattern<'a>>),
+    Or(Vec<Pattern<'a>>),
+}
+
+// Premise and asserted facts of a rule, a rule without pattern always fires
+struct Rule<'a> {
+    // The name of the defrule is the label of the rule
+    name: &'a str,
+    patterns: Vec<Pattern<'a>>,
+    asserted: Vec<&'a str>,
+}
+
+type Invalid = (usize, String);
+
+fn invalid(offset: usize, message: &str) -> Invalid {
+    (offset, message.to_string())
+}
+
+// Tokens of the content with their offsets, a string is a single token and the comments start with `;`
+fn tokens(content: &str) -> Result<Vec<(usize, &str)>, Invalid> {
+    let mut tokens = vec![];
+    let mut characters = content.char_indices().peekable();
+    while let Some((offset, c)) = characters.next() {
+        match c {
+            '(' | ')' => tokens.push((offset, &content[offset..offset + 1])),
+            ';' => while characters.next_if(|(_, c)| *c != '\n').is_some() {},
+            '"' => {
+                let mut escaped = false;
+                let end = loop {
+                    match characters.next() {
+                        Some((_, '\\')) if !escaped => escaped = true,
+                        Some((end, '"')) if !escaped => break end,
+                        Some(_) => escaped = false,
+                        None => return Err(invalid(offset, "Unterminated string")),
+                    }
+                };
+                tokens.push((offset, &content[offset..=end]));
+            }
+            c if c.is_whitespace() => {}
+            _ => {
+                let mut end = offset + c.len_utf8();
+                while let Some((next, c)) = characters
+                    .next_if(|(_, c)| !c.is_whitespace() && !matches!(c, '(' | ')' | ';' | '"'))
+                {
+                    end = next + c.len_utf8();
+                }
+                tokens.push((offset, &content[offset..end]));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+// Top-level forms of the content
+fn sexps<'a>(tokens: &[(usize, &'a str)]) -> Result<Vec<Sexp<'a>>, Invalid> {
+    let mut stack: Vec<(usize, Vec<Sexp>)> = vec![(0, vec![])];
+    for (offset, token) in tokens.iter() {
+        match *token {
+            "(" => stack.push((*offset, vec![])),
+            ")" => {
+                if stack.len() == 1 {
+                    return Err(invalid(*offset, "Unexpected `)`"));
+                }
+                let (start, list) = stack.pop().unwrap();
+                stack.last_mut().unwrap().1.push(Sexp::List(start, list));
+            }
+            atom => stack.last_mut().unwrap().1.push(Sexp::Atom(*offset, atom)),
+        }
+    }
+    if stack.len() > 1 {
+        return Err(invalid(stack.last().unwrap().0, "Unclosed `(`"));
+    }
+    Ok(stack.pop().unwrap().1)
+}
+
+// Symbol of an ordered fact with a single field, like `(fever)`
+fn fact<'a>(sexp: &Sexp<'a>) -> Result<&'a str, Invalid> {
+    let fields = match sexp {
+        Sexp::List(_, fields) => fields,
+        Sexp::Atom(offset, atom) if atom.starts_with('?') => {
+            return Err(invalid(*offset, "Variables are not supported"))
+        }
+        Sexp::Atom(offset, _) => return Err(invalid(*offset, "Expected a fact like `(fever)`")),
+    };
+    // A variable in any field of the fact
+    if let Some(variable) = fields.iter().find(|field| {
+        field
+            .atom()
+            .is_some_and(|atom| atom.starts_with('?') || atom.starts_with("$?"))
+    }) {
+        return Err(invalid(variable.offset(), "Variables are not supported"));
+    }
+    match fields.first().and_then(Sexp::atom) {
+        Some(atom) if atom.starts_with('"') => Err(invalid(
+            fields[0].offset(),
+            "A fact is a symbol, not a string",
+        )),
+        Some(atom) if fields.len() == 1 => Ok(atom),
+        Some(_) => Err(invalid(
+            fields[1].offset(),
+            "Only facts with a single symbol are supported, like `(fever)`",
+        )),
+        None => Err(invalid(sexp.offset(), "Expected a fact like `(fever)`")),
+    }
+}
+
+// regex: \(name\)|\(not pattern\)|\((and|or) pattern+\)
+fn pattern<'a>(sexp: &Sexp<'a>) -> Result<Pattern<'a>, Invalid> {
+    let (offset, elements) = match sexp {
+        Sexp::List(offset, elements) => (*offset, elements),
+        Sexp::Atom(offset, atom) if atom.starts_with('?') || *atom == "<-" => {
+            return Err(invalid(
+                *offset,
+                "Variables and fact addresses are not supported",
+            ))
+        }
+        Sexp::Atom(offset, _) => return Err(invalid(*offset, "Expected a pattern like `(fever)`")),
+    };
+    let operands = || -> Result<Vec<Pattern<'a>>, Invalid> {
+        if elements.len() < 2 {
+            return Err(invalid(offset, "Expected at least one pattern"));
+        }
+        elements[1..].iter().map(pattern).collect()
+    };
+    match elements.first().and_then(Sexp::atom) {
+        Some("not") => {
+            let mut operands = operands()?;
+            if operands.len() > 1 {
+                return Err(invalid(elements[2].offset(), "`not` has a single pattern"));
+            }
+            Ok(Pattern::Not(Box::new(operands.remove(0))))
+        }
+        Some("and") => Ok(Pattern::And(operands()?)),
+        Some("or") => Ok(Pattern::Or(operands()?)),
+        Some(element @ ("test" | "exists" | "forall" | "logical" | "declare")) => Err(invalid(
+            elements[0].offset(),
+            &format!("Unsupported conditional element `{}`", element),
+        )),
+        _ => Ok(Pattern::Fact(fact(sexp)?)),
+    }
+}
+
+// regex: \(assert fact+\)
+fn action<'a>(sexp: &Sexp<'a>) -> Result<Vec<&'a str>, Invalid> {
+    match sexp {
+        Sexp::List(offset, elements) => match elements.first().and_then(Sexp::atom) {
+            Some("assert") if elements.len() > 1 => elements[1..].iter().map(fact).collect(),
+            Some("assert") => Err(invalid(*offset, "Expected a fact to assert")),
+            Some(action) => Err(invalid(
+                elements[0].offset(),
+                &format!(
+                    "Unsupported action `{}`, only `assert` is supported",
+                    action
+                ),
+            )),
+            None => Err(invalid(*offset, "Expected an action like `(assert (flu))`")),
+        },
+        Sexp::Atom(offset, _) => Err(invalid(*offset, "Expected an action like `(assert (flu))`")),
+    }
+}
+
+// Elements after the name and the optional comment of a construct
+fn body<'a, 'b>(elements: &'b [Sexp<'a>], offset: usize) -> Result<&'b [Sexp<'a>], Invalid> {
+    match elements.get(1).and_then(Sexp::atom) {
+        Some(name) if !name.starts_with('"') => {}
+        _ => return Err(invalid(offset, "Expected the name of the construct")),
+    }
+    match elements.get(2).and_then(Sexp::atom) {
+        Some(comment) if comment.starts_with('"') => Ok(&elements[3..]),
+        _ => Ok(&elements[2..]),
+    }
+}
+
+// regex: \(defrule name comment? pattern* => action*\)
+fn defrule<'a>(elements: &[Sexp<'a>], offset: usize) -> Result<Rule<'a>, Invalid> {
+    let body = body(elements, offset)?;
+    let arrow = match body.iter().position(|element| element.atom() == Some("=>")) {
+        Some(arrow) => arrow,
+        None => {
+            return Err(invalid(
+                offset,
+                "Missing `=>` between the patterns and the actions",
+            ))
+        }
+    };
+    let patterns = body[..arrow]
+        .iter()
+        .map(pattern)
+        .collect::<Result<Vec<Pattern>, Invalid>>()?;
+    let mut asserted = vec![];
+    for sexp in body[arrow + 1..].iter() {
+        asserted.extend(action(sexp)?);
+    }
+    Ok(Rule {
+        name: elements[1].atom().unwrap(),
+        patterns,
+        asserted,
+    })
+}
+
+fn names<'a>(pattern: &Pattern<'a>, names: &mut Vec<&'a str>) {
+    match pattern {
+        Pattern::Fact(name) => names.push(name),
+        Pattern::Not(pattern) => self::names(pattern, names),
+        Pattern::And(patterns) | Pattern::Or(patterns) => patterns
+            .iter()
+            .for_each(|pattern| self::names(pattern, names)),
+    }
+}
+
+fn binary(operator: Operator, patterns: &[Pattern], symbols: &HashMap<String, char>) -> Expression {
+    patterns
+        .iter()
+        .map(|pattern| expression(pattern, symbols))
+        .reduce(|left, right| Expression::Binary(operator, Box::new(left), Box::new(right)))
+        .unwrap()
+}
+
+fn expression(pattern: &Pattern, symbols: &HashMap<String, char>) -> Expression {
+    match pattern {
+        Pattern::Fact(name) => Expression::Fact(symbols[*name]),
+        Pattern::Not(pattern) => Expression::Not(Box::new(expression(pattern, symbols))),
+        Pattern::And(patterns) => {
+            Expression::Group(Box::new(binary(Operator::And, patterns, symbols)))
+        }
+        Pattern::Or(patterns) => {
+            Expression::Group(Box::new(binary(Operator::Or, patterns, symbols)))
+        }
+    }
+}
+
+// Load the `defrule` and `deffacts` constructs of a CLIPS file, each rule concludes the facts it asserts
+// and the asserted facts are queried
+// -- the facts are ordered facts with a single symbol and the patterns are joined by `and`, `or` and `not`,
+// -- every construct is checked and all errors are kept in `errors`
+pub fn parse_clips(input: &mut Input, content: &str) -> Result<(), Error> {
+    let errors = input.errors.len();
+    let error = |(offset, message): Invalid| {
+        Error::new(ErrorKind::InvalidClips, &message).at_offset(content, offset)
+    };
+    let forms = match tokens(content).and_then(|tokens| sexps(&tokens)) {
+        Ok(forms) => forms,
+        Err(invalid) => {
+            let error = error(invalid);
+            input.errors.push(error.clone());
+            return Err(error);
+        }
+    };
+
+    let mut facts: Vec<&str> = vec![];
+    let mut rules: Vec<Rule> = vec![];
+    for form in forms.iter() {
+        let (offset, elements) = match form {
+            Sexp::List(offset, elements) => (*offset, elements),
+            Sexp::Atom(offset, _) => {
+                input.errors.push(error(invalid(
+                    *offset,
+                    "Expected a construct like `(defrule ...)`",
+                )));
+                continue;
+            }
+        };
+        let result = match elements.first().and_then(Sexp::atom) {
+            Some("deffacts") => body(elements, offset).and_then(|body| {
+                body.iter()
+                    .map(fact)
+                    .collect::<Result<Vec<&str>, Invalid>>()
+                    .map(|body| facts.extend(body))
+            }),
+            Some("defrule") => defrule(elements, offset).map(|rule| rules.push(rule)),
+            Some(construct) => Err(invalid(
+                elements[0].offset(),
+                &format!(
+                    "Unsupported construct `{}`, expected `defrule` or `deffacts`",
+                    construct
+                ),
+            )),
+            None => Err(invalid(offset, "Expected a construct like `(defrule ...)`")),
+        };
+        if let Err(invalid) = result {
+            input.errors.push(error(invalid));
+        }
+    }
+    if input.errors.len() > errors {
+        return Err(input.errors[errors].clone());
+    }
+
+    // The names in order of appearance, with the offset of their first fact
+    let mut all: Vec<&str> = facts.clone();
+    for rule in rules.iter() {
+        rule.patterns
+            .iter()
+            .for_each(|pattern| names(pattern, &mut all));
+        all.extend(rule.asserted.iter());
+    }
+    let mut named: Vec<(usize, &str)> = vec![];
+    for name in all {
+        if !named.iter().any(|(_, other)| *other == name) {
+            named.push((name.as_ptr() as usize - content.as_ptr() as usize, name));
+        }
+    }
+    let symbols = match input.name_facts(&named, "names") {
+        Ok(symbols) => symbols,
+        Err(invalid) => {
+            let error = error(invalid);
+            input.errors.push(error.clone());
+            return Err(error);
+        }
+    };
+
+    let mut initial_facts: Vec<char> = vec![];
+    let mut initial = |symbol: char| {
+        if !initial_facts.contains(&symbol) {
+            initial_facts.push(symbol);
+        }
+    };
+    facts.iter().for_each(|name| initial(symbols[*name]));
+    let mut queries: Vec<char> = vec![];
+    for rule in rules.iter() {
+        let asserted: Vec<char> = rule.asserted.iter().map(|name| symbols[*name]).collect();
+        for symbol in asserted.iter() {
+            if !queries.contains(symbol) {
+                queries.push(*symbol);
+            }
+        }
+        if rule.patterns.is_empty() {
+            asserted.into_iter().for_each(&mut initial);
+            continue;
+        }
+        if asserted.is_empty() {
+            continue;
+        }
+        let premise = binary(Operator::And, &rule.patterns, &symbols);
+        let conclusion = asserted
+            .iter()
+            .map(|symbol| Expression::Fact(*symbol))
+            .reduce(|left, right| {
+                Expression::Binary(Operator::And, Box::new(left), Box::new(right))
+            })
+            .unwrap();
+        let node = input.expression_rule(Operator::Implies, &premise, &conclusion);
+        RefCell::borrow_mut(&node).label = Some(rule.name.to_string());
+        input.add_rule(node);
+    }
+
+    let mut result = Ok(());
+    if !initial_facts.is_empty() {
+        let initial_facts: String = initial_facts.iter().collect();
+        result = input.parse_initial_facts(&format!("={}", initial_facts));
+    }
+    if queries.is_empty() {
+        queries = initial_facts;
+    }
+    if result.is_ok() && !queries.is_empty() {
+        let queries: String = queries.iter().collect();
+        result = input.parse_queries(&format!("?{}", queries));
+    }
+    if let Err(error) = &result {
+        input.errors.push(error.clone());
+    }
+    input.reset();
+    result
+}
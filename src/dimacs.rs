@@ -0,0 +1,361 @@
+use crate::{
+    error::{Error, ErrorKind},
+    input::Input,
+    lint::LintKind,
+    node::{Node, Operator},
+    parser::Expression,
+};
+use std::{cell::RefCell, collections::HashMap};
+
+// Clauses of the Tseitin transformation, each subexpression has its own variable
+struct Tseitin {
+    variables: i64,
+    clauses: Vec<Vec<i64>>,
+}
+
+impl Tseitin {
+    // New variable for a subexpression, with the clauses that define it
+    fn gate(&mut self, clauses: impl Fn(i64) -> Vec<Vec<i64>>) -> i64 {
+        self.variables += 1;
+        self.clauses.extend(clauses(self.variables));
+        self.variables
+    }
+
+    // Literal equivalent to the node
+    fn literal(&mut self, node: &Node, variables: &HashMap<char, i64>) -> i64 {
+        if let Some(fact) = &node.fact {
+            let variable = variables[&RefCell::borrow(fact).repr];
+            return if node.operator_eq(&Operator::Not) {
+                -variable
+            } else {
+                variable
+            };
+        }
+        let a = self.literal(&RefCell::borrow(node.left.as_ref().unwrap()), variables);
+        let operator = match node.operator {
+            None => return a,
+            Some(Operator::Not) => return -a,
+            Some(operator) => operator,
+        };
+        let b = self.literal(&RefCell::borrow(node.right.as_ref().unwrap()), variables);
+        match operator {
+            Operator::And => self.gate(|g| vec![vec![-g, a], vec![-g, b], vec![g, -a, -b]]),
+            Operator::Or => self.gate(|g| vec![vec![g, -a], vec![g, -b], vec![-g, a, b]]),
+            Operator::Xor => self.gate(|g| {
+                vec![
+                    vec![-g, a, b],
+                    vec![-g, -a, -b],
+                    vec![g, -a, b],
+                    vec![g, a, -b],
+                ]
+            }),
+            Operator::Nand => self.gate(|g| vec![vec![g, a], vec![g, b], vec![-g, -a, -b]]),
+            Operator::Nor => self.gate(|g| vec![vec![-g, -a], vec![-g, -b], vec![g, a, b]]),
+            Operator::Implies => self.gate(|g| vec![vec![-g, -a, b], vec![g, a], vec![g, -b]]),
+            Operator::IfAndOnlyIf | Operator::Xnor => self.gate(|g| {
+                vec![
+                    vec![-g, -a, b],
+                    vec![-g, a, -b],
+                    vec![g, a, b],
+                    vec![g, -a, -b],
+                ]
+            }),
+            Operator::Not => unreachable!(),
+        }
+    }
+}
+
+// CNF of the rules and the initial facts, the facts are the first variables
+// -- the facts that are not initial facts are free, unlike in the closed world of the engine
+pub fn to_dimacs(input: &Input) -> String {
+    let mut symbols: Vec<&char> = input.facts.keys().collect();
+    symbols.sort_unstable();
+    let variables: HashMap<char, i64> = symbols
+        .iter()
+        .enumerate()
+        .map(|(index, symbol)| (**symbol, index as i64 + 1))
+        .collect();
+    let mut tseitin = Tseitin {
+        variables: variables.len() as i64,
+        clauses: vec![],
+    };
+    for rule in input.rules.iter() {
+        let rule = RefCell::borrow(rule);
+        let a = tseitin.literal(&RefCell::borrow(rule.left.as_ref().unwrap()), &variables);
+        let b = tseitin.literal(&RefCell::borrow(rule.right.as_ref().unwrap()), &variables);
+        tseitin.clauses.push(vec![-a, b]);
+        if rule.operator_eq(&Operator::IfAndOnlyIf) {
+            tseitin.clauses.push(vec![a, -b]);
+        }
+    }
+    for symbol in input.initial_facts.iter() {
+        tseitin.clauses.push(vec![variables[symbol]]);
+    }
+    for (symbol, value) in input.explicit_facts.iter() {
+        if value.is_false() {
+            tseitin.clauses.push(vec![-variables[symbol]]);
+        }
+    }
+
+    let mut dimacs = String::from("c expert-system knowledge base\n");
+    dimacs.push_str(&format!(
+        "c variables 1 to {} are facts, the others are subexpressions\n",
+        variables.len()
+    ));
+    for symbol in symbols {
+        let fact = RefCell::borrow(input.facts.get(symbol).unwrap());
+        match &fact.description {
+            Some(description) => dimacs.push_str(&format!(
+                "c {} {} {}\n",
+                variables[symbol], symbol, description
+            )),
+            None => dimacs.push_str(&format!("c {} {}\n", variables[symbol], symbol)),
+        }
+    }
+    dimacs.push_str(&format!(
+        "p cnf {} {}\n",
+        tseitin.variables,
+        tseitin.clauses.len()
+    ));
+    for clause in tseitin.clauses.iter() {
+        let literals: Vec<String> = clause.iter().map(i64::to_string).collect();
+        dimacs.push_str(&format!("{} 0\n", literals.join(" ")));
+    }
+    dimacs
+}
+
+fn invalid(message: &str, line: usize, source: &str, column: usize) -> Error {
+    Error::new(ErrorKind::InvalidDimacs, message)
+        .at_column(column)
+        .at_line(line, source, 0)
+}
+
+// Variable name from a comment of the header, like `c 1 A description`
+fn variable_name(line: &str) -> Option<(usize, char, Option<&str>)> {
+    let mut parts = line[1..].trim().splitn(3, ' ');
+    let variable = parts.next()?.parse::<usize>().ok()?;
+    let mut symbol = parts.next()?.chars();
+    match (symbol.next(), symbol.next()) {
+        (Some(symbol), None) if symbol.is_ascii_uppercase() => {
+            Some((variable, symbol, parts.next().map(str::trim)))
+        }
+        _ => None,
+    }
+}
+
+fn literal_expression(literal: i64, symbols: &HashMap<usize, char>, negated: bool) -> Expression {
+    let fact = Expression::Fact(symbols[&(literal.unsigned_abs() as usize)]);
+    if (literal < 0) != negated {
+        Expression::Not(Box::new(fact))
+    } else {
+        fact
+    }
+}
+
+// A clause concludes its last positive literal when the others are false,
+// a clause without positive literals is a constraint that concludes a negation
+// -- a unit clause is an initial fact, true if positive and explicitly false if negative
+fn add_clause(input: &mut Input, clause: &[i64], symbols: &HashMap<usize, char>) -> Option<i64> {
+    let mut literals: Vec<i64> = vec![];
+    for literal in clause {
+        if literals.contains(&-literal) {
+            return None;
+        }
+        if !literals.contains(literal) {
+            literals.push(*literal);
+        }
+    }
+    let position = literals
+        .iter()
+        .rposition(|literal| *literal > 0)
+        .unwrap_or(literals.len() - 1);
+    let conclusion = literals.remove(position);
+    if literals.is_empty() {
+        return Some(conclusion);
+    }
+    let premise = literals
+        .iter()
+        .map(|literal| literal_expression(*literal, symbols, true))
+        .reduce(|left, right| Expression::Binary(Operator::And, Box::new(left), Box::new(right)))
+        .unwrap();
+    let conclusion = literal_expression(conclusion, symbols, false);
+    let rule = input.expression_rule(Operator::Implies, &premise, &conclusion);
+    input.add_rule(rule);
+    None
+}
+
+// Each variable is a fact, so a DIMACS file can't have more variables than uppercase letters
+pub const MAX_VARIABLES: usize = 26;
+
+// Load a DIMACS CNF file as rules, the unit clauses are the initial facts and every fact is queried
+// -- the variables are named by the comments of the header, or by the unused letters in order
+pub fn parse_dimacs(input: &mut Input, content: &str) -> Result<(), Error> {
+    let result = dimacs(input, content);
+    if let Err(error) = &result {
+        input.errors.push(error.clone());
+    }
+    result
+}
+
+fn dimacs(input: &mut Input, content: &str) -> Result<(), Error> {
+    let mut names: HashMap<usize, (char, Option<String>)> = HashMap::new();
+    let mut header: Option<(usize, usize, usize)> = None;
+    let mut clauses: Vec<Vec<i64>> = vec![];
+    let mut clause: Vec<i64> = vec![];
+
+    for (index, source) in content.lines().enumerate() {
+        let line = source.trim();
+        let line_number = index + 1;
+        if line.starts_with('%') {
+            break;
+        }
+        if line.is_empty() {
+            continue;
+        }
+        if line.starts_with('c') {
+            if let Some((variable, symbol, description)) = variable_name(line) {
+                names.insert(variable, (symbol, description.map(str::to_string)));
+            }
+            continue;
+        }
+        if line.starts_with('p') {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if let ["p", "cnf", variables, count] = parts[..] {
+                if let (None, Ok(variables), Ok(count)) = (header, variables.parse(), count.parse())
+                {
+                    header = Some((variables, count, line_number));
+                    continue;
+                }
+            }
+            let message = if header.is_some() {
+                "Duplicate problem line"
+            } else {
+                "Invalid problem line, expected `p cnf <variables> <clauses>`"
+            };
+            return Err(invalid(message, line_number, source, 1));
+        }
+        let variables = match header {
+            Some((variables, _, _)) => variables,
+            None => {
+                return Err(invalid(
+                    "Clause before the problem line `p cnf <variables> <clauses>`",
+                    line_number,
+                    source,
+                    1,
+                ))
+            }
+        };
+        for token in line.split_whitespace() {
+            let column = source[..(token.as_ptr() as usize - source.as_ptr() as usize)]
+                .chars()
+                .count()
+                + 1;
+            let literal = match token.parse::<i64>() {
+                Ok(literal) => literal,
+                Err(_) => {
+                    return Err(invalid(
+                        &format!("Invalid literal `{}`", token),
+                        line_number,
+                        source,
+                        column,
+                    ))
+                }
+            };
+            if literal == 0 {
+                clauses.push(std::mem::take(&mut clause));
+            } else if literal.unsigned_abs() as usize > variables {
+                return Err(invalid(
+                    &format!("Literal {} is not a variable of the problem line", literal),
+                    line_number,
+                    source,
+                    column,
+                ));
+            } else {
+                clause.push(literal);
+            }
+        }
+    }
+    // The last clause can end without 0
+    if !clause.is_empty() {
+        clauses.push(clause);
+    }
+    let (variables, count, line) = match header {
+        Some(header) => header,
+        None => {
+            return Err(Error::new(
+                ErrorKind::InvalidDimacs,
+                "Missing problem line `p cnf <variables> <clauses>`",
+            ))
+        }
+    };
+    if clauses.len() != count {
+        input.warn(
+            LintKind::InvalidClauses,
+            format!("Expected {} clauses, found {}", count, clauses.len()),
+        );
+    }
+
+    if variables > MAX_VARIABLES {
+        let source = content.lines().nth(line - 1).unwrap();
+        return Err(invalid(
+            &format!(
+                "Too many variables ({}), the limit is {} as facts are the uppercase letters",
+                variables, MAX_VARIABLES
+            ),
+            line,
+            source,
+            1,
+        ));
+    }
+
+    // Name each variable, the named variables keep their letter
+    // -- there are always enough unused letters as there are at most 26 variables
+    let mut unused = ('A'..='Z').filter(|symbol| !names.values().any(|(name, _)| name == symbol));
+    let mut symbols: HashMap<usize, char> = HashMap::new();
+    for variable in 1..=variables {
+        let symbol = match names.get(&variable) {
+            Some((symbol, _)) => *symbol,
+            None => unused.next().unwrap(),
+        };
+        symbols.insert(variable, symbol);
+        let fact = input.get_or_insert_fact(&symbol);
+        if let Some((_, Some(description))) = names.get(&variable) {
+            RefCell::borrow_mut(&fact).description = Some(description.clone());
+        }
+    }
+    let mut initial_facts: Vec<i64> = vec![];
+    for clause in clauses.iter().filter(|clause| !clause.is_empty()) {
+        if let Some(literal) = add_clause(input, clause, &symbols) {
+            if !initial_facts.contains(&literal) {
+                initial_facts.push(literal);
+            }
+        }
+    }
+    if clauses.iter().any(|clause| clause.is_empty()) {
+        input.warn(
+            LintKind::InvalidClauses,
+            "Empty clause, the problem is unsatisfiable".to_string(),
+        );
+    }
+
+    if !initial_facts.is_empty() {
+        let initial_facts: String = initial_facts
+            .into_iter()
+            .map(|literal| {
+                let symbol = symbols[&(literal.unsigned_abs() as usize)];
+                if literal < 0 {
+                    format!("!{}", symbol)
+                } else {
+                    symbol.to_string()
+                }
+            })
+            .collect();
+        input.parse_initial_facts(&format!("={}", initial_facts))?;
+    }
+    let queries: String = (1..=variables).map(|variable| symbols[&variable]).collect();
+    if !queries.is_empty() {
+        input.parse_queries(&format!("?{}", queries))?;
+    }
+    input.reset();
+    Ok(())
+}
@@ -0,0 +1,98 @@
+use crate::{input::Input, node::Resolve};
+use std::{cell::RefCell, mem};
+
+// Number of shared facts above which the sets of initial facts are too many to check,
+// the same limit as `MAX_EQUIVALENCE_FACTS`
+pub const MAX_SHARED_FACTS: usize = 12;
+
+#[derive(Debug)]
+pub struct Counterexample {
+    pub initial_facts: Vec<char>,
+    pub query: char,
+    pub first: Result<Resolve, String>,
+    pub second: Result<Resolve, String>,
+}
+
+pub fn shared_facts(first: &Input, second: &Input) -> Vec<char> {
+    let mut facts: Vec<char> = first
+        .facts
+        .keys()
+        .filter(|fact| second.facts.contains_key(fact))
+        .cloned()
+        .collect();
+    facts.sort_unstable();
+    facts
+}
+
+// Resolve a single query from a clean state with the given initial facts
+pub fn resolve_with(
+    input: &mut Input,
+    initial_facts: &[char],
+    query: &char,
+) -> Result<Resolve, String> {
+    input.initial_facts = initial_facts.to_vec();
+    input.reset();
+    let fact = RefCell::borrow(input.facts.get(query).unwrap().as_ref());
+    let mut path: Vec<String> = vec![];
+    let result = fact.resolve(&mut path);
+    if result.is_err() {
+        fact.cleanup();
+    }
+    result.map_err(|error| error.to_string())
+}
+
+// Two inputs are equivalent if every query on a shared fact gives the same answer
+// -- for every set of shared initial facts, the smallest sets are checked first
+pub fn check_equivalence(
+    first: &mut Input,
+    second: &mut Input,
+) -> Result<Option<Counterexample>, String> {
+    let facts = shared_facts(first, second);
+    if facts.len() > MAX_SHARED_FACTS {
+        return Err(format!(
+            "Too many shared facts to check ({}), the limit is {}",
+            facts.len(),
+            MAX_SHARED_FACTS
+        ));
+    }
+    // The explicit facts are not given while the sets of initial facts are checked
+    let first_initial_facts = first.initial_facts.clone();
+    let second_initial_facts = second.initial_facts.clone();
+    let first_explicit_facts = mem::take(&mut first.explicit_facts);
+    let second_explicit_facts = mem::take(&mut second.explicit_facts);
+
+    let mut sets: Vec<usize> = (0..(1_usize << facts.len())).collect();
+    sets.sort_by_key(|set| set.count_ones());
+    let mut counterexample: Option<Counterexample> = None;
+    'sets: for set in sets {
+        let initial_facts: Vec<char> = facts
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| set & (1 << index) != 0)
+            .map(|(_, fact)| *fact)
+            .collect();
+        for query in facts.iter() {
+            let first_result = resolve_with(first, &initial_facts, query);
+            let second_result = resolve_with(second, &initial_facts, query);
+            // Different errors are different answers
+            if first_result != second_result {
+                counterexample = Some(Counterexample {
+                    initial_facts,
+                    query: *query,
+                    first: first_result,
+                    second: second_result,
+                });
+                break 'sets;
+            }
+        }
+    }
+
+    // Restore the original state of both inputs
+    first.initial_facts = first_initial_facts;
+    first.explicit_facts = first_explicit_facts;
+    first.reset();
+    second.initial_facts = second_initial_facts;
+    second.explicit_facts = second_explicit_facts;
+    second.reset();
+    Ok(counterexample)
+}
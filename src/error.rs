@@ -0,0 +1,185 @@
+use std::fmt;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ErrorKind {
+    InvalidCharacter,
+    InvalidContext,
+    InvalidNegation,
+    InvalidOperator,
+    MissingOperator,
+    IncompleteExpression,
+    MissingLeftSide,
+    MissingRightSide,
+    InvalidRule,
+    InvalidInitialFacts,
+    InvalidQueries,
+    MisplacedLine,
+    MissingInitialFacts,
+    MissingQueries,
+    InvalidDirective,
+    Io,
+    IncludeCycle,
+    MissingExpectations,
+    InvalidJson,
+    InvalidDimacs,
+    InvalidProlog,
+    InvalidClips,
+    DeniedWarning,
+}
+
+impl ErrorKind {
+    pub fn code(&self) -> &'static str {
+        match self {
+            ErrorKind::InvalidCharacter => "E0001",
+            ErrorKind::InvalidContext => "E0002",
+            ErrorKind::InvalidNegation => "E0003",
+            ErrorKind::InvalidOperator => "E0004",
+            ErrorKind::MissingOperator => "E0005",
+            ErrorKind::IncompleteExpression => "E0006",
+            ErrorKind::MissingLeftSide => "E0007",
+            ErrorKind::MissingRightSide => "E0008",
+            ErrorKind::InvalidRule => "E0009",
+            ErrorKind::InvalidInitialFacts => "E0010",
+            ErrorKind::InvalidQueries => "E0011",
+            ErrorKind::MisplacedLine => "E0012",
+            ErrorKind::MissingInitialFacts => "E0013",
+            ErrorKind::MissingQueries => "E0014",
+            ErrorKind::InvalidDirective => "E0015",
+            ErrorKind::Io => "E0016",
+            ErrorKind::IncludeCycle => "E0017",
+            ErrorKind::MissingExpectations => "E0018",
+            ErrorKind::InvalidJson => "E0019",
+            ErrorKind::InvalidDimacs => "E0020",
+            ErrorKind::InvalidProlog => "E0021",
+            ErrorKind::InvalidClips => "E0022",
+            ErrorKind::DeniedWarning => "E0023",
+        }
+    }
+}
+
+// Line and column are 1-based, the column is in characters in the source line
+#[derive(Clone, Debug, PartialEq)]
+pub struct Error {
+    pub kind: ErrorKind,
+    pub message: String,
+    pub file: Option<String>,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub source: Option<String>,
+}
+
+impl Error {
+    pub fn new(kind: ErrorKind, message: &str) -> Error {
+        Error {
+            kind,
+            message: message.to_string(),
+            file: None,
+            line: None,
+            column: None,
+            source: None,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        self.kind.code()
+    }
+
+    pub fn at_column(mut self, column: usize) -> Error {
+        self.column = Some(column);
+        self
+    }
+
+    pub fn with_source(mut self, source: &str) -> Error {
+        self.source = Some(source.to_string());
+        self
+    }
+
+    // Move the error to a line of a file, the column is shifted by the removed indentation
+    pub fn at_line(mut self, line: usize, source: &str, indentation: usize) -> Error {
+        self.line = Some(line);
+        self.source = Some(source.to_string());
+        self.column = self.column.map(|column| column + indentation);
+        self
+    }
+
+    // Move the error to a byte offset of a content, for the formats that are not parsed by line
+    pub fn at_offset(self, content: &str, offset: usize) -> Error {
+        let line = content[..offset].matches('\n').count() + 1;
+        let line_start = content[..offset].rfind('\n').map_or(0, |index| index + 1);
+        let column = content[line_start..offset].chars().count() + 1;
+        let source = content[line_start..].lines().next().unwrap_or("");
+        self.at_column(column).at_line(line, source, 0)
+    }
+
+    pub fn in_file(mut self, file: &str) -> Error {
+        self.file = Some(file.to_string());
+        self
+    }
+}
+
+// Location of a rule or an expression in its file, or in the lines of the interactive mode
+// -- the columns are in characters like the errors, the end column is after the last character
+#[derive(Clone, Debug, PartialEq)]
+pub struct Span {
+    pub file: Option<String>,
+    pub line: usize,
+    pub column: usize,
+    pub end: usize,
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.file {
+            Some(file) => write!(f, "{}:{}:{}", file, self.line, self.column),
+            None => write!(f, "{}:{}", self.line, self.column),
+        }
+    }
+}
+
+// Rendered like rustc diagnostics, with a caret under the column in the source line
+// error[E0005]: Missing operator between symbols
+//  --> file.txt:2:3
+//   |
+// 2 | A B => C
+//   |   ^
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "error[{}]: {}", self.code(), self.message)?;
+        let gutter = match self.line {
+            Some(line) => line.to_string(),
+            None => String::new(),
+        };
+        let location = match (&self.file, self.line, self.column) {
+            (Some(file), Some(line), Some(column)) => Some(format!("{}:{}:{}", file, line, column)),
+            (Some(file), Some(line), None) => Some(format!("{}:{}", file, line)),
+            (Some(file), None, _) => Some(file.clone()),
+            (None, Some(line), Some(column)) => Some(format!("{}:{}", line, column)),
+            (None, Some(line), None) => Some(line.to_string()),
+            (None, None, _) => None,
+        };
+        if let Some(location) = location {
+            write!(
+                f,
+                "\n{:width$}--> {}",
+                "",
+                location,
+                width = gutter.len().max(1)
+            )?;
+        }
+        if let Some(source) = &self.source {
+            write!(f, "\n{:width$} |", "", width = gutter.len())?;
+            write!(f, "\n{} | {}", gutter, source)?;
+            if let Some(column) = self.column {
+                write!(
+                    f,
+                    "\n{:width$} | {:column$}^",
+                    "",
+                    "",
+                    width = gutter.len(),
+                    column = column - 1
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,372 @@
+use crate::{
+    error::Error,
+    input::{self, Input},
+    node::{Node, Operator},
+    parser::{find_binary_operator, Associativity, BinaryOperator},
+};
+use std::{cell::RefCell, mem};
+
+// Sections of a file in their canonical order
+// -- the directives and the rules are the only lines that can be moved
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Section {
+    Header,
+    Include,
+    Description,
+    Ask,
+    Rule,
+    Queries,
+    Scenario,
+    Footer,
+}
+
+// A blank line has no code and no comment
+#[derive(Debug, Default)]
+struct Line {
+    code: Option<String>,
+    comment: Option<String>,
+    // Comment aligned with the comment of the previous line
+    continuation: bool,
+}
+
+// A line of code with the comments above it, or only comments
+#[derive(Debug)]
+struct Chunk {
+    section: Section,
+    blank_before: bool,
+    has_code: bool,
+    opens_scenario: bool,
+    lines: Vec<Line>,
+}
+
+// Split the comment of a line, a `#` in a quoted string is not a comment
+fn split_comment(line: &str) -> (&str, Option<&str>) {
+    let mut quoted = false;
+    for (i, c) in line.char_indices() {
+        if c == '"' {
+            quoted = !quoted;
+        } else if c == '#' && !quoted {
+            return (line[..i].trim_end(), Some(line[i..].trim_end()));
+        }
+    }
+    (line.trim_end(), None)
+}
+
+fn format_operand(node: &Node) -> (String, Option<BinaryOperator>) {
+    if let Some(fact) = &node.fact {
+        let repr = RefCell::borrow(fact).repr;
+        if node.operator_eq(&Operator::Not) {
+            return (format!("!{}", repr), None);
+        }
+        return (repr.to_string(), None);
+    }
+    let left = || format_operand(&RefCell::borrow(node.left.as_ref().unwrap()));
+    let right = || format_operand(&RefCell::borrow(node.right.as_ref().unwrap()));
+    match node.operator {
+        None => left(),
+        Some(Operator::Not) => match left() {
+            (operand, Some(_)) => (format!("!({})", operand), None),
+            (operand, None) => (format!("!{}", operand), None),
+        },
+        Some(operator) => {
+            let binary_operator = find_binary_operator(&operator).unwrap();
+            let precedence = binary_operator.precedence;
+            // Operands with a lower precedence, or the same precedence on the side
+            // that is not the associativity of the operator, need a context
+            let needs_context = |operand: &Option<BinaryOperator>, associativity| match operand {
+                Some(operand) => {
+                    operand.precedence < precedence
+                        || (operand.precedence == precedence
+                            && binary_operator.associativity != associativity)
+                }
+                None => false,
+            };
+            let (left, left_operator) = left();
+            let (right, right_operator) = right();
+            let left = if needs_context(&left_operator, Associativity::Left) {
+                format!("({})", left)
+            } else {
+                left
+            };
+            let right = if needs_context(&right_operator, Associativity::Right) {
+                format!("({})", right)
+            } else {
+                right
+            };
+            (
+                format!("{} {} {}", left, binary_operator.symbol, right),
+                Some(binary_operator),
+            )
+        }
+    }
+}
+
+// Canonical representation of a node, with only the required contexts
+// -- a rule or a query is split at its first implication, a nested implication always has a context
+pub fn format_node(node: &Node) -> String {
+    let context = |(operand, operator): (String, Option<BinaryOperator>)| match operator {
+        Some(operator)
+            if matches!(operator.operator, Operator::Implies | Operator::IfAndOnlyIf) =>
+        {
+            format!("({})", operand)
+        }
+        _ => operand,
+    };
+    if node.is_implication() && !node.has_fact() {
+        let left = format_operand(&RefCell::borrow(node.left.as_ref().unwrap()));
+        let right = format_operand(&RefCell::borrow(node.right.as_ref().unwrap()));
+        let symbol = find_binary_operator(&node.operator.unwrap())
+            .unwrap()
+            .symbol;
+        let rule = format!("{} {} {}", context(left), symbol, context(right));
+        return match &node.label {
+            Some(label) => format!("[{}] {}", label, rule),
+            None => rule,
+        };
+    }
+    context(format_operand(node))
+}
+
+fn format_queries(input: &mut Input, code: &str) -> Result<String, Error> {
+    input.parse_queries(code)?;
+    let expressions = mem::take(&mut input.query_expressions);
+    let mut expressions = expressions.iter();
+    let (_, queries) = input::queries(code).unwrap();
+    let mut formatted: Vec<String> = vec![];
+    for query in queries {
+        if input::is_query_expression(query) {
+            let expression = expressions.next().unwrap();
+            formatted.push(format_node(&RefCell::borrow(expression)));
+        } else {
+            formatted.push(query.chars().filter(|c| !c.is_whitespace()).collect());
+        }
+    }
+    Ok(format!("?{}", formatted.join(", ")))
+}
+
+// Format the code of a line, the line is checked with the parser of the Input first
+fn format_code(input: &mut Input, code: &str, section: Section) -> Result<String, Error> {
+    if section == Section::Include {
+        let path = input::include_path(code)?;
+        return Ok(format!("include \"{}\"", path));
+    }
+    if section == Section::Description {
+        input.parse_description(code)?;
+        let (_, (symbol, description)) = input::description(code).unwrap();
+        return Ok(format!("@{} \"{}\"", symbol, description));
+    }
+    if section == Section::Ask {
+        input.parse_ask(code)?;
+        let (_, (symbol, prompt)) = input::ask(code).unwrap();
+        return Ok(match prompt {
+            Some(prompt) => format!("ask {} \"{}\"", symbol, prompt),
+            None => format!("ask {}", symbol),
+        });
+    }
+    if section == Section::Rule {
+        let rule = input.build_rule(code)?;
+        return Ok(format_node(&RefCell::borrow(&rule)));
+    }
+    if code.starts_with("scenario") {
+        input.parse_scenario(code)?;
+        let (_, name) = input::scenario(code).unwrap();
+        return Ok(format!("scenario \"{}\"", name));
+    }
+    if code.starts_with("expect") {
+        let expectations = input.parse_expectations(code)?;
+        let expectations: Vec<String> = expectations
+            .iter()
+            .map(|(symbol, expected)| format!("{} {}", symbol, expected))
+            .collect();
+        return Ok(format!("expect {}", expectations.join(", ")));
+    }
+    if code.starts_with('?') {
+        return format_queries(input, code);
+    }
+    input.parse_initial_facts(code)?;
+    let (_, initial_facts) = input::initial_facts(code).unwrap();
+    let initial_facts: String = initial_facts
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    Ok(format!("={}", initial_facts))
+}
+
+// Section of a line of code, the state is updated with the line
+fn code_section(code: &str, in_block: &mut bool, in_scenario: &mut bool) -> Section {
+    if code.starts_with("scenario") {
+        *in_scenario = true;
+    } else if code.starts_with('=') && !code.starts_with("=>") {
+        *in_block = true;
+    }
+    if *in_scenario {
+        Section::Scenario
+    } else if *in_block {
+        Section::Queries
+    } else if code.starts_with("include") {
+        Section::Include
+    } else if code.starts_with('@') {
+        Section::Description
+    } else if code.starts_with("ask") {
+        Section::Ask
+    } else {
+        Section::Rule
+    }
+}
+
+fn split_chunks(content: &str) -> Result<Vec<Chunk>, Error> {
+    let mut input = Input::new();
+    let mut chunks: Vec<Chunk> = vec![];
+    let mut comments: Vec<Line> = vec![];
+    let mut comments_blank_before = false;
+    let mut blank = false;
+    let (mut in_block, mut in_scenario) = (false, false);
+
+    for (line_number, source) in content.lines().enumerate() {
+        let line = source.trim();
+        let indentation = source.chars().take_while(|c| c.is_whitespace()).count();
+        if line.is_empty() {
+            // Comments just below a line of code and followed by a blank line are kept with it
+            if !comments.is_empty() && !comments_blank_before && !chunks.is_empty() {
+                chunks.last_mut().unwrap().lines.append(&mut comments);
+            } else if !comments.is_empty() {
+                chunks.push(Chunk {
+                    section: Section::Footer,
+                    blank_before: comments_blank_before,
+                    has_code: false,
+                    opens_scenario: false,
+                    lines: mem::take(&mut comments),
+                });
+            }
+            blank = true;
+            continue;
+        }
+        if line.starts_with('#') {
+            // An indented comment just below a commented line continues its comment
+            let previous = chunks.last().and_then(|chunk| chunk.lines.last());
+            if comments.is_empty()
+                && !blank
+                && indentation > 0
+                && previous.is_some_and(|previous| {
+                    previous.continuation || (previous.code.is_some() && previous.comment.is_some())
+                })
+            {
+                chunks.last_mut().unwrap().lines.push(Line {
+                    code: None,
+                    comment: Some(line.to_string()),
+                    continuation: true,
+                });
+                continue;
+            }
+            if comments.is_empty() {
+                comments_blank_before = blank;
+            }
+            comments.push(Line {
+                code: None,
+                comment: Some(line.to_string()),
+                continuation: false,
+            });
+            blank = false;
+            continue;
+        }
+
+        let (code, comment) = split_comment(line);
+        let section = code_section(code, &mut in_block, &mut in_scenario);
+        let code = format_code(&mut input, code, section)
+            .map_err(|error| error.at_line(line_number + 1, source, indentation))?;
+        let blank_before = if comments.is_empty() {
+            blank
+        } else {
+            comments_blank_before
+        };
+        let mut lines = mem::take(&mut comments);
+        lines.push(Line {
+            code: Some(code),
+            comment: comment.map(str::to_string),
+            continuation: false,
+        });
+        chunks.push(Chunk {
+            section,
+            blank_before,
+            has_code: true,
+            opens_scenario: line.starts_with("scenario"),
+            lines,
+        });
+        blank = false;
+    }
+    if !comments.is_empty() {
+        chunks.push(Chunk {
+            section: Section::Footer,
+            blank_before: comments_blank_before,
+            has_code: false,
+            opens_scenario: false,
+            lines: comments,
+        });
+    }
+
+    // Comments separated from the code are in the section of the next line of code
+    // -- the comments at the start of the file are the header
+    let mut next_section = Section::Footer;
+    for chunk in chunks.iter_mut().rev() {
+        if chunk.has_code {
+            next_section = chunk.section;
+        } else {
+            chunk.section = next_section;
+        }
+    }
+    if chunks.len() > 1 && !chunks[0].has_code && chunks[1].blank_before {
+        chunks[0].section = Section::Header;
+    }
+    Ok(chunks)
+}
+
+// Pad the code of each group of lines to align their comments
+fn align(lines: &[Line]) -> String {
+    let mut output = String::new();
+    for group in lines.split(|line| line.code.is_none() && line.comment.is_none()) {
+        let width = group
+            .iter()
+            .filter(|line| line.comment.is_some())
+            .filter_map(|line| line.code.as_ref())
+            .map(|code| code.chars().count())
+            .max();
+        for line in group {
+            match (&line.code, &line.comment, width) {
+                (Some(code), Some(comment), Some(width)) => {
+                    output.push_str(&format!("{:width$} {}", code, comment, width = width))
+                }
+                (Some(code), _, _) => output.push_str(code),
+                (None, Some(comment), Some(width)) if line.continuation => {
+                    output.push_str(&format!("{:width$} {}", "", comment, width = width))
+                }
+                (None, Some(comment), _) => output.push_str(comment),
+                (None, None, _) => {}
+            }
+            output.push('\n');
+        }
+        output.push('\n');
+    }
+    output.truncate(output.trim_end().len());
+    output.push('\n');
+    output
+}
+
+// Canonical form of a file, the result is parsed to the same knowledge base
+// -- the included files are not checked or formatted
+pub fn format_content(content: &str) -> Result<String, Error> {
+    let mut chunks = split_chunks(content)?;
+    chunks.sort_by_key(|chunk| chunk.section);
+
+    let mut lines: Vec<Line> = vec![];
+    let mut previous_section: Option<Section> = None;
+    for chunk in chunks {
+        if let Some(previous_section) = previous_section {
+            if chunk.blank_before || chunk.opens_scenario || chunk.section != previous_section {
+                lines.push(Line::default());
+            }
+        }
+        previous_section = Some(chunk.section);
+        lines.extend(chunk.lines);
+    }
+    Ok(align(&lines))
+}
@@ -0,0 +1,347 @@
+use crate::{
+    format::format_node,
+    input::Input,
+    json::{Proof, QueryResult},
+    node::{Node, Operator, Resolve},
+    parser::find_binary_operator,
+};
+use std::cell::RefCell;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Shape {
+    Fact,
+    Rule,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GraphNode {
+    pub id: String,
+    pub label: String,
+    pub shape: Shape,
+    pub initial: bool,
+    pub query: bool,
+    pub value: Option<Resolve>,
+}
+
+impl GraphNode {
+    fn new(id: &str, label: &str, shape: Shape) -> GraphNode {
+        GraphNode {
+            id: id.to_string(),
+            label: label.to_string(),
+            shape,
+            initial: false,
+            query: false,
+            value: None,
+        }
+    }
+}
+
+// Edge from a premise to a rule or from a rule to a conclusion,
+// the label is the operator that joins the fact in the rule
+#[derive(Clone, Debug, PartialEq)]
+pub struct Edge {
+    pub from: String,
+    pub to: String,
+    pub label: Option<&'static str>,
+    pub negated: bool,
+    // Both sides of an equivalence conclude each other
+    pub both: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Graph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<Edge>,
+}
+
+impl Graph {
+    // Edge of a proof, a rule that proves several facts has its premises only once
+    fn add_edge(&mut self, from: &str, to: &str) {
+        if !self
+            .edges
+            .iter()
+            .any(|edge| edge.from == from && edge.to == to)
+        {
+            self.edges.push(Edge {
+                from: from.to_string(),
+                to: to.to_string(),
+                label: None,
+                negated: false,
+                both: false,
+            });
+        }
+    }
+}
+
+// Each fact of a side of a rule, with the operator above it and if it's negated
+fn leaves(
+    node: &Node,
+    operator: Option<&'static str>,
+    negated: bool,
+    leaves: &mut Vec<(char, Option<&'static str>, bool)>,
+) {
+    if let Some(fact) = &node.fact {
+        let negated = negated != node.operator_eq(&Operator::Not);
+        leaves.push((RefCell::borrow(fact).repr, operator, negated));
+        return;
+    }
+    let left = RefCell::borrow(node.left.as_ref().unwrap());
+    match node.operator {
+        None => self::leaves(&left, operator, negated, leaves),
+        Some(Operator::Not) => self::leaves(&left, operator, !negated, leaves),
+        Some(binary) => {
+            let symbol = find_binary_operator(&binary).map(|operator| operator.symbol);
+            self::leaves(&left, symbol, negated, leaves);
+            let right = RefCell::borrow(node.right.as_ref().unwrap());
+            self::leaves(&right, symbol, negated, leaves);
+        }
+    }
+}
+
+fn fact_label(input: &Input, symbol: &char) -> String {
+    match input.facts.get(symbol) {
+        Some(fact) => RefCell::borrow(fact).subject(),
+        None => symbol.to_string(),
+    }
+}
+
+fn is_queried(input: &Input, symbol: &char) -> bool {
+    input.queries.contains(symbol)
+        || input
+            .query_expressions
+            .iter()
+            .any(|expression| RefCell::borrow(expression).contains_fact(symbol))
+}
+
+// Graph of all facts and rules, the initial and queried facts are highlighted
+pub fn rule_graph(input: &Input) -> Graph {
+    let mut graph = Graph::default();
+    let mut symbols: Vec<&char> = input.facts.keys().collect();
+    symbols.sort_unstable();
+    for symbol in symbols {
+        let mut node = GraphNode::new(&symbol.to_string(), &fact_label(input, symbol), Shape::Fact);
+        node.initial = input.initial_facts.contains(symbol);
+        node.query = is_queried(input, symbol);
+        graph.nodes.push(node);
+    }
+    for (index, rule) in input.rules.iter().enumerate() {
+        let id = format!("R{}", index + 1);
+        let rule = RefCell::borrow(rule);
+        graph
+            .nodes
+            .push(GraphNode::new(&id, &format_node(&rule), Shape::Rule));
+        let both = rule.operator_eq(&Operator::IfAndOnlyIf);
+        let (mut premises, mut conclusions) = (vec![], vec![]);
+        leaves(
+            &RefCell::borrow(rule.left.as_ref().unwrap()),
+            None,
+            false,
+            &mut premises,
+        );
+        leaves(
+            &RefCell::borrow(rule.right.as_ref().unwrap()),
+            None,
+            false,
+            &mut conclusions,
+        );
+        for (symbol, label, negated) in premises {
+            graph.edges.push(Edge {
+                from: symbol.to_string(),
+                to: id.clone(),
+                label,
+                negated,
+                both,
+            });
+        }
+        for (symbol, label, negated) in conclusions {
+            graph.edges.push(Edge {
+                from: id.clone(),
+                to: symbol.to_string(),
+                label,
+                negated,
+                both,
+            });
+        }
+    }
+    graph
+}
+
+fn add_proof(input: &Input, graph: &mut Graph, proof: &Proof) {
+    let id = proof.fact.to_string();
+    if graph.nodes.iter().any(|node| node.id == id) {
+        return;
+    }
+    let mut node = GraphNode::new(
+        &id,
+        &format!("{}: {}", fact_label(input, &proof.fact), proof.value),
+        Shape::Fact,
+    );
+    node.initial = input.initial_facts.contains(&proof.fact);
+    node.value = Some(proof.value);
+    graph.nodes.push(node);
+    for rule in proof.rules.iter() {
+        // The same rule can be in the proof of several facts
+        let rule_id = match graph
+            .nodes
+            .iter()
+            .find(|node| node.shape == Shape::Rule && node.label == rule.rule)
+        {
+            Some(node) => node.id.clone(),
+            None => {
+                let rule_id = format!(
+                    "R{}",
+                    graph
+                        .nodes
+                        .iter()
+                        .filter(|node| node.shape == Shape::Rule)
+                        .count()
+                        + 1
+                );
+                let mut node = GraphNode::new(&rule_id, &rule.rule, Shape::Rule);
+                node.value = Some(rule.premise);
+                graph.nodes.push(node);
+                rule_id
+            }
+        };
+        for premise in rule.facts.iter() {
+            graph.add_edge(&premise.fact.to_string(), &rule_id);
+            add_proof(input, graph, premise);
+        }
+        graph.add_edge(&rule_id, &id);
+    }
+}
+
+// Graph of the proof of a single query, each fact has the value found by the engine
+pub fn proof_graph(input: &Input, result: &QueryResult) -> Graph {
+    let mut graph = Graph::default();
+    for proof in result.proof.iter() {
+        add_proof(input, &mut graph, proof);
+    }
+    for node in graph.nodes.iter_mut() {
+        node.query = node.shape == Shape::Fact && result.query.contains(&node.id);
+    }
+    graph
+}
+
+fn value_color(value: Resolve) -> &'static str {
+    match value {
+        Resolve::True => "#b2ebf2",
+        Resolve::Ambiguous => "#e1bee7",
+        Resolve::False => "#fff9c4",
+    }
+}
+
+const INITIAL_COLOR: &str = "#c8e6c9";
+const QUERY_COLOR: &str = "#7b1fa2";
+
+// Initial facts are highlighted before their value
+fn fill(node: &GraphNode) -> Option<&'static str> {
+    if node.initial {
+        Some(INITIAL_COLOR)
+    } else {
+        node.value.map(value_color)
+    }
+}
+
+pub fn to_dot(graph: &Graph) -> String {
+    let mut dot = String::from("digraph rules {\n    rankdir=LR;\n");
+    for node in graph.nodes.iter() {
+        let mut attributes = vec![
+            format!("label=\"{}\"", node.label.replace('"', "\\\"")),
+            match node.shape {
+                Shape::Fact => "shape=ellipse".to_string(),
+                Shape::Rule => "shape=box".to_string(),
+            },
+        ];
+        if let Some(fill) = fill(node) {
+            attributes.push(format!("style=filled, fillcolor=\"{}\"", fill));
+        }
+        if node.query {
+            attributes.push(format!("color=\"{}\", penwidth=2", QUERY_COLOR));
+        }
+        dot.push_str(&format!("    {} [{}];\n", node.id, attributes.join(", ")));
+    }
+    for edge in graph.edges.iter() {
+        let mut attributes: Vec<String> = vec![];
+        if let Some(label) = edge.label {
+            attributes.push(format!("label=\"{}\"", label));
+        }
+        if edge.negated {
+            attributes.push("style=dashed".to_string());
+        }
+        if edge.both {
+            attributes.push("dir=both".to_string());
+        }
+        if attributes.is_empty() {
+            dot.push_str(&format!("    {} -> {};\n", edge.from, edge.to));
+        } else {
+            dot.push_str(&format!(
+                "    {} -> {} [{}];\n",
+                edge.from,
+                edge.to,
+                attributes.join(", ")
+            ));
+        }
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+// Characters that Mermaid would read as its own syntax
+fn mermaid_escape(text: &str) -> String {
+    text.replace('"', "#quot;")
+        .replace('<', "#lt;")
+        .replace('>', "#gt;")
+        .replace('|', "#124;")
+}
+
+pub fn to_mermaid(graph: &Graph) -> String {
+    let mut mermaid = String::from("flowchart LR\n");
+    for node in graph.nodes.iter() {
+        let label = mermaid_escape(&node.label);
+        match node.shape {
+            Shape::Fact => mermaid.push_str(&format!("    {}([\"{}\"])\n", node.id, label)),
+            Shape::Rule => mermaid.push_str(&format!("    {}[\"{}\"]\n", node.id, label)),
+        }
+    }
+    for edge in graph.edges.iter() {
+        let arrow = match (edge.negated, edge.both) {
+            (false, false) => "-->",
+            (false, true) => "<-->",
+            (true, false) => "-.->",
+            (true, true) => "<-.->",
+        };
+        match edge.label {
+            Some(label) => mermaid.push_str(&format!(
+                "    {} {}|{}| {}\n",
+                edge.from,
+                arrow,
+                mermaid_escape(label),
+                edge.to
+            )),
+            None => mermaid.push_str(&format!("    {} {} {}\n", edge.from, arrow, edge.to)),
+        }
+    }
+    // A class for each fill color and one for the queried facts
+    let mut classes: Vec<(String, Vec<&str>)> = vec![];
+    for node in graph.nodes.iter() {
+        let styles = fill(node)
+            .map(|fill| format!("fill:{}", fill))
+            .into_iter()
+            .chain(
+                node.query
+                    .then(|| format!("stroke:{},stroke-width:3px", QUERY_COLOR)),
+            );
+        for style in styles {
+            match classes.iter_mut().find(|(class, _)| *class == style) {
+                Some((_, ids)) => ids.push(&node.id),
+                None => classes.push((style, vec![&node.id])),
+            }
+        }
+    }
+    for (index, (style, ids)) in classes.iter().enumerate() {
+        mermaid.push_str(&format!("    classDef c{} {}\n", index, style));
+        mermaid.push_str(&format!("    class {} c{}\n", ids.join(","), index));
+    }
+    mermaid
+}
@@ -1,53 +1,271 @@
-use crate::node::{Fact, Node, Operator, Resolve};
+use crate::{
+    clips, dimacs,
+    error::{Error, ErrorKind, Span},
+    json,
+    lint::{self, allow, Allow, Lint, LintKind, Severity},
+    node::{Answers, Fact, Node, Notation, Operator, Resolve},
+    parser::{locate, parse_block, Expression, OPERATOR_SPELLINGS},
+    prolog,
+    scenario::Scenario,
+};
 use colored::Colorize;
 use nom::{
     branch::alt,
-    bytes::complete::{tag, take_until1, take_while, take_while1},
-    character::complete::{anychar, multispace0},
-    combinator::{opt, value},
-    multi::many0,
-    sequence::tuple,
+    bytes::complete::{tag, take_until, take_until1, take_while, take_while1},
+    character::complete::{anychar, multispace0, multispace1, satisfy},
+    combinator::{eof, opt, recognize, value},
+    error::ErrorKind as NomErrorKind,
+    multi::{many0, separated_list1},
+    sequence::{delimited, preceded, separated_pair, terminated, tuple},
     IResult,
 };
-use std::{cell::RefCell, collections::HashMap, fs, rc::Rc};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fs, mem,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
 
 #[derive(Debug)]
 pub struct Input {
     pub facts: HashMap<char, Rc<RefCell<Fact>>>,
     pub rules: Vec<Rc<RefCell<Node>>>,
     pub initial_facts: Vec<char>,
+    // Initial facts stated false with `!` or unknown with `?`
+    pub explicit_facts: Vec<(char, Resolve)>,
     pub queries: Vec<char>,
-    pub warnings: Vec<String>,
+    pub query_expressions: Vec<Rc<RefCell<Node>>>,
+    pub askable: Vec<char>,
+    // Answers of the askable facts, they are not asked without it
+    pub answers: Option<Rc<dyn Answers>>,
+    pub warnings: Vec<Lint>,
+    // Lints allowed by the `# allow(ES0004)` comments
+    allowed: Vec<Allow>,
+    // The warnings are errors, with `--deny warnings`
+    pub deny_warnings: bool,
+    // Notation of the rules and query expressions that are shown
+    pub notation: Notation,
+    pub errors: Vec<Error>,
+    pub scenarios: Vec<Scenario>,
+    // Every loaded file, the top-level file first
+    pub files: Vec<PathBuf>,
+    // Files being loaded to detect include cycles, with the path used to load them
+    including: Vec<(PathBuf, PathBuf)>,
+    // Span of the line being parsed, the nodes built from it keep their span
+    line_span: Option<Span>,
+}
+
+// Prepared block with the span of its line
+struct BlockSource<'a> {
+    string: &'a str,
+    columns: &'a [usize],
+    line: Span,
 }
 
-fn remove_whitespaces(string: &str) -> String {
-    string.chars().filter(|c| !c.is_whitespace()).collect()
+impl BlockSource<'_> {
+    // Span of the characters of the block between two indexes
+    fn span(&self, start: usize, end: usize) -> Span {
+        Span {
+            file: self.line.file.clone(),
+            line: self.line.line,
+            column: self.line.column + self.columns[start] - 1,
+            end: self.line.column + self.columns[end - 1],
+        }
+    }
 }
 
-fn is_only_valid_characters(string: &str) -> Result<(), String> {
-    if !string.chars().all(|c| {
-        char::is_ascii_uppercase(&c)
+// Source of an expression and its index in the block, without source the nodes have no span
+type At<'a> = Option<(&'a BlockSource<'a>, usize)>;
+
+// Number of characters before a slice of the line
+fn offset(line: &str, part: &str) -> usize {
+    line[..(part.as_ptr() as usize - line.as_ptr() as usize)]
+        .chars()
+        .count()
+}
+
+// Column of the remaining input of a nom error in the line
+fn error_column(
+    line: &str,
+    error: &nom::Err<nom::error::Error<&str>>,
+) -> (usize, Option<NomErrorKind>) {
+    match error {
+        nom::Err::Error(error) | nom::Err::Failure(error) => (
+            line[..(line.len() - error.input.len())].chars().count() + 1,
+            Some(error.code),
+        ),
+        nom::Err::Incomplete(_) => (line.chars().count() + 1, None),
+    }
+}
+
+fn is_only_valid_characters(string: &str) -> Result<(), Error> {
+    if let Some(column) = string.chars().position(|c| {
+        !(char::is_ascii_uppercase(&c)
             || c == '!'
             || c == '+'
             || c == '|'
             || c == '^'
+            || c == '='
+            || c == '<'
+            || c == '>'
             || c == '('
-            || c == ')'
+            || c == ')')
     }) {
-        return Err(
-            "Invalid characters in Node, only uppercase letters and operators are allowed"
-                .to_string(),
-        );
+        return Err(Error::new(
+            ErrorKind::InvalidCharacter,
+            "Invalid characters in Node, only uppercase letters and operators are allowed",
+        )
+        .at_column(column + 1));
     }
     Ok(())
 }
 
-fn prepare_rule(left: &str, right: &str) -> Result<(String, String), String> {
-    let left = remove_whitespaces(left);
-    let right = remove_whitespaces(right);
-    is_only_valid_characters(&left)?;
-    is_only_valid_characters(&right)?;
-    Ok((left, right))
+// Remove the whitespaces of a block and keep the column of each remaining character in the line,
+// the alternative spellings of the operators are replaced by their symbol at the column of their first character
+fn prepare_block(line: &str, block: &str) -> (String, Vec<usize>) {
+    let offset = offset(line, block);
+    let mut string = String::new();
+    let mut columns: Vec<usize> = vec![];
+    let characters: Vec<char> = block.chars().collect();
+    let mut i = 0;
+    while i < characters.len() {
+        let c = characters[i];
+        // A word starts after a character that is not an uppercase letter
+        let word: String = if i == 0 || !characters[i - 1].is_ascii_uppercase() {
+            characters[i..]
+                .iter()
+                .take_while(|c| c.is_ascii_uppercase())
+                .collect()
+        } else {
+            String::new()
+        };
+        let spelling =
+            OPERATOR_SPELLINGS
+                .iter()
+                .find(|(spelling, _)| match spelling.chars().count() {
+                    1 => spelling.starts_with(c),
+                    _ => *spelling == word,
+                });
+        if let Some((spelling, symbol)) = spelling {
+            string.push_str(symbol);
+            columns.extend(symbol.chars().map(|_| offset + i + 1));
+            i += spelling.chars().count();
+            continue;
+        }
+        if !c.is_whitespace() {
+            string.push(c);
+            columns.push(offset + i + 1);
+        }
+        i += 1;
+    }
+    (string, columns)
+}
+
+// Convert the column of an error in a prepared block to the column in the line
+fn block_error(error: Error, columns: &[usize]) -> Error {
+    match error.column {
+        Some(column) if column <= columns.len() => {
+            let column = columns[column - 1];
+            error.at_column(column)
+        }
+        Some(_) => {
+            let column = columns.last().map_or(1, |column| column + 1);
+            error.at_column(column)
+        }
+        None => error,
+    }
+}
+
+fn rule_error(line: &str, error: nom::Err<nom::error::Error<&str>>) -> Error {
+    let (column, code) = error_column(line, &error);
+    // The left side starts after the whitespaces and the label
+    let left = match &error {
+        nom::Err::Error(error) | nom::Err::Failure(error) => error.input,
+        nom::Err::Incomplete(_) => line,
+    };
+    match code {
+        Some(NomErrorKind::TakeUntil) if implication(left).is_some() => {
+            Error::new(ErrorKind::MissingLeftSide, "Missing left side of rule").at_column(column)
+        }
+        Some(NomErrorKind::TakeUntil) => Error::new(
+            ErrorKind::InvalidRule,
+            "Invalid rule, expected an implication `=>` or `<=>`",
+        )
+        .at_column(column),
+        Some(NomErrorKind::TakeWhile1) => {
+            Error::new(ErrorKind::MissingRightSide, "Missing right side of rule").at_column(column)
+        }
+        _ => Error::new(ErrorKind::InvalidRule, "Invalid rule").at_column(column),
+    }
+}
+
+// Implications of a rule with their alternative spellings, `<=>` is before `<=` and `=>` that it contains
+// -- `<=` is a reverse implication, its sides are swapped when the rule is built
+const IMPLICATIONS: [(&str, Operator); 7] = [
+    ("<=>", Operator::IfAndOnlyIf),
+    ("<=", Operator::Implies),
+    ("=>", Operator::Implies),
+    ("↔", Operator::IfAndOnlyIf),
+    ("→", Operator::Implies),
+    ("IFF", Operator::IfAndOnlyIf),
+    ("IMPLIES", Operator::Implies),
+];
+
+fn is_word(spelling: &str) -> bool {
+    spelling.starts_with(|c: char| c.is_ascii_uppercase())
+}
+
+// Implication at the start of the input, a word can't be followed by an uppercase letter
+fn implication(i: &str) -> Option<(&'static str, Operator)> {
+    IMPLICATIONS.iter().copied().find(|(spelling, _)| {
+        i.starts_with(spelling)
+            && !(is_word(spelling)
+                && i[spelling.len()..].starts_with(|c: char| c.is_ascii_uppercase()))
+    })
+}
+
+// The left side of a rule, until its first implication outside of a context
+// -- a word is only an implication when it's not part of a longer word
+// -- with unbalanced contexts it's the first implication, the block reports the context
+fn take_until_implication(i: &str) -> IResult<&str, &str> {
+    let mut previous: Option<char> = None;
+    let mut depth = 0;
+    let mut first: Option<usize> = None;
+    for (index, c) in i.char_indices() {
+        let after_letter = previous.is_some_and(|c| c.is_ascii_uppercase());
+        if let Some((spelling, _)) = implication(&i[index..]) {
+            if index > 0 && !(is_word(spelling) && after_letter) {
+                if depth == 0 {
+                    return Ok((&i[index..], &i[..index]));
+                }
+                first = first.or(Some(index));
+            }
+        }
+        match c {
+            '(' => depth += 1,
+            ')' if depth > 0 => depth -= 1,
+            _ => (),
+        }
+        previous = Some(c);
+    }
+    if let Some(index) = first.filter(|_| depth > 0) {
+        return Ok((&i[index..], &i[..index]));
+    }
+    Err(nom::Err::Error(nom::error::Error::new(
+        i,
+        NomErrorKind::TakeUntil,
+    )))
+}
+
+fn implication_operator(i: &str) -> IResult<&str, (&'static str, Operator)> {
+    match implication(i) {
+        Some((spelling, operator)) => Ok((&i[spelling.len()..], (spelling, operator))),
+        None => Err(nom::Err::Error(nom::error::Error::new(
+            i,
+            NomErrorKind::Tag,
+        ))),
+    }
 }
 
 // Separate rule in two blocks and parse the two blocks individually later
@@ -58,24 +276,61 @@ fn prepare_rule(left: &str, right: &str) -> Result<(String, String), String> {
 //           |       operator    |
 //           v       vvvvv       v
 // block: !*\(*{fact}[+|^]{fact}\)*
-// regex: ^\s*({fact}|{block})\s*(<=>|=>)\s*({fact}|{block})\s*(?:#.+)?$
-fn rule(i: &str) -> IResult<&str, (&str, &str, &str)> {
-    let (input, (_, left, _, op, _, right, _, _)) = tuple((
+// regex: ^\[([\w-]+)\]
+pub(crate) fn label(i: &str) -> IResult<&str, &str> {
+    delimited(
+        tag("["),
+        take_while1(|c: char| c.is_ascii_alphanumeric() || c == '_' || c == '-'),
+        tag("]"),
+    )(i)
+}
+
+// Label, sides with the implication and comment of a rule
+type RuleParts<'a> = (Option<&'a str>, &'a str, Operator, &'a str, Option<&'a str>);
+
+// regex: ^\s*(?:\[[\w-]+\]\s*)?({fact}|{block})\s*(<=>|=>|↔|→|IFF|IMPLIES)\s*({fact}|{block})\s*(?:#(.+))?$
+fn rule(i: &str) -> IResult<&str, RuleParts<'_>> {
+    let (input, (_, label, left, _, op, _, right, _, comment)) = tuple((
         value((), multispace0),
-        alt((take_until1("<=>"), take_until1("=>"))),
+        opt(terminated(label, multispace0)),
+        take_until_implication,
         value((), multispace0),
-        alt((tag("<=>"), tag("=>"))),
+        implication_operator,
         value((), multispace0),
         take_while1(|c| c != '#'),
-        // Ignore comments
         value((), multispace0),
-        opt(tuple((tag("#"), many0(anychar)))),
+        opt(preceded(tag("#"), recognize(many0(anychar)))),
     ))(i)?;
-    Ok((input, (left, op, right)))
+    match op {
+        ("<=", op) => Ok((input, (label, right, op, left, comment))),
+        (_, op) => Ok((input, (label, left, op, right, comment))),
+    }
 }
 
-// regex: ^=(\w)*\s*(?:#.+)?$
-fn initial_facts(i: &str) -> IResult<&str, Vec<char>> {
+// Metadata in the comment of a rule, like `# owner: powertrain, reviewed: yes`
+// -- a comment that is not only `key: value` pairs is not metadata
+fn metadata(comment: &str) -> Vec<(String, String)> {
+    let mut metadata = vec![];
+    for pair in comment.split(',') {
+        match pair.split_once(':') {
+            Some((key, value))
+                if !key.trim().is_empty()
+                    && key
+                        .trim()
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+                    && !value.trim().is_empty() =>
+            {
+                metadata.push((key.trim().to_string(), value.trim().to_string()))
+            }
+            _ => return vec![],
+        }
+    }
+    metadata
+}
+
+// regex: ^=([!?]?\w)*\s*(?:#.+)?$
+pub(crate) fn initial_facts(i: &str) -> IResult<&str, &str> {
     let (input, (_, symbols, _, _)) = tuple((
         tag("="),
         take_while(|c| c != '#'),
@@ -83,14 +338,13 @@ fn initial_facts(i: &str) -> IResult<&str, Vec<char>> {
         value((), multispace0),
         opt(tuple((tag("#"), many0(anychar)))),
     ))(i)?;
-
-    let symbols = Vec::from_iter(symbols.chars().filter(|c| c != &' '));
     Ok((input, symbols))
 }
 
-// regex: ^\?(\w)+\s*(?:#.+)?$
-fn queries(i: &str) -> IResult<&str, Vec<char>> {
-    let (input, (_, symbols, _, _)) = tuple((
+// Each query is either a list of facts or an expression, separated by a comma
+// regex: ^\?([^,#]+)(,[^,#]+)*\s*(?:#.+)?$
+pub(crate) fn queries(i: &str) -> IResult<&str, Vec<&str>> {
+    let (input, (_, queries, _, _)) = tuple((
         tag("?"),
         take_while1(|c| c != '#'),
         // Ignore comments
@@ -98,8 +352,136 @@ fn queries(i: &str) -> IResult<&str, Vec<char>> {
         opt(tuple((tag("#"), many0(anychar)))),
     ))(i)?;
 
-    let symbols = Vec::from_iter(symbols.chars().filter(|c| c != &' '));
-    Ok((input, symbols))
+    let queries = Vec::from_iter(queries.split(',').map(|query| query.trim()));
+    Ok((input, queries))
+}
+
+// regex: ^ask\s+(\w)\s*(?:"([^"]*)")?\s*(?:#.+)?$
+pub(crate) fn ask(i: &str) -> IResult<&str, (char, Option<&str>)> {
+    let (input, (_, _, symbol, _, prompt, _, _, _)) = tuple((
+        tag("ask"),
+        multispace1,
+        satisfy(|c| c.is_ascii_uppercase()),
+        multispace0,
+        opt(delimited(tag("\""), take_until("\""), tag("\""))),
+        // Ignore comments
+        value((), multispace0),
+        opt(tuple((tag("#"), many0(anychar)))),
+        eof,
+    ))(i)?;
+    Ok((input, (symbol, prompt)))
+}
+
+// regex: ^@(\w)\s*"([^"]*)"\s*(?:#.+)?$
+pub(crate) fn description(i: &str) -> IResult<&str, (char, &str)> {
+    let (input, (_, symbol, _, description, _, _, _)) = tuple((
+        tag("@"),
+        satisfy(|c| c.is_ascii_uppercase()),
+        multispace0,
+        delimited(tag("\""), take_until("\""), tag("\"")),
+        // Ignore comments
+        value((), multispace0),
+        opt(tuple((tag("#"), many0(anychar)))),
+        eof,
+    ))(i)?;
+    Ok((input, (symbol, description)))
+}
+
+// The comment at the end of a line, a `#` in a quoted description is not a comment
+fn trailing_comment(line: &str) -> Option<&str> {
+    let mut quoted = false;
+    for (index, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '#' if !quoted => return Some(&line[index..]),
+            _ => {}
+        }
+    }
+    None
+}
+
+// regex: ^include\s+"([^"]+)"\s*(?:#.+)?$
+fn include(i: &str) -> IResult<&str, &str> {
+    let (input, (_, _, path, _, _, _)) = tuple((
+        tag("include"),
+        multispace1,
+        delimited(tag("\""), take_until1("\""), tag("\"")),
+        // Ignore comments
+        value((), multispace0),
+        opt(tuple((tag("#"), many0(anychar)))),
+        eof,
+    ))(i)?;
+    Ok((input, path))
+}
+
+// regex: ^scenario\s+"([^"]+)"\s*(?:#.+)?$
+pub(crate) fn scenario(i: &str) -> IResult<&str, &str> {
+    let (input, (_, _, name, _, _, _)) = tuple((
+        tag("scenario"),
+        multispace1,
+        delimited(tag("\""), take_until1("\""), tag("\"")),
+        // Ignore comments
+        value((), multispace0),
+        opt(tuple((tag("#"), many0(anychar)))),
+        eof,
+    ))(i)?;
+    Ok((input, name))
+}
+
+// regex: ^expect\s+(\w\s+(true|ambiguous|false))(\s*,\s*\w\s+(true|ambiguous|false))*\s*(?:#.+)?$
+pub(crate) fn expectations(i: &str) -> IResult<&str, Vec<(char, Resolve)>> {
+    let (input, (_, _, expectations, _, _, _)) = tuple((
+        tag("expect"),
+        multispace1,
+        separated_list1(
+            tuple((multispace0, tag(","), multispace0)),
+            separated_pair(
+                satisfy(|c| c.is_ascii_uppercase()),
+                multispace1,
+                alt((
+                    value(Resolve::True, tag("true")),
+                    value(Resolve::Ambiguous, tag("ambiguous")),
+                    value(Resolve::False, tag("false")),
+                )),
+            ),
+        ),
+        // Ignore comments
+        value((), multispace0),
+        opt(tuple((tag("#"), many0(anychar)))),
+        eof,
+    ))(i)?;
+    Ok((input, expectations))
+}
+
+pub(crate) fn include_path(line: &str) -> Result<&str, Error> {
+    match include(line) {
+        Ok((_, path)) => Ok(path),
+        Err(result) => {
+            let (column, _) = error_column(line, &result);
+            Err(Error::new(
+                ErrorKind::InvalidDirective,
+                "Invalid include, expected `include \"path\"`",
+            )
+            .at_column(column)
+            .with_source(line))
+        }
+    }
+}
+
+pub(crate) fn is_query_expression(string: &str) -> bool {
+    // A word operator is only an operator next to another word, `?AND` are the facts A, N and D
+    let words: Vec<&str> = string.split_whitespace().collect();
+    let spellings = || {
+        OPERATOR_SPELLINGS
+            .iter()
+            .map(|(spelling, _)| *spelling)
+            .chain(IMPLICATIONS.iter().map(|(spelling, _)| *spelling))
+    };
+    string
+        .chars()
+        .any(|c| c == '!' || c == '+' || c == '|' || c == '^' || c == '(' || c == '=')
+        || spellings().any(|spelling| !is_word(spelling) && string.contains(spelling))
+        || (words.len() > 1 && spellings().any(|spelling| words.contains(&spelling)))
 }
 
 impl Default for Input {
@@ -114,24 +496,122 @@ impl Input {
             facts: HashMap::new(),
             rules: vec![],
             initial_facts: vec![],
+            explicit_facts: vec![],
             queries: vec![],
+            query_expressions: vec![],
+            askable: vec![],
+            answers: None,
             warnings: vec![],
+            allowed: vec![],
+            deny_warnings: false,
+            notation: Notation::Ascii,
+            errors: vec![],
+            scenarios: vec![],
+            files: vec![],
+            including: vec![],
+            line_span: None,
         }
     }
 
-    pub fn load_file(&mut self, file_path: &str) -> Result<(), String> {
-        let content = fs::read_to_string(file_path);
-        if let Err(e) = content {
-            return Err(e.to_string());
+    // All errors of the file are kept in `errors`, the first one is returned
+    pub fn load_file(&mut self, file_path: &str) -> Result<(), Error> {
+        match fs::read_to_string(file_path) {
+            Ok(content) => self.load_content(file_path, &content),
+            Err(e) => {
+                self.clear_diagnostics();
+                self.errors
+                    .push(Error::new(ErrorKind::Io, &e.to_string()).in_file(file_path));
+                self.first_error()
+            }
         }
+    }
 
-        self.parse_content(&content.unwrap())?;
-        self.check()?;
+    // Load the content of a file that is not read from the disk, like an edited file, its includes are read
+    pub fn load_content(&mut self, file_path: &str, content: &str) -> Result<(), Error> {
+        self.clear_diagnostics();
+        // The knowledge base is still checked after errors to report its warnings
+        let path = Path::new(file_path);
+        if let Ok(canonical) = path.canonicalize() {
+            self.files.push(canonical.clone());
+            self.including.push((canonical, path.to_path_buf()));
+        }
+        // The errors of the other formats are already in `errors`
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") => {
+                let _ = json::parse_json(self, content);
+            }
+            Some("cnf") | Some("dimacs") => {
+                let _ = dimacs::parse_dimacs(self, content);
+            }
+            Some("clp") => {
+                let _ = clips::parse_clips(self, content);
+            }
+            Some("pl") => {
+                let _ = prolog::parse_prolog(self, content);
+            }
+            _ => self.parse_lines(content, Some(path), true),
+        }
+        self.including.clear();
+        self.check();
+        self.lint();
+        for error in self.errors.iter_mut() {
+            if error.file.is_none() {
+                error.file = Some(file_path.to_string());
+            }
+        }
 
-        Ok(())
+        self.first_error()
+    }
+
+    // Fact of each name of another format, a single letter is the fact of its letter and the other names
+    // take an unused letter, their own first letter if possible, with the name as description
+    // -- the error is the offset of the name that has no letter left, with the noun of the names in the format
+    pub(crate) fn name_facts(
+        &mut self,
+        names: &[(usize, &str)],
+        noun: &str,
+    ) -> Result<HashMap<String, char>, (usize, String)> {
+        let mut symbols: HashMap<String, char> = HashMap::new();
+        for (_, name) in names.iter() {
+            let mut letters = name.chars();
+            if let (Some(letter), None) = (letters.next(), letters.next()) {
+                let symbol = letter.to_ascii_uppercase();
+                if symbol.is_ascii_uppercase() && !symbols.values().any(|used| *used == symbol) {
+                    symbols.insert(name.to_string(), symbol);
+                }
+            }
+        }
+        for (offset, name) in names.iter() {
+            if symbols.contains_key(*name) {
+                continue;
+            }
+            let first = name.chars().next().unwrap_or('A').to_ascii_uppercase();
+            let symbol = std::iter::once(first).chain('A'..='Z').find(|symbol| {
+                symbol.is_ascii_uppercase() && !symbols.values().any(|used| used == symbol)
+            });
+            match symbol {
+                Some(symbol) => symbols.insert(name.to_string(), symbol),
+                None => {
+                    return Err((
+                        *offset,
+                        format!(
+                            "Too many {}, `{}` can't be a fact of the 26 uppercase letters",
+                            noun, name
+                        ),
+                    ))
+                }
+            };
+        }
+        for (name, symbol) in symbols.iter() {
+            let fact = self.get_or_insert_fact(symbol);
+            if name.to_ascii_uppercase() != symbol.to_string() {
+                RefCell::borrow_mut(&fact).description = Some(name.clone());
+            }
+        }
+        Ok(symbols)
     }
 
-    fn get_or_insert_fact(&mut self, symbol: &char) -> Rc<RefCell<Fact>> {
+    pub(crate) fn get_or_insert_fact(&mut self, symbol: &char) -> Rc<RefCell<Fact>> {
         let fact = self.facts.get(symbol);
         if fact.is_none() {
             self.facts.insert(
@@ -141,6 +621,9 @@ impl Input {
                     value: RefCell::new(Resolve::False),
                     resolved: RefCell::new(false),
                     rules: vec![],
+                    answers: None,
+                    prompt: None,
+                    description: None,
                 })),
             );
             return Rc::clone(self.facts.get(symbol).unwrap());
@@ -148,7 +631,7 @@ impl Input {
         Rc::clone(fact.unwrap())
     }
 
-    fn fact_node(&mut self, symbol: &char) -> Rc<RefCell<Node>> {
+    fn fact_node(&mut self, symbol: &char, span: Option<Span>) -> Rc<RefCell<Node>> {
         let fact = self.get_or_insert_fact(symbol);
         Rc::new(RefCell::new(Node {
             visited: RefCell::new(false),
@@ -156,450 +639,1031 @@ impl Input {
             left: None,
             right: None,
             operator: None,
+            label: None,
+            metadata: vec![],
+            disabled: false,
+            span,
         }))
     }
 
-    pub fn parse_rule_block(&mut self, string: &str) -> Result<Rc<RefCell<Node>>, String> {
-        // Initial state
-        let mut opened_context = 0;
-        let mut upper_symbols: Vec<Rc<RefCell<Node>>> = vec![];
-        let mut current_symbol: Rc<RefCell<Node>> = Rc::new(RefCell::new(Node::new()));
-
-        // Parse
-        for (i, c) in string.chars().enumerate() {
-            if c == '(' {
-                // Open context on available symbol side
-                opened_context += 1;
-                if !RefCell::borrow(&current_symbol).has_left() {
-                    let new_symbol = Rc::new(RefCell::new(Node::new()));
-                    RefCell::borrow_mut(&current_symbol).left = Some(Rc::clone(&new_symbol));
-                    upper_symbols.push(Rc::clone(&current_symbol));
-                    current_symbol = new_symbol;
-                } else if !RefCell::borrow(&current_symbol).has_right() {
-                    if !RefCell::borrow(&current_symbol).has_operator() {
-                        return Err(format!(
-                            "Opening context on a incomplete symbol in block `{}` column {}",
-                            string,
-                            i + 1
-                        ));
-                    }
-                    let new_symbol = Rc::new(RefCell::new(Node::new()));
-                    RefCell::borrow_mut(&current_symbol).right = Some(Rc::clone(&new_symbol));
-                    upper_symbols.push(Rc::clone(&current_symbol));
-                    current_symbol = new_symbol;
-                } else {
-                    return Err(format!(
-                        "Invalid opening context on empty symbol in block `{}` column {}",
-                        string,
-                        i + 1
-                    ));
-                }
-            } else if c == ')' {
-                // Close context by poping the last upper symbol
-                if upper_symbols.is_empty() {
-                    return Err(format!(
-                        "Closing context on root symbol in block `{}` column {}",
-                        string,
-                        i + 1
-                    ));
-                }
-                if RefCell::borrow(&current_symbol).operator_eq(&Operator::Not) {
-                    current_symbol = upper_symbols.pop().unwrap();
-                }
-                if RefCell::borrow(&current_symbol).has_left()
-                    && !RefCell::borrow(&current_symbol).has_right()
-                    && !RefCell::borrow(&current_symbol).has_fact()
-                    && RefCell::borrow(&current_symbol).has_operator()
-                {
-                    return Err(format!(
-                        "Closing context on incomplete symbol in block `{}` column {}",
-                        string,
-                        i + 1
-                    ));
-                }
-                if !RefCell::borrow(&current_symbol).has_left()
-                    && !RefCell::borrow(&current_symbol).has_right()
-                    && !RefCell::borrow(&current_symbol).has_fact()
-                {
-                    return Err(format!(
-                        "Unused context in block `{}` column {}",
-                        string,
-                        i + 1
-                    ));
-                }
-                opened_context -= 1;
-                current_symbol = upper_symbols.pop().unwrap();
-            } else if c == '!' {
-                // Open context on an available symbol side
-                if !RefCell::borrow(&current_symbol).has_left() {
-                    let new_symbol = Rc::new(RefCell::new(Node::operator(Operator::Not)));
-                    RefCell::borrow_mut(&current_symbol).left = Some(Rc::clone(&new_symbol));
-                    upper_symbols.push(Rc::clone(&current_symbol));
-                    current_symbol = new_symbol;
-                } else if !RefCell::borrow(&current_symbol).has_right() {
-                    if !RefCell::borrow(&current_symbol).has_operator() {
-                        return Err(format!(
-                            "Invalid NOT operator on incomplete symbol in block `{}` column {}",
-                            string,
-                            i + 1
-                        ));
-                    }
-                    let new_symbol = Rc::new(RefCell::new(Node::operator(Operator::Not)));
-                    RefCell::borrow_mut(&current_symbol).right = Some(Rc::clone(&new_symbol));
-                    upper_symbols.push(Rc::clone(&current_symbol));
-                    current_symbol = new_symbol;
-                } else {
-                    return Err(format!(
-                        "Invalid NOT operator on empty symbol in block `{}` column {}",
-                        string,
-                        i + 1
-                    ));
-                }
-            } else if c == '+' || c == '|' || c == '^' {
-                // If there is already an Operator::Not, go up in symbols if it's complete
-                if RefCell::borrow(&current_symbol).operator_eq(&Operator::Not) {
-                    if RefCell::borrow(&current_symbol).has_fact()
-                        || RefCell::borrow(&current_symbol).has_left()
-                    {
-                        current_symbol = upper_symbols.pop().unwrap();
-                    } else {
-                        return Err(format!(
-                            "Closing Operator Not on empty symbol `{}` column {}",
-                            string,
-                            i + 1
-                        ));
-                    }
-                }
-                // Set the operator of the current symbol or create a new one
-                if RefCell::borrow(&current_symbol).has_operator() {
-                    if RefCell::borrow(&current_symbol).has_left()
-                        && RefCell::borrow(&current_symbol).has_right()
-                    {
-                        // If the current symbol is full create new symbol with the nested previous
-                        // -- Check for operator priority
-                        let new_operator = Node::match_operator(c).unwrap();
-                        // -- If an operator has "more" priority
-                        // -- the right side of the current symbol is inserted on a new symbol with the new operator
-                        // -- and the new nested symbol is set as the current symbol
-                        if new_operator < RefCell::borrow(&current_symbol).operator.unwrap() {
-                            let new_symbol = Rc::new(RefCell::new(Node::new()));
-                            RefCell::borrow_mut(&new_symbol).left = Some(Rc::clone(
-                                RefCell::borrow(&current_symbol).right.as_ref().unwrap(),
-                            ));
-                            RefCell::borrow_mut(&new_symbol).operator = Some(new_operator);
-                            RefCell::borrow_mut(&current_symbol).right =
-                                Some(Rc::clone(&new_symbol));
-                            upper_symbols.push(Rc::clone(&current_symbol));
-                            current_symbol = new_symbol;
-                        }
-                        // -- Else a new symbol is created and the previous one is added on the left side of the new one
-                        else {
-                            let new_symbol = Rc::new(RefCell::new(Node::new()));
-                            RefCell::borrow_mut(&new_symbol).left =
-                                Some(Rc::clone(&current_symbol));
-                            RefCell::borrow_mut(&new_symbol).operator = Some(new_operator);
-                            current_symbol = new_symbol;
-                            // Update last upper symbol left or right which was for the current symbol
-                            if !upper_symbols.is_empty() {
-                                let last = upper_symbols.last().unwrap();
-                                if RefCell::borrow(last).has_right() {
-                                    RefCell::borrow_mut(last).right =
-                                        Some(Rc::clone(&current_symbol));
-                                } else if RefCell::borrow(last).has_left() {
-                                    RefCell::borrow_mut(last).left =
-                                        Some(Rc::clone(&current_symbol));
-                                } else {
-                                    return Err(format!("Opening a new nested symbol on a full operator with an empty context in block `{}` column {}", string, i + 1));
-                                }
-                            }
-                        }
-                    } else {
-                        return Err(format!(
-                            "Operator on already set symbol in block `{}` column {}",
-                            string,
-                            i + 1
-                        ));
-                    }
-                } else {
-                    if !RefCell::borrow(&current_symbol).has_left() {
-                        if RefCell::borrow(&current_symbol).has_fact() {
-                            let symbol_ref = current_symbol.borrow();
-                            let fact_ref = Rc::clone(symbol_ref.fact.as_ref().unwrap());
-                            let fact = RefCell::borrow(&fact_ref);
-                            drop(symbol_ref);
-                            RefCell::borrow_mut(&current_symbol).left =
-                                Some(self.fact_node(&fact.repr));
-                            RefCell::borrow_mut(&current_symbol).fact = None;
-                        } else {
-                            return Err(format!(
-                                "Adding operator to empty symbol in block `{}` column {}",
-                                string,
-                                i + 1
-                            ));
-                        }
-                    }
-                    RefCell::borrow_mut(&current_symbol).operator = Node::match_operator(c);
-                }
-            } else if !RefCell::borrow(&current_symbol).has_left() {
-                // If the current symbol has a Operator::Not
-                // -- set the value of the symbol
-                // Else create a symbol with a value on an opened side
-                if RefCell::borrow(&current_symbol).operator_eq(&Operator::Not) {
-                    RefCell::borrow_mut(&current_symbol).fact = Some(self.get_or_insert_fact(&c));
-                    if !upper_symbols.is_empty() {
-                        let last = upper_symbols.pop().unwrap();
-                        current_symbol = Rc::clone(&last);
-                    }
-                } else if !RefCell::borrow(&current_symbol).has_fact()
-                    && !RefCell::borrow(&current_symbol).has_left()
-                    && !RefCell::borrow(&current_symbol).has_right()
-                    && !RefCell::borrow(&current_symbol).has_operator()
-                {
-                    RefCell::borrow_mut(&current_symbol).fact = Some(self.get_or_insert_fact(&c));
-                } else {
-                    RefCell::borrow_mut(&current_symbol).left = Some(self.fact_node(&c));
-                }
-            } else if !RefCell::borrow(&current_symbol).has_right() {
-                if !RefCell::borrow(&current_symbol).has_operator() {
-                    return Err(format!(
-                        "Missing operator between symbols in block `{}` column {}",
-                        string,
-                        i + 1
-                    ));
-                }
-                RefCell::borrow_mut(&current_symbol).right = Some(self.fact_node(&c));
-            } else {
-                return Err(format!(
-                    "Extraneous symbol with no operators or block in block `{}` column {}",
-                    string,
-                    i + 1
-                ));
-            }
-        }
+    pub fn parse_rule_block(&mut self, string: &str) -> Result<Rc<RefCell<Node>>, Error> {
+        let expression = parse_block(string)?;
+        Ok(self.block_node(&expression))
+    }
 
-        // Check incomplete current_symbol
-        let has_upper_symbols = upper_symbols.is_empty();
-        if
-        // Missing right side on root symbol with operator
-        (!has_upper_symbols
-                && RefCell::borrow(&current_symbol).has_left()
-                && !RefCell::borrow(&current_symbol).has_operator())
-            ||
-            // Empty symbol
-            (!has_upper_symbols
-                && !RefCell::borrow(&current_symbol).has_fact()
-                && !RefCell::borrow(&current_symbol).has_left()
-                && !RefCell::borrow(&current_symbol).has_right())
-            ||
-            // Nested symbol has missing right side -- missing right side on root is allowed
-            (has_upper_symbols
-                && RefCell::borrow(&current_symbol).has_left()
-                && !RefCell::borrow(&current_symbol).has_right()
-                && RefCell::borrow(&current_symbol).has_operator())
-        {
-            return Err(format!("Incomplete symbol in block `{}`", string));
-        }
+    pub(crate) fn block_node(&mut self, expression: &Expression) -> Rc<RefCell<Node>> {
+        self.located_block_node(expression, None)
+    }
 
-        // Check contexts
-        if opened_context != 0 {
-            return Err(format!("Unclosed context in block `{}`", string));
+    // A block is its binary node or fact, a negation or a context is nested on the left side
+    fn located_block_node(&mut self, expression: &Expression, at: At) -> Rc<RefCell<Node>> {
+        match expression {
+            Expression::Fact(_) | Expression::Binary(..) => self.expression_node(expression, at),
+            Expression::Not(_) | Expression::Group(_) => {
+                let node = self.expression_node(expression, at);
+                let span = RefCell::borrow(&node).span.clone();
+                Rc::new(RefCell::new(Node {
+                    visited: RefCell::new(false),
+                    fact: None,
+                    left: Some(node),
+                    right: None,
+                    operator: None,
+                    label: None,
+                    metadata: vec![],
+                    disabled: false,
+                    span,
+                }))
+            }
         }
+    }
 
-        // Unshift the root symbol
-        if !upper_symbols.is_empty() {
-            current_symbol = upper_symbols.swap_remove(0);
+    // The operands are at their own index of the block
+    fn expression_node(&mut self, expression: &Expression, at: At) -> Rc<RefCell<Node>> {
+        let located = at.and_then(|(source, start)| {
+            locate(expression, source.string, start).map(|located| (source, start, located))
+        });
+        let span = located.map(|(source, start, located)| source.span(start, located.end));
+        let left_at: At = located.map(|(source, _, located)| (source, located.operands.0));
+        let right_at: At = located.map(|(source, _, located)| (source, located.operands.1));
+        match expression {
+            Expression::Fact(symbol) => self.fact_node(symbol, span),
+            Expression::Not(expression) => {
+                let node = Node {
+                    span,
+                    ..Node::operator(Operator::Not)
+                };
+                let node = match expression.as_ref() {
+                    Expression::Fact(symbol) => Node {
+                        fact: Some(self.get_or_insert_fact(symbol)),
+                        ..node
+                    },
+                    expression => Node {
+                        left: Some(self.expression_node(expression, left_at)),
+                        ..node
+                    },
+                };
+                Rc::new(RefCell::new(node))
+            }
+            Expression::Group(expression) => self.located_block_node(expression, left_at),
+            Expression::Binary(operator, left, right) => Rc::new(RefCell::new(Node {
+                visited: RefCell::new(false),
+                fact: None,
+                left: Some(self.expression_node(left, left_at)),
+                right: Some(self.expression_node(right, right_at)),
+                operator: Some(*operator),
+                label: None,
+                metadata: vec![],
+                disabled: false,
+                span,
+            })),
         }
+    }
+
+    // Rule shaped Node, with a block on each side of the implication
+    pub(crate) fn expression_rule(
+        &mut self,
+        operator: Operator,
+        left: &Expression,
+        right: &Expression,
+    ) -> Rc<RefCell<Node>> {
+        Rc::new(RefCell::new(Node {
+            visited: RefCell::new(false),
+            fact: None,
+            left: Some(self.block_node(left)),
+            right: Some(self.block_node(right)),
+            operator: Some(operator),
+            label: None,
+            metadata: vec![],
+            disabled: false,
+            span: None,
+        }))
+    }
 
-        Ok(current_symbol)
+    // Parse a block of a line, the column of the errors are in the line
+    fn parse_block(&mut self, line: &str, block: &str) -> Result<Rc<RefCell<Node>>, Error> {
+        let (string, columns) = prepare_block(line, block);
+        is_only_valid_characters(&string).map_err(|error| block_error(error, &columns))?;
+        let expression = parse_block(&string).map_err(|error| block_error(error, &columns))?;
+        let source = self.line_span.clone().map(|line| BlockSource {
+            string: &string,
+            columns: &columns,
+            line,
+        });
+        Ok(self.located_block_node(&expression, source.as_ref().map(|source| (source, 0))))
     }
 
-    pub fn parse_rule(&mut self, line: &str) -> Result<(), String> {
+    pub(crate) fn build_rule(&mut self, line: &str) -> Result<Rc<RefCell<Node>>, Error> {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with('[') && label(trimmed).is_err() {
+            return Err(Error::new(
+                ErrorKind::InvalidRule,
+                "Invalid rule label, expected `[name]` with letters, digits, `_` or `-`",
+            )
+            .at_column(offset(line, trimmed) + 1));
+        }
         let result = rule(line);
         if let Err(result) = result {
-            return Err(result.to_string());
+            return Err(rule_error(line, result));
         }
-        let (_, (left, op, right)) = result.unwrap();
-        let (left, right) = prepare_rule(left, right)?;
-        let rule = Rc::new(RefCell::new(Node {
+        let (_, (label, left, op, right, comment)) = result.unwrap();
+        // The rule is written until its comment
+        let code = line.split('#').next().unwrap().trim();
+        let span = self.line_span.as_ref().map(|line_span| Span {
+            file: line_span.file.clone(),
+            line: line_span.line,
+            column: line_span.column + offset(line, code),
+            end: line_span.column + offset(line, code) + code.chars().count(),
+        });
+        Ok(Rc::new(RefCell::new(Node {
             visited: RefCell::new(false),
             fact: None,
-            left: Some(self.parse_rule_block(&left)?),
-            right: Some(self.parse_rule_block(&right)?),
-            operator: if op == "=>" {
-                Some(Operator::Implies)
-            } else {
-                Some(Operator::IfAndOnlyIf)
-            },
-        }));
-        let rule_ref = RefCell::borrow(&rule);
-        if rule_ref.operator_eq(&Operator::IfAndOnlyIf) {
-            for fact in RefCell::borrow(rule_ref.left.as_ref().unwrap())
-                .all_facts()
-                .iter()
-            {
-                RefCell::borrow_mut(fact).rules.push(Rc::clone(&rule));
+            left: Some(self.parse_block(line, left)?),
+            right: Some(self.parse_block(line, right)?),
+            operator: Some(op),
+            label: label.map(str::to_string),
+            metadata: comment.map_or(vec![], metadata),
+            disabled: false,
+            span,
+        })))
+    }
+
+    pub fn parse_rule(&mut self, line: &str) -> Result<(), Error> {
+        let rule = self
+            .build_rule(line)
+            .map_err(|error| error.with_source(line))?;
+        if let Some(label) = &RefCell::borrow(&rule).label {
+            if self.find_rule(label).is_some() {
+                return Err(Error::new(
+                    ErrorKind::InvalidRule,
+                    &format!("Duplicate rule label `{}`", label),
+                )
+                .at_column(offset(line, line.trim_start()) + 2)
+                .with_source(line));
             }
         }
-        for fact in RefCell::borrow(rule_ref.right.as_ref().unwrap())
-            .all_facts()
+        self.add_rule(rule);
+        Ok(())
+    }
+
+    pub fn find_rule(&self, label: &str) -> Option<Rc<RefCell<Node>>> {
+        self.rules
             .iter()
-        {
+            .find(|rule| RefCell::borrow(rule).label.as_deref() == Some(label))
+            .cloned()
+    }
+
+    // Disable or enable a rule, a disabled rule is kept but concludes nothing
+    pub fn toggle_rule(&mut self, label: &str) -> Result<bool, String> {
+        let rule = self
+            .find_rule(label)
+            .ok_or(format!("No rule labelled `{}`", label))?;
+        let mut rule = RefCell::borrow_mut(&rule);
+        rule.disabled = !rule.disabled;
+        Ok(rule.disabled)
+    }
+
+    // Remove a rule and its links to the facts it concludes
+    pub fn remove_rule(&mut self, label: &str) -> Result<Rc<RefCell<Node>>, String> {
+        let rule = self
+            .find_rule(label)
+            .ok_or(format!("No rule labelled `{}`", label))?;
+        self.rules.retain(|other| !Rc::ptr_eq(other, &rule));
+        for fact in self.facts.values() {
+            RefCell::borrow_mut(fact)
+                .rules
+                .retain(|other| !Rc::ptr_eq(other, &rule));
+        }
+        Ok(rule)
+    }
+
+    // Link the rule to each fact it can conclude
+    // -- all the facts of an equivalence, the premises of the nested implications of a conclusion are not concluded
+    pub(crate) fn add_rule(&mut self, rule: Rc<RefCell<Node>>) {
+        let rule_ref = RefCell::borrow(&rule);
+        let right = RefCell::borrow(rule_ref.right.as_ref().unwrap());
+        let facts = if rule_ref.operator_eq(&Operator::IfAndOnlyIf) {
+            let left = RefCell::borrow(rule_ref.left.as_ref().unwrap());
+            [left.all_facts(), right.all_facts()].concat()
+        } else {
+            right.conclusion_facts()
+        };
+        for fact in facts.iter() {
             RefCell::borrow_mut(fact).rules.push(Rc::clone(&rule));
         }
         self.rules.push(Rc::clone(&rule));
+    }
+
+    pub fn parse_ask(&mut self, line: &str) -> Result<(), Error> {
+        let result = ask(line);
+        if let Err(result) = result {
+            let (column, _) = error_column(line, &result);
+            return Err(Error::new(
+                ErrorKind::InvalidDirective,
+                "Invalid askable fact, expected `ask A` or `ask A \"prompt\"`",
+            )
+            .at_column(column)
+            .with_source(line));
+        }
+        let (_, (symbol, prompt)) = result.unwrap();
+        if self.askable.contains(&symbol) {
+            self.warn(
+                LintKind::DuplicateDeclaration,
+                format!("Duplicate askable fact {}", symbol),
+            );
+        } else {
+            self.askable.push(symbol);
+        }
+        let fact = self.get_or_insert_fact(&symbol);
+        let mut fact = RefCell::borrow_mut(&fact);
+        fact.answers = self.answers.clone();
+        if let Some(prompt) = prompt {
+            fact.prompt = Some(prompt.to_string());
+        }
         Ok(())
     }
 
-    pub fn reparse_initial_facts(&mut self, line: &str) -> Result<(), String> {
+    pub fn parse_description(&mut self, line: &str) -> Result<(), Error> {
+        let result = description(line);
+        if let Err(result) = result {
+            let (column, _) = error_column(line, &result);
+            return Err(Error::new(
+                ErrorKind::InvalidDirective,
+                "Invalid fact description, expected `@A \"description\"`",
+            )
+            .at_column(column)
+            .with_source(line));
+        }
+        let (_, (symbol, description)) = result.unwrap();
+        let fact = self.get_or_insert_fact(&symbol);
+        if RefCell::borrow(&fact).description.is_some() {
+            self.warn(
+                LintKind::DuplicateDeclaration,
+                format!("Duplicate description for fact {}", symbol),
+            );
+        }
+        RefCell::borrow_mut(&fact).description = Some(description.to_string());
+        Ok(())
+    }
+
+    // Enable asking for the value of askable facts with these answers, or disable it
+    pub fn set_answers(&mut self, answers: Option<Rc<dyn Answers>>) {
+        for symbol in self.askable.iter() {
+            RefCell::borrow_mut(self.facts.get(symbol).unwrap()).answers = answers.clone();
+        }
+        self.answers = answers;
+    }
+
+    pub fn reparse_initial_facts(&mut self, line: &str) -> Result<(), Error> {
         self.initial_facts = vec![];
-        self.warnings = vec![];
+        self.explicit_facts = vec![];
+        self.clear_diagnostics();
         self.parse_initial_facts(line)
     }
 
-    pub fn parse_initial_facts(&mut self, line: &str) -> Result<(), String> {
+    pub fn parse_initial_facts(&mut self, line: &str) -> Result<(), Error> {
         let result = initial_facts(line);
         // If it's not the initial facts it's just an error
         if let Err(result) = result {
-            return Err(result.to_string());
+            let (column, _) = error_column(line, &result);
+            return Err(Error::new(
+                ErrorKind::InvalidInitialFacts,
+                "Invalid initial facts, expected `=` followed by facts",
+            )
+            .at_column(column)
+            .with_source(line));
         }
-        // Else add them to the Input
+        // Else add them to the Input, a fact is true unless it's preceded by `!` or `?`
         let (_, initial_facts) = result.unwrap();
-        if !initial_facts.iter().all(char::is_ascii_uppercase) {
-            return Err("Initial facts can only be uppercase letters".to_string());
+        let offset = offset(line, initial_facts);
+        let error = |message: &str, index: usize| {
+            Err(Error::new(ErrorKind::InvalidInitialFacts, message)
+                .at_column(offset + index + 1)
+                .with_source(line))
+        };
+        let mut values: Vec<(char, Resolve)> = vec![];
+        let mut prefix: Option<(char, usize)> = None;
+        for (index, c) in initial_facts.chars().enumerate() {
+            if c.is_ascii_uppercase() {
+                let value = match prefix.take() {
+                    Some(('!', _)) => Resolve::False,
+                    Some(_) => Resolve::Ambiguous,
+                    None => Resolve::True,
+                };
+                values.push((c, value));
+            } else if let Some((prefix, index)) =
+                prefix.filter(|_| c.is_whitespace() || c == '!' || c == '?')
+            {
+                return error(&format!("Missing fact after `{}`", prefix), index);
+            } else if c == '!' || c == '?' {
+                prefix = Some((c, index));
+            } else if !c.is_whitespace() {
+                return error("Initial facts can only be uppercase letters", index);
+            }
+        }
+        if let Some((prefix, index)) = prefix {
+            return error(&format!("Missing fact after `{}`", prefix), index);
         }
-        for symbol in initial_facts.iter() {
+        for (symbol, value) in values {
             // Check if each initial facts are not duplicated
-            if self.initial_facts.contains(symbol) {
-                self.warnings
-                    .push(format!("Duplicate initial fact for symbol {}", symbol));
+            if self.initial_facts.contains(&symbol)
+                || self.explicit_facts.iter().any(|(fact, _)| *fact == symbol)
+            {
+                self.warn(
+                    LintKind::DuplicateInitialFact,
+                    format!("Duplicate initial fact for symbol {}", symbol),
+                );
+                continue;
+            } else if value.is_true() {
+                self.initial_facts.push(symbol);
             } else {
-                self.initial_facts.push(*symbol);
+                self.explicit_facts.push((symbol, value));
             }
-            if !self.facts.contains_key(symbol) {
-                self.warnings
-                    .push(format!("Unused Initial fact {}", symbol));
-                RefCell::borrow_mut(&self.get_or_insert_fact(symbol)).set(Resolve::True);
+            if !self.facts.contains_key(&symbol) {
+                self.warn(
+                    LintKind::UnusedInitialFact,
+                    format!("Unused Initial fact {}", symbol),
+                );
             }
-            // Always set the value to True if called from reparse
-            RefCell::borrow_mut(&self.get_or_insert_fact(symbol)).set(Resolve::True);
+            // Always set the value if called from reparse
+            RefCell::borrow_mut(&self.get_or_insert_fact(&symbol)).set(value);
         }
         Ok(())
     }
 
-    pub fn reparse_queries(&mut self, line: &str) -> Result<(), String> {
+    pub fn reparse_queries(&mut self, line: &str) -> Result<(), Error> {
         self.queries = vec![];
-        self.warnings = vec![];
+        self.query_expressions = vec![];
+        self.clear_diagnostics();
         self.parse_queries(line)
     }
 
-    pub fn parse_queries(&mut self, line: &str) -> Result<(), String> {
+    pub fn parse_query_expression(&mut self, string: &str) -> Result<(), Error> {
+        // The words of the operators are not facts
+        let prepared = prepare_block(string, string).0;
+        let mut missing: Vec<char> = vec![];
+        for symbol in prepared.chars().filter(char::is_ascii_uppercase) {
+            if !self.facts.contains_key(&symbol) && !missing.contains(&symbol) {
+                self.warn(
+                    LintKind::MissingFact,
+                    format!("Query for missing fact {}", symbol),
+                );
+                missing.push(symbol);
+            }
+        }
+        // Implications are parsed as a rule but not added to the rules
+        let expression = if take_until_implication(string).is_ok() {
+            self.build_rule(string)
+        } else {
+            self.parse_block(string, string)
+        };
+        let expression = expression?;
+        // The missing facts are added as false, like the ones of a query for a single fact
+        for symbol in missing.iter() {
+            RefCell::borrow_mut(&self.get_or_insert_fact(symbol)).set(Resolve::False);
+        }
+        self.query_expressions.push(expression);
+        Ok(())
+    }
+
+    pub fn parse_queries(&mut self, line: &str) -> Result<(), Error> {
         let result = queries(line);
         if let Err(result) = result {
-            return Err(result.to_string());
+            let (column, _) = error_column(line, &result);
+            let error = if line.starts_with('?') {
+                Error::new(ErrorKind::MissingQueries, "Queries can't be empty")
+            } else {
+                Error::new(
+                    ErrorKind::InvalidQueries,
+                    "Invalid queries, expected `?` followed by facts or expressions",
+                )
+            };
+            return Err(error.at_column(column).with_source(line));
         }
         let (_, queries) = result.unwrap();
-        if !queries.iter().all(char::is_ascii_uppercase) {
-            return Err("Queries can only be uppercase letters".to_string());
+        if queries.iter().all(|query| query.is_empty()) {
+            return Err(
+                Error::new(ErrorKind::MissingQueries, "Queries can't be empty")
+                    .at_column(2)
+                    .with_source(line),
+            );
+        }
+        let mut symbols: Vec<char> = vec![];
+        for query in queries {
+            let offset = offset(line, query);
+            if is_query_expression(query) {
+                // The nodes of the expression are in the line of the queries
+                let line_span = self.line_span.clone();
+                if let Some(span) = &mut self.line_span {
+                    span.column += offset;
+                }
+                let result = self.parse_query_expression(query);
+                self.line_span = line_span;
+                result.map_err(|error| {
+                    let column = error.column.map_or(1, |column| column + offset);
+                    error.at_column(column).with_source(line)
+                })?;
+            } else if let Some(column) = query
+                .chars()
+                .position(|c| !c.is_whitespace() && !c.is_ascii_uppercase())
+            {
+                return Err(Error::new(
+                    ErrorKind::InvalidQueries,
+                    "Queries can only be uppercase letters",
+                )
+                .at_column(offset + column + 1)
+                .with_source(line));
+            } else {
+                symbols.extend(query.chars().filter(|c| !c.is_whitespace()));
+            }
         }
         // Check if each queries are not duplicate and exist in rules or initial facts
-        for query in queries.iter() {
+        for query in symbols.iter() {
             if self.queries.contains(query) {
-                self.warnings
-                    .push(format!("Duplicate query for fact {}", query));
+                self.warn(
+                    LintKind::DuplicateDeclaration,
+                    format!("Duplicate query for fact {}", query),
+                );
             } else {
                 self.queries.push(*query);
             }
             if !self.facts.contains_key(query) {
-                self.warnings
-                    .push(format!("Query for missing fact {}", query));
+                self.warn(
+                    LintKind::MissingFact,
+                    format!("Query for missing fact {}", query),
+                );
                 RefCell::borrow_mut(&self.get_or_insert_fact(query)).set(Resolve::False);
             }
         }
         Ok(())
     }
 
-    pub fn parse_content(&mut self, content: &str) -> Result<(), String> {
+    // Errors are recovered at the end of the line and all of them are kept in `errors`,
+    // the valid lines are still added to the Input
+    pub fn parse_content(&mut self, content: &str) -> Result<(), Error> {
+        self.clear_diagnostics();
+        self.parse_lines(content, None, true);
+        self.lint();
+        self.first_error()
+    }
+
+    // Included files can only have rules and directives,
+    // their errors keep their own file
+    fn parse_lines(&mut self, content: &str, file_path: Option<&Path>, top_level: bool) {
+        // State of the current block of initial facts, queries and expectations
         let mut parsed_initial_facts = false;
         let mut parsed_queries = false;
+        let mut parsed_expectations = false;
+        // The top-level block is optional if there is at least one scenario
+        let mut parsed_top_level_initial_facts = false;
+        let mut parsed_top_level_queries = false;
+        let mut has_scenario = false;
+        let mut scenario: Option<(Scenario, &str, usize)> = None;
 
-        for (line_number, line) in content.lines().enumerate() {
-            let line = line.trim();
-            // Ignore empty lines and lines with only a comment
+        let file = file_path.map(|file_path| file_path.display().to_string());
+        for (line_number, source) in content.lines().enumerate() {
+            let line = source.trim();
+            let indentation = source.chars().take_while(|c| c.is_whitespace()).count();
+            // Ignore empty lines and lines with only a comment, except for the lints they allow
+            if line.starts_with('#') {
+                self.set_line(file.as_deref(), line_number + 1, source);
+                self.parse_allow(line, file.as_deref(), None);
+            }
             if line.starts_with('#') || line.is_empty() {
                 continue;
             }
-            if parsed_queries {
-                return Err(format!(
-                    "queries should be the last line of a configuration file\nLine {} `{}`",
-                    line_number, line
-                ));
+            self.set_line(file.as_deref(), line_number + 1, source);
+            if let Some(comment) = trailing_comment(line) {
+                self.parse_allow(comment, file.as_deref(), Some(line_number + 1));
             }
+            let is_initial_facts = line.starts_with('=') && !line.starts_with("=>");
 
+            let result = if top_level && line.starts_with("scenario") {
+                if let Err(error) = self.end_scenario(
+                    scenario.take(),
+                    parsed_initial_facts,
+                    parsed_queries,
+                    parsed_expectations,
+                ) {
+                    self.push_error(error, file_path);
+                }
+                has_scenario = true;
+                parsed_initial_facts = false;
+                parsed_queries = false;
+                parsed_expectations = false;
+                self.parse_scenario(line).map(|new_scenario| {
+                    scenario = Some((
+                        Scenario {
+                            line: line_number + 1,
+                            ..new_scenario
+                        },
+                        source,
+                        indentation,
+                    ));
+                })
+            } else if parsed_expectations {
+                Err(Error::new(
+                    ErrorKind::MisplacedLine,
+                    "Only a scenario can follow the expectations",
+                )
+                .at_column(1))
+            } else if parsed_queries && line.starts_with("expect") {
+                parsed_expectations = true;
+                self.parse_expectations(line)
+                    .map(|expectations| match &mut scenario {
+                        Some((scenario, _, _)) => scenario.expectations = expectations,
+                        None => self.scenarios.push(Scenario {
+                            initial_facts: self.initial_facts.clone(),
+                            queries: self.queries.clone(),
+                            query_expressions: self.query_expressions.clone(),
+                            expectations,
+                            ..Scenario::new("default", line_number + 1)
+                        }),
+                    })
+            } else if parsed_queries {
+                Err(Error::new(
+                    ErrorKind::MisplacedLine,
+                    "Queries should be the last line of a configuration file, or be followed by expectations or a scenario",
+                )
+                .at_column(1))
+            }
+            // Parse askable facts
+            else if !parsed_initial_facts && scenario.is_none() && line.starts_with("ask") {
+                self.parse_ask(line)
+            }
+            // Parse fact descriptions
+            else if !parsed_initial_facts && scenario.is_none() && line.starts_with('@') {
+                self.parse_description(line)
+            }
+            // Parse included files
+            else if !parsed_initial_facts && scenario.is_none() && line.starts_with("include") {
+                self.parse_include(line, file_path)
+            }
+            // Initial facts and queries of included files
+            else if !top_level && (line.starts_with('?') || is_initial_facts) {
+                Err(Error::new(
+                    ErrorKind::MisplacedLine,
+                    "Initial facts and queries can only be in the top-level file",
+                )
+                .at_column(1))
+            }
             // Parse queries
-            if parsed_initial_facts {
-                let result = self.parse_queries(line);
-                if let Err(error) = result {
-                    return Err(format!("{}\nLine {} `{}`", error, line_number, line));
+            else if parsed_initial_facts {
+                parsed_queries = true;
+                match &mut scenario {
+                    Some((scenario, _, _)) => self.parse_scenario_line(line, scenario),
+                    None => {
+                        parsed_top_level_queries = true;
+                        self.parse_queries(line)
+                    }
                 }
-                parsed_queries = true
             }
-            // Parse rule (and initial facts on error to switch context)
-            else {
-                let result = self.parse_rule(line);
-                // Parse initial facts if the rule parser doesn't match
-                if let Err(original_error) = result {
-                    let result = self.parse_initial_facts(line);
-                    // If it's not the initial facts it's just an error
-                    if result.is_err() {
-                        return Err(format!(
-                            "{}\nLine {} `{}`",
-                            original_error, line_number, line
-                        ));
+            // Queries before the initial facts
+            else if line.starts_with('?') {
+                Err(Error::new(
+                    ErrorKind::MisplacedLine,
+                    "Queries should be after the initial facts",
+                )
+                .at_column(1))
+            }
+            // Parse initial facts
+            else if is_initial_facts {
+                parsed_initial_facts = true;
+                match &mut scenario {
+                    Some((scenario, _, _)) => self.parse_scenario_line(line, scenario),
+                    None => {
+                        parsed_top_level_initial_facts = true;
+                        self.parse_initial_facts(line)
                     }
-                    parsed_initial_facts = true;
                 }
             }
+            // Rules after a scenario
+            else if scenario.is_some() {
+                Err(Error::new(
+                    ErrorKind::MisplacedLine,
+                    "Rules should be before the scenarios",
+                )
+                .at_column(1))
+            }
+            // Parse rule
+            else {
+                self.parse_rule(line)
+            };
+            if let Err(error) = result {
+                self.push_error(
+                    error.at_line(line_number + 1, source, indentation),
+                    file_path,
+                );
+            }
         }
+        self.line_span = None;
 
+        if !top_level {
+            return;
+        }
+        if let Err(error) = self.end_scenario(
+            scenario,
+            parsed_initial_facts,
+            parsed_queries,
+            parsed_expectations,
+        ) {
+            self.push_error(error, file_path);
+        }
+        // Without the top-level block the first scenario is used
+        if has_scenario && !parsed_top_level_initial_facts && !parsed_top_level_queries {
+            if let Some(first) = self.scenarios.first() {
+                self.initial_facts = first.initial_facts.clone();
+                self.explicit_facts = first.explicit_facts.clone();
+                self.queries = first.queries.clone();
+                self.query_expressions = first.query_expressions.clone();
+            }
+        } else {
+            if !parsed_top_level_initial_facts {
+                self.errors.push(Error::new(
+                    ErrorKind::MissingInitialFacts,
+                    "Missing initial facts",
+                ));
+            }
+            if !parsed_top_level_queries {
+                self.errors
+                    .push(Error::new(ErrorKind::MissingQueries, "Missing queries"));
+            }
+        }
+        // Only keep the values of the initial facts of the Input
+        self.reset();
+    }
+
+    // Lints allowed by a comment, in the whole file for a line with only a comment or else on its line
+    fn parse_allow(&mut self, comment: &str, file: Option<&str>, line: Option<usize>) {
+        let codes = match allow(comment.trim()) {
+            Ok((_, codes)) => codes,
+            Err(_) => return,
+        };
+        for code in codes {
+            match LintKind::from_code(code) {
+                Some(kind) => self.allowed.push(Allow {
+                    file: file.map(str::to_string),
+                    line,
+                    kind,
+                }),
+                None => self.warn(LintKind::UnknownLint, format!("Unknown lint {}", code)),
+            }
+        }
+    }
+
+    // Lint of the line being parsed
+    pub(crate) fn warn(&mut self, kind: LintKind, message: String) {
+        self.warnings
+            .push(Lint::new(kind, &message).at(self.line_span.clone()));
+    }
+
+    // Find the lints of the whole knowledge base and remove the allowed lints,
+    // the warnings are moved to the errors if they are denied
+    fn lint(&mut self) {
+        let lints = lint::lint(self);
+        self.warnings.extend(lints);
+        let allowed = mem::take(&mut self.allowed);
+        self.warnings
+            .retain(|warning| !allowed.iter().any(|allow| allow.allows(warning)));
+        if !self.deny_warnings {
+            return;
+        }
+        let (denied, warnings): (Vec<Lint>, Vec<Lint>) = mem::take(&mut self.warnings)
+            .into_iter()
+            .partition(|warning| warning.severity() == Severity::Warning);
+        self.warnings = warnings;
+        for warning in denied {
+            let error = Error::new(
+                ErrorKind::DeniedWarning,
+                &format!("{} [{}]", warning.message, warning.code()),
+            );
+            self.errors.push(match warning.span {
+                Some(span) => Error {
+                    file: span.file,
+                    line: Some(span.line),
+                    column: Some(span.column),
+                    ..error
+                },
+                None => error,
+            });
+        }
+    }
+
+    // The nodes of the next parsed lines are at this line
+    pub fn set_line(&mut self, file: Option<&str>, line: usize, source: &str) {
+        let indentation = source.chars().take_while(|c| c.is_whitespace()).count();
+        self.line_span = Some(Span {
+            file: file.map(str::to_string),
+            line,
+            column: indentation + 1,
+            end: indentation + source.trim().chars().count() + 1,
+        });
+    }
+
+    fn push_error(&mut self, error: Error, file_path: Option<&Path>) {
+        match file_path {
+            Some(file_path) => self
+                .errors
+                .push(error.in_file(&file_path.display().to_string())),
+            None => self.errors.push(error),
+        }
+    }
+
+    pub fn parse_scenario(&mut self, line: &str) -> Result<Scenario, Error> {
+        let result = scenario(line);
+        if let Err(result) = result {
+            let (column, _) = error_column(line, &result);
+            return Err(Error::new(
+                ErrorKind::InvalidDirective,
+                "Invalid scenario, expected `scenario \"name\"`",
+            )
+            .at_column(column)
+            .with_source(line));
+        }
+        let (_, name) = result.unwrap();
+        if self.scenarios.iter().any(|scenario| scenario.name == name) {
+            self.warn(
+                LintKind::DuplicateDeclaration,
+                format!("Duplicate scenario name {}", name),
+            );
+        }
+        Ok(Scenario::new(name, 0))
+    }
+
+    // Parse the initial facts or the queries of a scenario without changing the ones of the Input
+    fn parse_scenario_line(&mut self, line: &str, scenario: &mut Scenario) -> Result<(), Error> {
+        mem::swap(&mut self.initial_facts, &mut scenario.initial_facts);
+        mem::swap(&mut self.explicit_facts, &mut scenario.explicit_facts);
+        mem::swap(&mut self.queries, &mut scenario.queries);
+        mem::swap(&mut self.query_expressions, &mut scenario.query_expressions);
+        let result = if line.starts_with('?') {
+            self.parse_queries(line)
+        } else {
+            self.parse_initial_facts(line)
+        };
+        mem::swap(&mut self.initial_facts, &mut scenario.initial_facts);
+        mem::swap(&mut self.explicit_facts, &mut scenario.explicit_facts);
+        mem::swap(&mut self.queries, &mut scenario.queries);
+        mem::swap(&mut self.query_expressions, &mut scenario.query_expressions);
+        result
+    }
+
+    // A scenario is only kept if it has its initial facts, queries and expectations
+    fn end_scenario(
+        &mut self,
+        scenario: Option<(Scenario, &str, usize)>,
+        parsed_initial_facts: bool,
+        parsed_queries: bool,
+        parsed_expectations: bool,
+    ) -> Result<(), Error> {
+        let (scenario, source, indentation) = match scenario {
+            Some(scenario) => scenario,
+            None => return Ok(()),
+        };
         if !parsed_initial_facts {
-            return Err("Missing initial facts".to_string());
+            return Err(Error::new(
+                ErrorKind::MissingInitialFacts,
+                &format!("Missing initial facts in scenario {}", scenario.name),
+            )
+            .at_line(scenario.line, source, indentation));
         }
         if !parsed_queries {
-            return Err("Missing queries".to_string());
+            return Err(Error::new(
+                ErrorKind::MissingQueries,
+                &format!("Missing queries in scenario {}", scenario.name),
+            )
+            .at_line(scenario.line, source, indentation));
+        }
+        // A scenario without expectations would always pass
+        if !parsed_expectations {
+            return Err(Error::new(
+                ErrorKind::MissingExpectations,
+                &format!("Missing expectations in scenario {}", scenario.name),
+            )
+            .at_line(scenario.line, source, indentation));
         }
+        self.scenarios.push(scenario);
         Ok(())
     }
 
-    fn check(&mut self) -> Result<(), String> {
-        if self.rules.is_empty() {
-            self.warnings.push("No rules".to_string());
+    pub fn parse_expectations(&mut self, line: &str) -> Result<Vec<(char, Resolve)>, Error> {
+        let result = expectations(line);
+        if let Err(result) = result {
+            let (column, _) = error_column(line, &result);
+            return Err(Error::new(
+                ErrorKind::InvalidDirective,
+                "Invalid expectations, expected `expect A true, B false`",
+            )
+            .at_column(column)
+            .with_source(line));
+        }
+        let (_, expectations) = result.unwrap();
+        for (symbol, _) in expectations.iter() {
+            if !self.facts.contains_key(symbol) {
+                self.warn(
+                    LintKind::MissingFact,
+                    format!("Expectation for missing fact {}", symbol),
+                );
+            }
+        }
+        Ok(expectations)
+    }
+
+    // The path is relative to the including file, or to the current directory without a file
+    // -- a file is only included once
+    pub fn parse_include(&mut self, line: &str, file_path: Option<&Path>) -> Result<(), Error> {
+        let include_path = include_path(line)?;
+        let column = offset(line, include_path) + 1;
+        let path = match file_path.and_then(Path::parent) {
+            Some(directory) => directory.join(include_path),
+            None => PathBuf::from(include_path),
+        };
+        let content = fs::read_to_string(&path);
+        let canonical = path.canonicalize();
+        let (content, canonical) = match (content, canonical) {
+            (Ok(content), Ok(canonical)) => (content, canonical),
+            (Err(e), _) | (_, Err(e)) => {
+                return Err(Error::new(
+                    ErrorKind::Io,
+                    &format!("Failed to include `{}`: {}", path.display(), e),
+                )
+                .at_column(column)
+                .with_source(line));
+            }
+        };
+        if self.including.iter().any(|(file, _)| *file == canonical) {
+            let cycle: Vec<String> = self
+                .including
+                .iter()
+                .skip_while(|(file, _)| *file != canonical)
+                .map(|(_, path)| path.display().to_string())
+                .chain(std::iter::once(path.display().to_string()))
+                .collect();
+            return Err(Error::new(
+                ErrorKind::IncludeCycle,
+                &format!("Include cycle: {}", cycle.join(" -> ")),
+            )
+            .at_column(column)
+            .with_source(line));
         }
-        if self.queries.is_empty() {
-            return Err("Queries can't be empty".to_string());
+        if self.files.contains(&canonical) {
+            return Ok(());
         }
+
+        self.files.push(canonical.clone());
+        self.including.push((canonical, path.clone()));
+        self.parse_lines(&content, Some(&path), false);
+        self.including.pop();
         Ok(())
     }
 
+    // The errors and warnings of a previous load or reparse are not reported again
+    pub fn clear_diagnostics(&mut self) {
+        self.errors.clear();
+        self.warnings.clear();
+        self.allowed.clear();
+    }
+
+    fn first_error(&self) -> Result<(), Error> {
+        match self.errors.first() {
+            Some(error) => Err(error.clone()),
+            None => Ok(()),
+        }
+    }
+
+    fn check(&mut self) {
+        if self.rules.is_empty() {
+            self.warn(LintKind::NoRules, "No rules".to_string());
+        }
+        for symbol in self.askable.iter() {
+            let fact = RefCell::borrow(self.facts.get(symbol).unwrap());
+            if let Some(rule) = fact.rules.first() {
+                self.warnings.push(Lint::new(
+                    LintKind::ConcludedAskableFact,
+                    &format!(
+                        "Askable fact {} is concluded by rule {} and will never be asked",
+                        symbol,
+                        RefCell::borrow(rule).located()
+                    ),
+                ));
+            }
+        }
+        if self.errors.is_empty() && self.queries.is_empty() && self.query_expressions.is_empty() {
+            self.errors.push(Error::new(
+                ErrorKind::MissingQueries,
+                "Queries can't be empty",
+            ));
+        }
+    }
+
+    // Total of errors and warnings found while loading the Input
+    pub fn summary(&self) -> String {
+        let plural = |count: usize, name: &str| {
+            if count == 1 {
+                format!("{} {}", count, name)
+            } else {
+                format!("{} {}s", count, name)
+            }
+        };
+        format!(
+            "{} and {}",
+            plural(self.errors.len(), "error"),
+            plural(self.warnings.len(), "warning")
+        )
+    }
+
+    pub fn show_errors(&self) {
+        for error in self.errors.iter() {
+            eprintln!("{}\n", error);
+        }
+        eprintln!(
+            "{}",
+            format!("Failed to load input: {}", self.summary()).red()
+        );
+    }
+
     pub fn show_warnings(&self) {
         for warning in self.warnings.iter() {
-            println!("{}  {}", "!".red().on_yellow(), warning.yellow());
+            println!(
+                "{}  {}",
+                "!".red().on_yellow(),
+                warning.to_string().yellow()
+            );
         }
     }
 
     pub fn show_rules(&self) {
         for rule in self.rules.iter() {
+            let rule = RefCell::borrow(rule);
             print!("{}  ", "|".normal().on_blue(),);
-            RefCell::borrow(rule).print_short();
+            rule.print_short(self.notation);
+            if rule.disabled {
+                print!(" {}", "(disabled)".yellow());
+            }
+            if !rule.metadata.is_empty() {
+                let metadata: Vec<String> = rule
+                    .metadata
+                    .iter()
+                    .map(|(key, value)| format!("{}: {}", key, value))
+                    .collect();
+                print!("  {}", format!("# {}", metadata.join(", ")).dimmed());
+            }
             println!();
         }
     }
 
+    // Rule with its metadata and the facts it concludes
+    pub fn show_rule(&self, label: &str) -> Result<(), String> {
+        let rule = self
+            .find_rule(label)
+            .ok_or(format!("No rule labelled `{}`", label))?;
+        let rule = RefCell::borrow(&rule);
+        print!("{}  ", "|".normal().on_blue(),);
+        rule.print_short(self.notation);
+        println!();
+        for (key, value) in rule.metadata.iter() {
+            println!("{}  {}: {}", "|".normal().on_blue(), key, value);
+        }
+        let facts: Vec<String> = RefCell::borrow(rule.right.as_ref().unwrap())
+            .conclusion_facts()
+            .iter()
+            .map(|fact| RefCell::borrow(fact).subject())
+            .collect();
+        println!(
+            "{}  {} {}",
+            "|".normal().on_blue(),
+            if rule.disabled {
+                "disabled, concludes".yellow()
+            } else {
+                "concludes".cyan()
+            },
+            facts.join(", ")
+        );
+        Ok(())
+    }
+
+    pub fn show_descriptions(&self) {
+        let mut facts: Vec<&char> = self.facts.keys().collect();
+        facts.sort_unstable();
+        for symbol in facts {
+            let fact = RefCell::borrow(self.facts.get(symbol).unwrap());
+            if let Some(description) = &fact.description {
+                println!("{}  {} {}", "@".normal().on_blue(), symbol, description);
+            }
+        }
+    }
+
     pub fn show_initial_facts(&self) {
         print!("{}  ", "=".normal().on_green());
-        if !self.initial_facts.is_empty() {
+        if !self.initial_facts.is_empty() || !self.explicit_facts.is_empty() {
             for repr in self.initial_facts.iter() {
                 print!("{}", format!("{}", repr).green());
             }
+            for (repr, value) in self.explicit_facts.iter() {
+                match value {
+                    Resolve::False => print!("{}", format!("!{}", repr).red()),
+                    _ => print!("{}", format!("?{}", repr).yellow()),
+                }
+            }
         } else {
             print!("No initial facts");
         }
@@ -611,18 +1675,29 @@ impl Input {
         for query in self.queries.iter() {
             print!("{}", query);
         }
+        for (index, expression) in self.query_expressions.iter().enumerate() {
+            if index > 0 || !self.queries.is_empty() {
+                print!(", ");
+            }
+            RefCell::borrow(expression).print_short(self.notation);
+        }
         println!();
     }
 
     pub fn reset(&mut self) {
         for (repr, fact) in self.facts.iter() {
-            let is_initial_fact = self.initial_facts.contains(repr);
-            *RefCell::borrow_mut(&RefCell::borrow(fact).value) = if is_initial_fact {
-                Resolve::True
-            } else {
-                Resolve::False
+            let explicit = self
+                .explicit_facts
+                .iter()
+                .find(|(symbol, _)| symbol == repr)
+                .map(|(_, value)| *value);
+            let value = match explicit {
+                Some(value) => Some(value),
+                None if self.initial_facts.contains(repr) => Some(Resolve::True),
+                None => None,
             };
-            *RefCell::borrow_mut(&RefCell::borrow(fact).resolved) = is_initial_fact;
+            *RefCell::borrow_mut(&RefCell::borrow(fact).value) = value.unwrap_or(Resolve::False);
+            *RefCell::borrow_mut(&RefCell::borrow(fact).resolved) = value.is_some();
         }
     }
 }
@@ -0,0 +1,591 @@
+use crate::{
+    error::{Error, ErrorKind},
+    format::format_node,
+    input::Input,
+    lint::{Lint, LintKind},
+    node::{Fact, Node, Operator, Resolve},
+    parser::Expression,
+};
+use serde::{Deserialize, Serialize};
+use std::{cell::RefCell, rc::Rc};
+
+// Expression tree of a rule or a query, implications and equivalences can be nested in any operand
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JsonExpression {
+    Fact {
+        symbol: char,
+    },
+    Not {
+        operand: Box<JsonExpression>,
+    },
+    And {
+        left: Box<JsonExpression>,
+        right: Box<JsonExpression>,
+    },
+    Or {
+        left: Box<JsonExpression>,
+        right: Box<JsonExpression>,
+    },
+    Xor {
+        left: Box<JsonExpression>,
+        right: Box<JsonExpression>,
+    },
+    Nand {
+        left: Box<JsonExpression>,
+        right: Box<JsonExpression>,
+    },
+    Nor {
+        left: Box<JsonExpression>,
+        right: Box<JsonExpression>,
+    },
+    Xnor {
+        left: Box<JsonExpression>,
+        right: Box<JsonExpression>,
+    },
+    Implies {
+        left: Box<JsonExpression>,
+        right: Box<JsonExpression>,
+    },
+    IfAndOnlyIf {
+        left: Box<JsonExpression>,
+        right: Box<JsonExpression>,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct JsonFact {
+    pub symbol: char,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub askable: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+}
+
+// Whole knowledge base, a query is a fact or an expression
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct JsonInput {
+    #[serde(default)]
+    pub facts: Vec<JsonFact>,
+    pub rules: Vec<JsonExpression>,
+    #[serde(default)]
+    pub initial_facts: Vec<char>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub false_facts: Vec<char>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unknown_facts: Vec<char>,
+    pub queries: Vec<JsonExpression>,
+}
+
+// Why a fact has its value, rebuilt from the values found by the resolution
+// -- the rules are only listed for a fact that isn't given, the steps of the resolution are in the path
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Proof {
+    pub fact: char,
+    pub value: Resolve,
+    pub source: ProofSource,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<ProofRule>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProofSource {
+    InitialFact,
+    Answered,
+    Rules,
+    // Already in the proof of one of its premises
+    Cycle,
+    // A fact without rules is false in a closed world
+    Default,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ProofRule {
+    pub rule: String,
+    pub premise: Resolve,
+    pub facts: Vec<Proof>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct QueryResult {
+    pub query: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<Resolve>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    // Steps of the resolution in the order they were taken
+    pub path: Vec<String>,
+    pub proof: Vec<Proof>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Diagnostic {
+    pub code: &'static str,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+}
+
+impl From<&Error> for Diagnostic {
+    fn from(error: &Error) -> Self {
+        Diagnostic {
+            code: error.code(),
+            message: error.message.clone(),
+            file: error.file.clone(),
+            line: error.line,
+            column: error.column,
+        }
+    }
+}
+
+impl From<&Lint> for Diagnostic {
+    fn from(lint: &Lint) -> Self {
+        Diagnostic {
+            code: lint.code(),
+            message: lint.message.clone(),
+            file: lint.span.as_ref().and_then(|span| span.file.clone()),
+            line: lint.span.as_ref().map(|span| span.line),
+            column: lint.span.as_ref().map(|span| span.column),
+        }
+    }
+}
+
+// Results of an input file, or the reason it couldn't be loaded
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct FileReport {
+    pub file: String,
+    pub warnings: Vec<Diagnostic>,
+    pub errors: Vec<Diagnostic>,
+    pub results: Vec<QueryResult>,
+}
+
+fn binary(
+    operator: Operator,
+    left: Box<JsonExpression>,
+    right: Box<JsonExpression>,
+) -> JsonExpression {
+    match operator {
+        Operator::And => JsonExpression::And { left, right },
+        Operator::Or => JsonExpression::Or { left, right },
+        Operator::Xor => JsonExpression::Xor { left, right },
+        Operator::Nand => JsonExpression::Nand { left, right },
+        Operator::Nor => JsonExpression::Nor { left, right },
+        Operator::Xnor => JsonExpression::Xnor { left, right },
+        Operator::Implies => JsonExpression::Implies { left, right },
+        Operator::IfAndOnlyIf => JsonExpression::IfAndOnlyIf { left, right },
+        Operator::Not => JsonExpression::Not { operand: left },
+    }
+}
+
+pub fn node_expression(node: &Node) -> JsonExpression {
+    if let Some(fact) = &node.fact {
+        let symbol = RefCell::borrow(fact).repr;
+        if node.operator_eq(&Operator::Not) {
+            return JsonExpression::Not {
+                operand: Box::new(JsonExpression::Fact { symbol }),
+            };
+        }
+        return JsonExpression::Fact { symbol };
+    }
+    let left = Box::new(node_expression(&RefCell::borrow(
+        node.left.as_ref().unwrap(),
+    )));
+    match node.operator {
+        None => *left,
+        Some(Operator::Not) => JsonExpression::Not { operand: left },
+        Some(operator) => binary(
+            operator,
+            left,
+            Box::new(node_expression(&RefCell::borrow(
+                node.right.as_ref().unwrap(),
+            ))),
+        ),
+    }
+}
+
+pub fn export_input(input: &Input) -> JsonInput {
+    let mut symbols: Vec<&char> = input.facts.keys().collect();
+    symbols.sort_unstable();
+    let facts = symbols
+        .iter()
+        .map(|symbol| {
+            let fact = RefCell::borrow(input.facts.get(symbol).unwrap());
+            JsonFact {
+                symbol: fact.repr,
+                description: fact.description.clone(),
+                askable: input.askable.contains(symbol),
+                prompt: fact.prompt.clone(),
+            }
+        })
+        .collect();
+    let queries = input
+        .queries
+        .iter()
+        .map(|symbol| JsonExpression::Fact { symbol: *symbol })
+        .chain(
+            input
+                .query_expressions
+                .iter()
+                .map(|expression| node_expression(&RefCell::borrow(expression))),
+        )
+        .collect();
+    JsonInput {
+        facts,
+        rules: input
+            .rules
+            .iter()
+            .map(|rule| node_expression(&RefCell::borrow(rule)))
+            .collect(),
+        initial_facts: input.initial_facts.clone(),
+        false_facts: explicit_facts(input, Resolve::False),
+        unknown_facts: explicit_facts(input, Resolve::Ambiguous),
+        queries,
+    }
+}
+
+fn explicit_facts(input: &Input, value: Resolve) -> Vec<char> {
+    input
+        .explicit_facts
+        .iter()
+        .filter(|(_, explicit)| *explicit == value)
+        .map(|(symbol, _)| *symbol)
+        .collect()
+}
+
+fn invalid(path: &str, message: &str) -> Error {
+    Error::new(ErrorKind::InvalidJson, &format!("{}: {}", path, message))
+}
+
+fn check_symbol(symbol: char, path: &str) -> Result<char, Error> {
+    if symbol.is_ascii_uppercase() {
+        Ok(symbol)
+    } else {
+        Err(invalid(
+            path,
+            &format!("Invalid fact `{}`, facts are uppercase letters", symbol),
+        ))
+    }
+}
+
+// Convert to the Expression of the parser
+fn expression(json: &JsonExpression, path: &str) -> Result<Expression, Error> {
+    let boxed = |json: &JsonExpression, side: &str| {
+        expression(json, &format!("{}.{}", path, side)).map(Box::new)
+    };
+    Ok(match json {
+        JsonExpression::Fact { symbol } => Expression::Fact(check_symbol(*symbol, path)?),
+        JsonExpression::Not { operand } => Expression::Not(boxed(operand, "operand")?),
+        JsonExpression::And { left, right } => {
+            Expression::Binary(Operator::And, boxed(left, "left")?, boxed(right, "right")?)
+        }
+        JsonExpression::Or { left, right } => {
+            Expression::Binary(Operator::Or, boxed(left, "left")?, boxed(right, "right")?)
+        }
+        JsonExpression::Xor { left, right } => {
+            Expression::Binary(Operator::Xor, boxed(left, "left")?, boxed(right, "right")?)
+        }
+        JsonExpression::Nand { left, right } => {
+            Expression::Binary(Operator::Nand, boxed(left, "left")?, boxed(right, "right")?)
+        }
+        JsonExpression::Nor { left, right } => {
+            Expression::Binary(Operator::Nor, boxed(left, "left")?, boxed(right, "right")?)
+        }
+        JsonExpression::Xnor { left, right } => {
+            Expression::Binary(Operator::Xnor, boxed(left, "left")?, boxed(right, "right")?)
+        }
+        JsonExpression::Implies { left, right } => Expression::Binary(
+            Operator::Implies,
+            boxed(left, "left")?,
+            boxed(right, "right")?,
+        ),
+        JsonExpression::IfAndOnlyIf { left, right } => Expression::Binary(
+            Operator::IfAndOnlyIf,
+            boxed(left, "left")?,
+            boxed(right, "right")?,
+        ),
+    })
+}
+
+// Rule shaped Node of an implication, other expressions are not rules
+fn implication(
+    input: &mut Input,
+    json: &JsonExpression,
+    path: &str,
+) -> Result<Option<Rc<RefCell<Node>>>, Error> {
+    let (operator, left, right) = match json {
+        JsonExpression::Implies { left, right } => (Operator::Implies, left, right),
+        JsonExpression::IfAndOnlyIf { left, right } => (Operator::IfAndOnlyIf, left, right),
+        _ => return Ok(None),
+    };
+    let left = expression(left, &format!("{}.left", path))?;
+    let right = expression(right, &format!("{}.right", path))?;
+    Ok(Some(input.expression_rule(operator, &left, &right)))
+}
+
+fn add_fact(input: &mut Input, json: &JsonFact, path: &str) -> Result<(), Error> {
+    let symbol = check_symbol(json.symbol, &format!("{}.symbol", path))?;
+    let fact = input.get_or_insert_fact(&symbol);
+    if json.askable {
+        if input.askable.contains(&symbol) {
+            input.warn(
+                LintKind::DuplicateDeclaration,
+                format!("Duplicate askable fact {}", symbol),
+            );
+        } else {
+            input.askable.push(symbol);
+        }
+    }
+    let mut fact = RefCell::borrow_mut(&fact);
+    fact.answers = input.answers.clone().filter(|_| json.askable);
+    fact.prompt = json.prompt.clone();
+    fact.description = json.description.clone();
+    Ok(())
+}
+
+fn add_query(input: &mut Input, json: &JsonExpression, path: &str) -> Result<(), Error> {
+    if let JsonExpression::Fact { symbol } = json {
+        let symbol = check_symbol(*symbol, path)?;
+        // The warnings for duplicate or missing facts are the ones of the text format
+        return input.parse_queries(&format!("?{}", symbol));
+    }
+    let mut known: Vec<char> = input.facts.keys().copied().collect();
+    let node = match implication(input, json, path)? {
+        Some(node) => node,
+        None => {
+            let expression = expression(json, path)?;
+            input.block_node(&expression)
+        }
+    };
+    // The missing facts are added as false, like in the text format
+    for fact in RefCell::borrow(&node).all_facts() {
+        let symbol = RefCell::borrow(&fact).repr;
+        if !known.contains(&symbol) {
+            input.warn(
+                LintKind::MissingFact,
+                format!("Query for missing fact {}", symbol),
+            );
+            RefCell::borrow_mut(&fact).set(Resolve::False);
+            known.push(symbol);
+        }
+    }
+    input.query_expressions.push(node);
+    Ok(())
+}
+
+// Load a knowledge base from its JSON form, all errors are kept in `errors`
+// -- the location of a syntax error is in the content, the others have the path of the value
+pub fn parse_json(input: &mut Input, content: &str) -> Result<(), Error> {
+    let json: JsonInput = match serde_json::from_str(content) {
+        Ok(json) => json,
+        Err(error) => {
+            let message = error.to_string();
+            let message = message.split(" at line ").next().unwrap_or(&message);
+            let source = content.lines().nth(error.line().max(1) - 1).unwrap_or("");
+            let error = Error::new(ErrorKind::InvalidJson, message)
+                .at_column(error.column().max(1))
+                .at_line(error.line().max(1), source, 0);
+            input.errors.push(error.clone());
+            return Err(error);
+        }
+    };
+
+    for (index, fact) in json.facts.iter().enumerate() {
+        if let Err(error) = add_fact(input, fact, &format!("facts[{}]", index)) {
+            input.errors.push(error);
+        }
+    }
+    for (index, rule) in json.rules.iter().enumerate() {
+        let path = format!("rules[{}]", index);
+        match implication(input, rule, &path) {
+            Ok(Some(rule)) => input.add_rule(rule),
+            Ok(None) => input
+                .errors
+                .push(invalid(&path, "A rule is an implication or an equivalence")),
+            Err(error) => input.errors.push(error),
+        }
+    }
+    for (field, prefix, symbols) in [
+        ("initial_facts", "", &json.initial_facts),
+        ("false_facts", "!", &json.false_facts),
+        ("unknown_facts", "?", &json.unknown_facts),
+    ] {
+        for (index, symbol) in symbols.iter().enumerate() {
+            let result = check_symbol(*symbol, &format!("{}[{}]", field, index))
+                .and_then(|symbol| input.parse_initial_facts(&format!("={}{}", prefix, symbol)));
+            if let Err(error) = result {
+                input.errors.push(error);
+            }
+        }
+    }
+    for (index, query) in json.queries.iter().enumerate() {
+        if let Err(error) = add_query(input, query, &format!("queries[{}]", index)) {
+            input.errors.push(error);
+        }
+    }
+    input.reset();
+    match input.errors.first() {
+        Some(error) => Err(error.clone()),
+        None => Ok(()),
+    }
+}
+
+// Value of a node with the current value of its facts, nothing is resolved
+fn current_value(node: &Node) -> Resolve {
+    if let Some(fact) = &node.fact {
+        let value = *RefCell::borrow(fact).value.borrow();
+        if node.operator_eq(&Operator::Not) {
+            return value.not();
+        }
+        return value;
+    }
+    let left = current_value(&RefCell::borrow(node.left.as_ref().unwrap()));
+    let right = || current_value(&RefCell::borrow(node.right.as_ref().unwrap()));
+    match node.operator {
+        None => left,
+        Some(Operator::Not) => left.not(),
+        Some(Operator::And) => left.and(right()),
+        Some(Operator::Or) => left.or(right()),
+        Some(Operator::Xor) => left.xor(right()),
+        Some(Operator::Nand) => left.nand(right()),
+        Some(Operator::Nor) => left.nor(right()),
+        Some(Operator::Xnor) => left.xnor(right()),
+        Some(Operator::Implies) => left.implies(right()),
+        Some(Operator::IfAndOnlyIf) => left.if_and_only_if(right()),
+    }
+}
+
+fn unique_facts(node: &Node) -> Vec<Rc<RefCell<Fact>>> {
+    let mut facts: Vec<Rc<RefCell<Fact>>> = vec![];
+    for fact in node.all_facts() {
+        if !facts.iter().any(|other| Rc::ptr_eq(other, &fact)) {
+            facts.push(fact);
+        }
+    }
+    facts
+}
+
+// Proof of a resolved fact from the values found by the engine
+// -- a true fact is proven by its first rule with a true premise, the other values list all rules
+fn proof(input: &Input, fact: &Rc<RefCell<Fact>>, value: Resolve, branch: &mut Vec<char>) -> Proof {
+    let fact = RefCell::borrow(fact);
+    let resolved = *fact.resolved.borrow();
+    let mut proof = Proof {
+        fact: fact.repr,
+        value,
+        source: ProofSource::Default,
+        rules: vec![],
+    };
+    // An explicitly unknown fact made true by a rule is proved by the rule
+    let explicit = input
+        .explicit_facts
+        .iter()
+        .any(|(symbol, explicit)| *symbol == fact.repr && *explicit == value);
+    if (input.initial_facts.contains(&fact.repr) || explicit) && resolved {
+        proof.source = ProofSource::InitialFact;
+        return proof;
+    }
+    if fact.rules.is_empty() {
+        if fact.answers.is_some() && resolved {
+            proof.source = ProofSource::Answered;
+        }
+        return proof;
+    }
+    if branch.contains(&fact.repr) {
+        proof.source = ProofSource::Cycle;
+        return proof;
+    }
+    proof.source = ProofSource::Rules;
+    branch.push(fact.repr);
+    for rule in fact.rules.iter() {
+        let premise = RefCell::borrow(rule).premise(&fact.repr);
+        let premise = RefCell::borrow(&premise);
+        let premise_value = current_value(&premise);
+        if value.is_true() && !premise_value.is_true() {
+            continue;
+        }
+        proof.rules.push(ProofRule {
+            rule: format_node(&RefCell::borrow(rule)),
+            premise: premise_value,
+            facts: unique_facts(&premise)
+                .iter()
+                .map(|premise_fact| {
+                    let value = *RefCell::borrow(premise_fact).value.borrow();
+                    self::proof(input, premise_fact, value, branch)
+                })
+                .collect(),
+        });
+        if value.is_true() {
+            break;
+        }
+    }
+    branch.pop();
+    proof
+}
+
+// Resolve a fact of the Input with its proof
+pub fn fact_result(input: &Input, query: &char) -> QueryResult {
+    let fact_ref = input.facts.get(query).unwrap();
+    let fact = RefCell::borrow(fact_ref);
+    let mut path = vec![];
+    let result = fact.resolve(&mut path);
+    if result.is_err() {
+        fact.cleanup();
+    }
+    QueryResult {
+        query: query.to_string(),
+        description: fact.description.clone(),
+        value: result.as_ref().ok().copied(),
+        path,
+        proof: match &result {
+            Ok(value) => vec![proof(input, fact_ref, *value, &mut vec![])],
+            Err(_) => vec![],
+        },
+        error: result.err().map(|error| error.to_string()),
+    }
+}
+
+// Resolve every query of the Input with the proof of each of its facts
+pub fn query_results(input: &Input) -> Vec<QueryResult> {
+    let mut results: Vec<QueryResult> = input
+        .queries
+        .iter()
+        .map(|query| fact_result(input, query))
+        .collect();
+    for expression in input.query_expressions.iter() {
+        let expression = RefCell::borrow(expression);
+        let mut path = vec![];
+        let result = expression.resolve_query(&mut path);
+        if result.is_err() {
+            for fact in expression.all_facts() {
+                RefCell::borrow(&fact).cleanup();
+            }
+        }
+        results.push(QueryResult {
+            query: format_node(&expression),
+            description: None,
+            value: result.as_ref().ok().copied(),
+            path,
+            proof: match &result {
+                Ok(_) => unique_facts(&expression)
+                    .iter()
+                    .map(|fact| {
+                        let value = *RefCell::borrow(fact).value.borrow();
+                        proof(input, fact, value, &mut vec![])
+                    })
+                    .collect(),
+                Err(_) => vec![],
+            },
+            error: result.err().map(|error| error.to_string()),
+        });
+    }
+    results
+}
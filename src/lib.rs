@@ -1,2 +1,15 @@
+pub mod clips;
+pub mod dimacs;
+pub mod equivalence;
+pub mod error;
+pub mod format;
+pub mod graph;
 pub mod input;
+pub mod json;
+pub mod lint;
+pub mod lsp;
 pub mod node;
+pub mod parser;
+pub mod prolog;
+pub mod scenario;
+pub mod smtlib;
@@ -0,0 +1,394 @@
+use crate::{
+    error::Span,
+    format::format_node,
+    input::Input,
+    node::{Node, Operator},
+};
+use nom::{
+    bytes::complete::tag,
+    character::complete::{alphanumeric1, multispace0},
+    combinator::eof,
+    multi::separated_list1,
+    sequence::{delimited, tuple},
+    IResult,
+};
+use std::{cell::RefCell, collections::HashMap, fmt, rc::Rc};
+
+// Each lint has a stable code, a code is never reused for another lint
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LintKind {
+    DuplicateInitialFact,
+    UnusedInitialFact,
+    MissingFact,
+    TautologicalRule,
+    SelfImplyingRule,
+    ContradictoryConclusions,
+    DuplicateOperand,
+    UnsatisfiablePremise,
+    UnusedFact,
+    DuplicateDeclaration,
+    NoRules,
+    ConcludedAskableFact,
+    InvalidClauses,
+    UnknownLint,
+}
+
+// Only the warnings are errors with `--deny warnings`
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Severity {
+    Info,
+    Warning,
+}
+
+const LINTS: [LintKind; 14] = [
+    LintKind::DuplicateInitialFact,
+    LintKind::UnusedInitialFact,
+    LintKind::MissingFact,
+    LintKind::TautologicalRule,
+    LintKind::SelfImplyingRule,
+    LintKind::ContradictoryConclusions,
+    LintKind::DuplicateOperand,
+    LintKind::UnsatisfiablePremise,
+    LintKind::UnusedFact,
+    LintKind::DuplicateDeclaration,
+    LintKind::NoRules,
+    LintKind::ConcludedAskableFact,
+    LintKind::InvalidClauses,
+    LintKind::UnknownLint,
+];
+
+impl LintKind {
+    pub fn code(&self) -> &'static str {
+        match self {
+            LintKind::DuplicateInitialFact => "ES0001",
+            LintKind::UnusedInitialFact => "ES0002",
+            LintKind::MissingFact => "ES0003",
+            LintKind::TautologicalRule => "ES0004",
+            LintKind::SelfImplyingRule => "ES0005",
+            LintKind::ContradictoryConclusions => "ES0006",
+            LintKind::DuplicateOperand => "ES0007",
+            LintKind::UnsatisfiablePremise => "ES0008",
+            LintKind::UnusedFact => "ES0009",
+            LintKind::DuplicateDeclaration => "ES0010",
+            LintKind::NoRules => "ES0011",
+            LintKind::ConcludedAskableFact => "ES0012",
+            LintKind::InvalidClauses => "ES0013",
+            LintKind::UnknownLint => "ES0014",
+        }
+    }
+
+    pub fn severity(&self) -> Severity {
+        match self {
+            LintKind::DuplicateOperand | LintKind::UnusedFact => Severity::Info,
+            _ => Severity::Warning,
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<LintKind> {
+        LINTS.iter().find(|kind| kind.code() == code).copied()
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Info => write!(f, "info"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Lint {
+    pub kind: LintKind,
+    pub message: String,
+    pub span: Option<Span>,
+}
+
+impl Lint {
+    pub fn new(kind: LintKind, message: &str) -> Lint {
+        Lint {
+            kind,
+            message: message.to_string(),
+            span: None,
+        }
+    }
+
+    pub fn at(mut self, span: Option<Span>) -> Lint {
+        self.span = span;
+        self
+    }
+
+    pub fn code(&self) -> &'static str {
+        self.kind.code()
+    }
+
+    pub fn severity(&self) -> Severity {
+        self.kind.severity()
+    }
+}
+
+// warning[ES0004]: Tautological rule A and B implies A at rules.txt:3:1
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}[{}]: {}", self.severity(), self.code(), self.message)?;
+        if let Some(span) = &self.span {
+            write!(f, " at {}", span)?;
+        }
+        Ok(())
+    }
+}
+
+// Lint allowed in a whole file, or only on a line
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Allow {
+    pub file: Option<String>,
+    pub line: Option<usize>,
+    pub kind: LintKind,
+}
+
+impl Allow {
+    // A lint without span is only allowed in a whole file
+    pub fn allows(&self, lint: &Lint) -> bool {
+        self.kind == lint.kind
+            && match &lint.span {
+                Some(span) => {
+                    self.file == span.file && self.line.map_or(true, |line| line == span.line)
+                }
+                None => self.line.is_none(),
+            }
+    }
+}
+
+// regex: ^#\s*allow\(\s*(\w+)(?:\s*,\s*(\w+))*\s*\)\s*$
+pub(crate) fn allow(i: &str) -> IResult<&str, Vec<&str>> {
+    delimited(
+        tuple((tag("#"), multispace0, tag("allow("), multispace0)),
+        separated_list1(tuple((multispace0, tag(","), multispace0)), alphanumeric1),
+        tuple((multispace0, tag(")"), multispace0, eof)),
+    )(i)
+}
+
+type Rule = Rc<RefCell<Node>>;
+
+// Above this number of facts a rule is too large to be checked for every value of its facts
+const MAX_CHECKED_FACTS: usize = 16;
+
+fn unique_facts(node: &Node) -> Vec<char> {
+    let mut facts: Vec<char> = vec![];
+    for fact in node.all_facts() {
+        let repr = RefCell::borrow(&fact).repr;
+        if !facts.contains(&repr) {
+            facts.push(repr);
+        }
+    }
+    facts
+}
+
+// Check if a node is true for all or for none of the values of its facts
+fn evaluations(node: &Node) -> Option<(bool, bool)> {
+    let facts = unique_facts(node);
+    if facts.len() > MAX_CHECKED_FACTS {
+        return None;
+    }
+    let (mut always, mut never) = (true, true);
+    for model in 0..(1_u32 << facts.len()) {
+        let values: HashMap<char, bool> = facts
+            .iter()
+            .enumerate()
+            .map(|(index, fact)| (*fact, model & (1 << index) != 0))
+            .collect();
+        if node.evaluate(&values) {
+            never = false;
+        } else {
+            always = false;
+        }
+    }
+    Some((always, never))
+}
+
+// `X + X` in any binary expression of a node
+fn duplicate_operands(node: &Node, lints: &mut Vec<Lint>) {
+    let is_binary = matches!(
+        node.operator,
+        Some(
+            Operator::And
+                | Operator::Or
+                | Operator::Xor
+                | Operator::Nand
+                | Operator::Nor
+                | Operator::Xnor
+        )
+    );
+    if let (true, false, Some(left), Some(right)) =
+        (is_binary, node.has_fact(), &node.left, &node.right)
+    {
+        let left = format_node(&RefCell::borrow(left));
+        if left == format_node(&RefCell::borrow(right)) {
+            lints.push(
+                Lint::new(
+                    LintKind::DuplicateOperand,
+                    &format!("Duplicate operand {} in {}", left, format_node(node)),
+                )
+                .at(node.span.clone()),
+            );
+        }
+    }
+    for side in [&node.left, &node.right].into_iter().flatten() {
+        duplicate_operands(&RefCell::borrow(side), lints);
+    }
+}
+
+// Each fact of a conclusion and if it's negated, a double negation is not a negation
+fn polarities(node: &Node, negated: bool, facts: &mut Vec<(char, bool)>) {
+    let negated = negated != node.operator_eq(&Operator::Not);
+    if let Some(fact) = &node.fact {
+        let fact = (RefCell::borrow(fact).repr, negated);
+        if !facts.contains(&fact) {
+            facts.push(fact);
+        }
+    }
+    if let Some(left) = &node.left {
+        polarities(
+            &RefCell::borrow(left),
+            negated != node.operator_eq(&Operator::Implies),
+            facts,
+        );
+    }
+    if let Some(right) = &node.right {
+        polarities(&RefCell::borrow(right), negated, facts);
+    }
+}
+
+fn rule_lints(rule: &Node, lints: &mut Vec<Lint>) {
+    let left = RefCell::borrow(rule.left.as_ref().unwrap());
+    let right = RefCell::borrow(rule.right.as_ref().unwrap());
+    let lint = |kind: LintKind, message: String| Lint::new(kind, &message).at(rule.span.clone());
+    // A rule that can never be used is also true for any value of its facts, so it is only reported as unusable
+    // -- a tautological or unusable rule can also be self-implying
+    if rule.operator_eq(&Operator::Implies) && matches!(evaluations(&left), Some((_, true))) {
+        lints.push(lint(
+            LintKind::UnsatisfiablePremise,
+            format!("The premise of rule {} can never be true", rule),
+        ));
+    } else if let Some((true, _)) = evaluations(rule) {
+        lints.push(lint(
+            LintKind::TautologicalRule,
+            format!(
+                "Tautological rule {}, it's true for any value of its facts",
+                rule
+            ),
+        ));
+    }
+    if rule.operator_eq(&Operator::Implies) {
+        let facts: Vec<char> = right
+            .conclusion_facts()
+            .iter()
+            .map(|fact| RefCell::borrow(fact).repr)
+            .filter(|fact| left.contains_fact(fact))
+            .collect();
+        if let Some(fact) = facts.first() {
+            lints.push(lint(
+                LintKind::SelfImplyingRule,
+                format!("Self-implying rule {}, {} is in its premise", rule, fact),
+            ));
+        }
+    }
+    duplicate_operands(rule, lints);
+}
+
+// Lints of the rules and facts of a loaded knowledge base, the lints of each line are found while it's parsed
+pub fn lint(input: &Input) -> Vec<Lint> {
+    let mut lints: Vec<Lint> = vec![];
+    let rules: Vec<&Rc<RefCell<Node>>> = input
+        .rules
+        .iter()
+        .filter(|rule| !RefCell::borrow(rule).disabled)
+        .collect();
+    for rule in rules.iter() {
+        rule_lints(&RefCell::borrow(rule), &mut lints);
+    }
+
+    // The first rule that concludes each fact and the first that concludes its negation
+    let mut conclusions: Vec<(char, Option<Rule>, Option<Rule>)> = vec![];
+    for rule in rules.iter() {
+        let node = RefCell::borrow(rule);
+        if !node.operator_eq(&Operator::Implies) {
+            continue;
+        }
+        let mut facts: Vec<(char, bool)> = vec![];
+        polarities(
+            &RefCell::borrow(node.right.as_ref().unwrap()),
+            false,
+            &mut facts,
+        );
+        for (repr, negated) in facts {
+            let index = match conclusions.iter().position(|(fact, _, _)| *fact == repr) {
+                Some(index) => index,
+                None => {
+                    conclusions.push((repr, None, None));
+                    conclusions.len() - 1
+                }
+            };
+            let (_, positive, negative) = &mut conclusions[index];
+            let conclusion = if negated { negative } else { positive };
+            conclusion.get_or_insert_with(|| Rc::clone(rule));
+        }
+    }
+    for (fact, positive, negative) in conclusions.iter() {
+        if let (Some(positive), Some(negative)) = (positive, negative) {
+            let negative = RefCell::borrow(negative);
+            lints.push(
+                Lint::new(
+                    LintKind::ContradictoryConclusions,
+                    &format!(
+                        "{} is concluded by rule {} and its negation by rule {}",
+                        fact,
+                        RefCell::borrow(positive).located(),
+                        negative.located()
+                    ),
+                )
+                .at(negative.span.clone()),
+            );
+        }
+    }
+
+    // A described or askable fact that is never used
+    let mut used: Vec<char> = input
+        .initial_facts
+        .iter()
+        .chain(input.explicit_facts.iter().map(|(fact, _)| fact))
+        .chain(input.queries.iter())
+        .copied()
+        .collect();
+    for scenario in input.scenarios.iter() {
+        used.extend(scenario.initial_facts.iter());
+        used.extend(scenario.queries.iter());
+        used.extend(scenario.explicit_facts.iter().map(|(fact, _)| fact));
+    }
+    let expressions = input
+        .scenarios
+        .iter()
+        .flat_map(|scenario| scenario.query_expressions.iter());
+    for node in input
+        .rules
+        .iter()
+        .chain(input.query_expressions.iter())
+        .chain(expressions)
+    {
+        used.extend(unique_facts(&RefCell::borrow(node)));
+    }
+    let mut facts: Vec<&char> = input.facts.keys().collect();
+    facts.sort_unstable();
+    for symbol in facts {
+        let fact = RefCell::borrow(input.facts.get(symbol).unwrap());
+        if (fact.description.is_some() || input.askable.contains(symbol)) && !used.contains(symbol)
+        {
+            lints.push(Lint::new(
+                LintKind::UnusedFact,
+                &format!("Unused fact {}", fact.subject()),
+            ));
+        }
+    }
+    lints
+}
@@ -0,0 +1,439 @@
+use crate::{
+    error::{Error, Span},
+    format::{format_content, format_node},
+    input::Input,
+    lint::{Lint, Severity},
+    node::Node,
+};
+use serde_json::{json, Value};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    io::{self, BufRead, Write},
+};
+
+// Language server over stdio, each request is answered from the last content of its document
+// -- the positions are in characters, like the UTF-16 code units of the protocol for the characters of the rules
+pub struct Server {
+    documents: HashMap<String, String>,
+}
+
+impl Default for Server {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Path of a `file://` URI, with its escaped characters decoded
+pub fn uri_path(uri: &str) -> String {
+    let path = uri.strip_prefix("file://").unwrap_or(uri);
+    let mut bytes: Vec<u8> = vec![];
+    let mut rest = path.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        let escaped = tail
+            .get(..2)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (byte, escaped) {
+            (b'%', Some(escaped)) => {
+                bytes.push(escaped);
+                rest = &tail[2..];
+            }
+            _ => {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
+    }
+    String::from_utf8_lossy(&bytes).to_string()
+}
+
+pub fn path_uri(path: &str) -> String {
+    let mut uri = String::from("file://");
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-_.~".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            uri.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    uri
+}
+
+fn range(line: usize, start: usize, end: usize) -> Value {
+    json!({
+        "start": { "line": line, "character": start },
+        "end": { "line": line, "character": end },
+    })
+}
+
+fn location(span: &Span) -> Value {
+    json!({
+        "uri": path_uri(span.file.as_deref().unwrap_or_default()),
+        "range": range(span.line - 1, span.column - 1, span.end - 1),
+    })
+}
+
+// Every fact of a node with the span of its symbol, the symbol is the last character of a negated fact
+fn written_facts(node: &Node, facts: &mut Vec<(char, Span)>) {
+    if let (Some(fact), Some(span)) = (&node.fact, &node.span) {
+        facts.push((
+            RefCell::borrow(fact).repr,
+            Span {
+                column: span.end - 1,
+                ..span.clone()
+            },
+        ));
+    }
+    for side in [&node.left, &node.right].into_iter().flatten() {
+        written_facts(&RefCell::borrow(side), facts);
+    }
+}
+
+fn all_written_facts(input: &Input) -> Vec<(char, Span)> {
+    let mut facts = vec![];
+    let expressions = input
+        .scenarios
+        .iter()
+        .flat_map(|scenario| scenario.query_expressions.iter());
+    for node in input
+        .rules
+        .iter()
+        .chain(input.query_expressions.iter())
+        .chain(expressions)
+    {
+        written_facts(&RefCell::borrow(node), &mut facts);
+    }
+    facts
+}
+
+// Fact under a position, in a rule or an expression, or in a line of facts outside of its strings
+fn fact_at(input: &Input, path: &str, text: &str, line: usize, character: usize) -> Option<char> {
+    let written = all_written_facts(input).into_iter().find(|(_, span)| {
+        span.file.as_deref() == Some(path)
+            && span.line == line + 1
+            && span.column <= character + 1
+            && character < span.end - 1
+    });
+    if let Some((symbol, _)) = written {
+        return Some(symbol);
+    }
+    let source = text.lines().nth(line)?;
+    let code = source.trim_start();
+    let is_facts_line = ["=", "?", "@", "ask", "expect"]
+        .iter()
+        .any(|start| code.starts_with(start));
+    if !is_facts_line || code.starts_with("=>") {
+        return None;
+    }
+    let characters: Vec<char> = source.chars().collect();
+    let quoted = characters[..character.min(characters.len())]
+        .iter()
+        .filter(|c| **c == '"')
+        .count()
+        % 2
+        == 1;
+    characters
+        .get(character)
+        .filter(|c| c.is_ascii_uppercase() && !quoted && input.facts.contains_key(c))
+        .copied()
+}
+
+fn diagnostic(error: &Error, path: &str) -> Value {
+    let line = error.line.map_or(0, |line| line - 1);
+    let column = error.column.map_or(0, |column| column - 1);
+    // The errors of the included files are shown at the start of the document
+    let (line, column, message) = match &error.file {
+        Some(file) if file != path => (
+            0,
+            0,
+            format!(
+                "{}:{}: {}",
+                file,
+                error.line.map_or(String::new(), |line| line.to_string()),
+                error.message
+            ),
+        ),
+        _ => (line, column, error.message.clone()),
+    };
+    json!({
+        "range": range(line, column, column + 1),
+        "severity": 1,
+        "code": error.code(),
+        "source": "expert-system",
+        "message": message,
+    })
+}
+
+// The lints without span, or in the included files, are shown at the start of the document
+fn lint_diagnostic(lint: &Lint, path: &str) -> Value {
+    let (range, message) = match &lint.span {
+        Some(span) if span.file.as_deref() == Some(path) => (
+            range(span.line - 1, span.column - 1, span.end - 1),
+            lint.message.clone(),
+        ),
+        Some(span) => (range(0, 0, 0), format!("{}: {}", span, lint.message)),
+        None => (range(0, 0, 0), lint.message.clone()),
+    };
+    json!({
+        "range": range,
+        "severity": match lint.severity() {
+            Severity::Warning => 2,
+            Severity::Info => 3,
+        },
+        "code": lint.code(),
+        "source": "expert-system",
+        "message": message,
+    })
+}
+
+impl Server {
+    pub fn new() -> Server {
+        Server {
+            documents: HashMap::new(),
+        }
+    }
+
+    // Knowledge base of a document, with the path of the document
+    fn load(&self, uri: &str) -> Option<(Input, String, &str)> {
+        let text = self.documents.get(uri)?;
+        let path = uri_path(uri);
+        let mut input = Input::new();
+        let _ = input.load_content(&path, text);
+        Some((input, path, text))
+    }
+
+    fn diagnostics(&self, uri: &str) -> Value {
+        let diagnostics: Vec<Value> = match self.load(uri) {
+            Some((input, path, _)) => input
+                .errors
+                .iter()
+                .map(|error| diagnostic(error, &path))
+                .chain(
+                    input
+                        .warnings
+                        .iter()
+                        .map(|warning| lint_diagnostic(warning, &path)),
+                )
+                .collect(),
+            None => vec![],
+        };
+        json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/publishDiagnostics",
+            "params": { "uri": uri, "diagnostics": diagnostics },
+        })
+    }
+
+    // Knowledge base of the document of a position and the fact under it
+    fn position_fact(&self, params: &Value) -> Option<(Input, char)> {
+        let uri = params["textDocument"]["uri"].as_str()?;
+        let line = params["position"]["line"].as_u64()? as usize;
+        let character = params["position"]["character"].as_u64()? as usize;
+        let (input, path, text) = self.load(uri)?;
+        let symbol = fact_at(&input, &path, text, line, character)?;
+        Some((input, symbol))
+    }
+
+    fn hover(&self, params: &Value) -> Option<Value> {
+        let (input, symbol) = self.position_fact(params)?;
+        let fact = RefCell::borrow(input.facts.get(&symbol)?);
+        let mut lines = vec![match &fact.description {
+            Some(description) => format!("**{}** {}", symbol, description),
+            None => format!("**{}**", symbol),
+        }];
+        if input.initial_facts.contains(&symbol) {
+            lines.push("Initial fact".to_string());
+        }
+        if input.askable.contains(&symbol) {
+            lines.push(match &fact.prompt {
+                Some(prompt) => format!("Askable: {}", prompt),
+                None => "Askable".to_string(),
+            });
+        }
+        if fact.rules.is_empty() {
+            lines.push("Not concluded by any rule".to_string());
+        } else {
+            lines.push("Concluded by:".to_string());
+            for rule in fact.rules.iter() {
+                lines.push(format!("- `{}`", format_node(&RefCell::borrow(rule))));
+            }
+        }
+        Some(json!({
+            "contents": { "kind": "markdown", "value": lines.join("\n\n") },
+        }))
+    }
+
+    fn definition(&self, params: &Value) -> Option<Value> {
+        let (input, symbol) = self.position_fact(params)?;
+        let fact = RefCell::borrow(input.facts.get(&symbol)?);
+        let locations: Vec<Value> = fact
+            .rules
+            .iter()
+            .filter_map(|rule| RefCell::borrow(rule).span.as_ref().map(location))
+            .collect();
+        Some(Value::Array(locations))
+    }
+
+    fn references(&self, params: &Value) -> Option<Value> {
+        let (input, symbol) = self.position_fact(params)?;
+        let locations: Vec<Value> = all_written_facts(&input)
+            .iter()
+            .filter(|(repr, _)| *repr == symbol)
+            .map(|(_, span)| location(span))
+            .collect();
+        Some(Value::Array(locations))
+    }
+
+    // The whole document is replaced by its canonical form
+    fn formatting(&self, params: &Value) -> Option<Value> {
+        let text = self
+            .documents
+            .get(params["textDocument"]["uri"].as_str()?)?;
+        let formatted = format_content(text).ok()?;
+        if formatted == *text {
+            return Some(json!([]));
+        }
+        Some(json!([{
+            "range": {
+                "start": { "line": 0, "character": 0 },
+                "end": { "line": text.lines().count() + 1, "character": 0 },
+            },
+            "newText": formatted,
+        }]))
+    }
+
+    fn completion(&self, params: &Value) -> Option<Value> {
+        let (input, _, _) = self.load(params["textDocument"]["uri"].as_str()?)?;
+        let mut symbols: Vec<&char> = input.facts.keys().collect();
+        symbols.sort_unstable();
+        let items: Vec<Value> = symbols
+            .into_iter()
+            .map(|symbol| {
+                let fact = RefCell::borrow(input.facts.get(symbol).unwrap());
+                let mut item = json!({ "label": symbol.to_string(), "kind": 6 });
+                if let Some(description) = &fact.description {
+                    item["detail"] = json!(description);
+                }
+                item
+            })
+            .collect();
+        Some(Value::Array(items))
+    }
+
+    fn open(&mut self, params: &Value) -> Option<Value> {
+        let uri = params["textDocument"]["uri"].as_str()?;
+        let text = params["textDocument"]["text"].as_str()?;
+        self.documents.insert(uri.to_string(), text.to_string());
+        Some(self.diagnostics(uri))
+    }
+
+    // The whole document is sent with each change
+    fn change(&mut self, params: &Value) -> Option<Value> {
+        let uri = params["textDocument"]["uri"].as_str()?;
+        let text = params["contentChanges"].as_array()?.last()?["text"].as_str()?;
+        self.documents.insert(uri.to_string(), text.to_string());
+        Some(self.diagnostics(uri))
+    }
+
+    fn close(&mut self, params: &Value) -> Option<Value> {
+        let uri = params["textDocument"]["uri"].as_str()?;
+        self.documents.remove(uri);
+        Some(self.diagnostics(uri))
+    }
+
+    // Responses and notifications for a message, `None` when the client asks to exit
+    pub fn handle(&mut self, message: &Value) -> Option<Vec<Value>> {
+        let params = &message["params"];
+        let method = message["method"].as_str().unwrap_or_default();
+        let result = match method {
+            "initialize" => Some(json!({
+                "capabilities": {
+                    "textDocumentSync": 1,
+                    "hoverProvider": true,
+                    "definitionProvider": true,
+                    "referencesProvider": true,
+                    "documentFormattingProvider": true,
+                    "completionProvider": {},
+                },
+                "serverInfo": { "name": "expert-system", "version": env!("CARGO_PKG_VERSION") },
+            })),
+            "shutdown" => Some(Value::Null),
+            "exit" => return None,
+            "textDocument/didOpen" => return Some(self.open(params).into_iter().collect()),
+            "textDocument/didChange" => return Some(self.change(params).into_iter().collect()),
+            "textDocument/didClose" => return Some(self.close(params).into_iter().collect()),
+            "textDocument/hover" => Some(self.hover(params).unwrap_or(Value::Null)),
+            "textDocument/definition" => Some(self.definition(params).unwrap_or(Value::Null)),
+            "textDocument/references" => Some(self.references(params).unwrap_or(Value::Null)),
+            "textDocument/formatting" => Some(self.formatting(params).unwrap_or(Value::Null)),
+            "textDocument/completion" => Some(self.completion(params).unwrap_or(Value::Null)),
+            _ => None,
+        };
+        // Notifications have no id and no response
+        let id = match message.get("id") {
+            Some(id) => id,
+            None => return Some(vec![]),
+        };
+        Some(vec![match result {
+            Some(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            None => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": -32601, "message": format!("Method not found: {}", method) },
+            }),
+        }])
+    }
+}
+
+// Message of the base protocol, its content is after a `Content-Length` header
+fn read_message(reader: &mut impl BufRead) -> io::Result<Option<Value>> {
+    let mut length: Option<usize> = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                length = value.trim().parse().ok();
+            }
+        }
+    }
+    let mut content = vec![0; length.unwrap_or(0)];
+    reader.read_exact(&mut content)?;
+    Ok(Some(
+        serde_json::from_slice(&content).unwrap_or(Value::Null),
+    ))
+}
+
+fn write_message(writer: &mut impl Write, message: &Value) -> io::Result<()> {
+    let content = message.to_string();
+    write!(
+        writer,
+        "Content-Length: {}\r\n\r\n{}",
+        content.len(),
+        content
+    )?;
+    writer.flush()
+}
+
+// Serve until the client asks to exit or closes the input
+pub fn serve(mut reader: impl BufRead, mut writer: impl Write) -> io::Result<()> {
+    let mut server = Server::new();
+    while let Some(message) = read_message(&mut reader)? {
+        match server.handle(&message) {
+            Some(messages) => {
+                for message in messages.iter() {
+                    write_message(&mut writer, message)?;
+                }
+            }
+            None => break,
+        }
+    }
+    Ok(())
+}
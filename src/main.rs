@@ -1,13 +1,40 @@
-use clap::{arg, command};
+use clap::{arg, command, Command};
 use colored::Colorize;
-use expert_system::input::Input;
+use expert_system::{
+    dimacs::to_dimacs,
+    equivalence::{check_equivalence, shared_facts},
+    format::format_content,
+    graph::{proof_graph, rule_graph, to_dot, to_mermaid},
+    input::Input,
+    json::{export_input, fact_result, query_results, Diagnostic, FileReport},
+    node::{Answers, Fact, Notation, Resolve, ResolveError},
+    prolog::to_prolog,
+    scenario::run_scenario,
+    smtlib::to_smtlib,
+};
 use std::{
     cell::RefCell,
+    fs,
     io::{self, Write},
+    process,
+    rc::Rc,
 };
 
+pub mod clips;
+pub mod dimacs;
+pub mod equivalence;
+pub mod error;
+pub mod format;
+pub mod graph;
 pub mod input;
+pub mod json;
+pub mod lint;
+pub mod lsp;
 pub mod node;
+pub mod parser;
+pub mod prolog;
+pub mod scenario;
+pub mod smtlib;
 
 fn interactive_line(line: &str) {
     println!("{}  {}", "$".yellow().on_black(), line);
@@ -18,14 +45,364 @@ fn interactive_input() {
     io::stdout().flush().unwrap();
 }
 
+fn interactive_read(prompt: &str) -> io::Result<String> {
+    interactive_line(prompt);
+    interactive_input();
+    let mut line = String::new();
+    io::stdin().read_line(&mut line)?;
+    Ok(line.trim().to_string())
+}
+
+fn show_result(
+    query: &str,
+    description: Option<&str>,
+    result: Result<Resolve, ResolveError>,
+    path: &[String],
+    visualize: bool,
+) {
+    if visualize {
+        path.iter()
+            .map(|path| println!("{}  {}", "?".purple().on_black(), path))
+            .for_each(drop);
+    }
+    let description = match description {
+        Some(description) => format!(" ({})", description),
+        None => String::new(),
+    };
+    if let Ok(result) = result {
+        println!(
+            "{}{} {}{}",
+            "?".normal().on_purple(),
+            query.bright_cyan().on_purple(),
+            result.colored(),
+            description
+        );
+    } else {
+        println!(
+            "{}{} {}",
+            "?".normal().on_purple(),
+            query.bright_cyan().on_purple(),
+            result.unwrap_err().to_string().red()
+        );
+    }
+}
+
+// Ask the value of the askable facts on the terminal until there is a valid answer
+#[derive(Debug)]
+struct Terminal;
+
+impl Answers for Terminal {
+    fn answer(&self, fact: &Fact) -> Result<Resolve, String> {
+        loop {
+            print!(
+                "{}  {} {}  ",
+                "?".normal().on_purple(),
+                fact.question(),
+                "[y/n/unknown]".purple()
+            );
+            io::stdout().flush().unwrap();
+            let mut answer = String::new();
+            match io::stdin().read_line(&mut answer) {
+                Err(error) => return Err(format!("Error while reading answer {}", error)),
+                Ok(0) => return Err(format!("No answer for fact {}", fact.repr)),
+                Ok(_) => {}
+            }
+            if let Some(value) = Fact::answer(&answer) {
+                return Ok(value);
+            }
+        }
+    }
+}
+
+fn load_input(file_path: &str) -> Option<Input> {
+    let mut input = Input::new();
+    if input.load_file(file_path).is_err() {
+        input.show_warnings();
+        input.show_errors();
+        return None;
+    }
+    Some(input)
+}
+
+fn equivalence(first_path: &str, second_path: &str) {
+    let first = load_input(first_path);
+    let second = load_input(second_path);
+    if first.is_none() || second.is_none() {
+        process::exit(2);
+    }
+    let (mut first, mut second) = (first.unwrap(), second.unwrap());
+    let facts = shared_facts(&first, &second);
+    println!(
+        "{}  {}",
+        "=".normal().on_green(),
+        format!("Shared facts {}", facts.iter().collect::<String>()).green()
+    );
+    match check_equivalence(&mut first, &mut second) {
+        Ok(None) => {
+            println!(
+                "{}  {} and {} are {}",
+                "?".normal().on_purple(),
+                first_path,
+                second_path,
+                "equivalent".cyan()
+            );
+        }
+        Ok(Some(counterexample)) => {
+            let show_result = |result: &Result<Resolve, String>| match result {
+                Ok(result) => result.to_string(),
+                Err(error) => error.to_string(),
+            };
+            println!(
+                "{}  {} and {} are {}",
+                "?".normal().on_purple(),
+                first_path,
+                second_path,
+                "not equivalent".yellow()
+            );
+            println!(
+                "{}  with ={} the query ?{} is {} in {} and {} in {}",
+                "!".red().on_yellow(),
+                counterexample.initial_facts.iter().collect::<String>(),
+                counterexample.query,
+                show_result(&counterexample.first),
+                first_path,
+                show_result(&counterexample.second),
+                second_path
+            );
+            process::exit(1);
+        }
+        Err(error) => {
+            eprintln!("{}", error.red());
+            process::exit(2);
+        }
+    }
+}
+
+// Print the knowledge base of an input file in another format,
+// or the result of a single query with its proof
+fn export(file_path: &str, format: &str, query: Option<&str>) {
+    let input = load_input(file_path);
+    if input.is_none() {
+        process::exit(1);
+    }
+    let input = input.unwrap();
+    let result = query.map(|query| {
+        let mut symbols = query.chars();
+        match (symbols.next(), symbols.next()) {
+            (Some(symbol), None) if input.facts.contains_key(&symbol) => {
+                fact_result(&input, &symbol)
+            }
+            _ => {
+                eprintln!("{}", format!("Unknown fact {}", query).red());
+                process::exit(2);
+            }
+        }
+    });
+    let graph = || match &result {
+        Some(result) => proof_graph(&input, result),
+        None => rule_graph(&input),
+    };
+    match format {
+        "json" => println!(
+            "{}",
+            match &result {
+                Some(result) => serde_json::to_string_pretty(result),
+                None => serde_json::to_string_pretty(&export_input(&input)),
+            }
+            .unwrap()
+        ),
+        "dot" => print!("{}", to_dot(&graph())),
+        "mermaid" => print!("{}", to_mermaid(&graph())),
+        "dimacs" => print!("{}", to_dimacs(&input)),
+        "smtlib" => print!("{}", to_smtlib(&input)),
+        "prolog" => print!("{}", to_prolog(&input)),
+        _ => unreachable!(),
+    }
+}
+
+// Resolve the queries of each file and print all results as a single JSON document
+fn json_results(file_paths: &[&str], deny_warnings: bool) {
+    let mut reports: Vec<FileReport> = vec![];
+    for file_path in file_paths {
+        let mut input = Input::new();
+        input.deny_warnings = deny_warnings;
+        let loaded = input.load_file(file_path).is_ok();
+        reports.push(FileReport {
+            file: file_path.to_string(),
+            warnings: input.warnings.iter().map(Diagnostic::from).collect(),
+            errors: input.errors.iter().map(Diagnostic::from).collect(),
+            results: if loaded {
+                query_results(&input)
+            } else {
+                vec![]
+            },
+        });
+    }
+    println!("{}", serde_json::to_string_pretty(&reports).unwrap());
+    if reports.iter().any(|report| !report.errors.is_empty()) {
+        process::exit(1);
+    }
+}
+
+// Write each file in its canonical form, or only list the files that would change
+fn format_files(file_paths: &[&str], check: bool) {
+    let mut failed = false;
+    for file_path in file_paths {
+        // Check the whole file with its includes before formatting it
+        if load_input(file_path).is_none() {
+            failed = true;
+            continue;
+        }
+        let content = fs::read_to_string(file_path).unwrap();
+        let formatted = match format_content(&content) {
+            Ok(formatted) => formatted,
+            Err(error) => {
+                eprintln!("{}", error.in_file(file_path));
+                failed = true;
+                continue;
+            }
+        };
+        if formatted == content {
+            continue;
+        }
+        if check {
+            println!("{}  {} is not formatted", "!".red().on_yellow(), file_path);
+            failed = true;
+        } else if let Err(error) = fs::write(file_path, formatted) {
+            eprintln!("Failed to write {}: {}", file_path, error);
+            failed = true;
+        } else {
+            println!("{}  Formatted {}", "|".normal().on_blue(), file_path);
+        }
+    }
+    if failed {
+        process::exit(1);
+    }
+}
+
+// Run the scenarios of each file, the expected and actual answers of a failed scenario are shown as a diff
+fn test_scenarios(file_paths: &[&str]) {
+    let (mut passed, mut failed) = (0, 0);
+    let mut failed_files = 0;
+    for file_path in file_paths {
+        println!("{}", format!("#  {}", file_path).black().on_white());
+        let input = load_input(file_path);
+        if input.is_none() {
+            failed_files += 1;
+            continue;
+        }
+        let mut input = input.unwrap();
+        if input.scenarios.is_empty() {
+            println!("{}  {}", "!".red().on_yellow(), "No scenarios".yellow());
+            continue;
+        }
+        let scenarios = std::mem::take(&mut input.scenarios);
+        for scenario in scenarios.iter() {
+            let outcomes = run_scenario(&mut input, scenario);
+            if outcomes.iter().all(|outcome| outcome.passed()) {
+                passed += 1;
+                println!("{}  {}", "PASS".normal().on_green(), scenario.name);
+                continue;
+            }
+            failed += 1;
+            println!(
+                "{}  {} (line {})",
+                "FAIL".normal().on_red(),
+                scenario.name,
+                scenario.line
+            );
+            for outcome in outcomes.iter().filter(|outcome| !outcome.passed()) {
+                let actual = match &outcome.actual {
+                    Ok(actual) => actual.to_string(),
+                    Err(error) => error.to_string(),
+                };
+                println!(
+                    "{}",
+                    format!("-  {} {}", outcome.fact, outcome.expected).green()
+                );
+                println!("{}", format!("+  {} {}", outcome.fact, actual).red());
+            }
+        }
+    }
+    let summary = format!("{} passed, {} failed", passed, failed);
+    if failed > 0 || failed_files > 0 {
+        println!("{}", summary.red());
+        process::exit(1);
+    }
+    println!("{}", summary.green());
+}
+
 fn main() {
     let matches = command!()
+        .subcommand_negates_reqs(true)
+        .subcommand(
+            Command::new("equiv")
+                .about("Check if two input files are equivalent over their shared facts")
+                .arg(arg!(<first> "Path to the first input file").forbid_empty_values(true))
+                .arg(arg!(<second> "Path to the second input file").forbid_empty_values(true)),
+        )
+        .subcommand(
+            Command::new("test")
+                .about("Check the expected answers of the scenarios of each input file")
+                .arg(
+                    arg!(<file_paths> "Path to the input file(s)")
+                        .takes_value(true)
+                        .multiple_values(true)
+                        .forbid_empty_values(true),
+                ),
+        )
+        .subcommand(
+            Command::new("fmt")
+                .about("Write each input file in its canonical form")
+                .arg(
+                    arg!(<file_paths> "Path to the input file(s)")
+                        .takes_value(true)
+                        .multiple_values(true)
+                        .forbid_empty_values(true),
+                )
+                .arg(
+                    arg!(-c --check "Only list the files that are not formatted")
+                        .required(false)
+                        .takes_value(false),
+                ),
+        )
+        .subcommand(
+            Command::new("lsp").about("Serve the language server protocol over stdio for the rule files"),
+        )
+        .subcommand(
+            Command::new("export")
+                .about("Print the knowledge base of an input file in another format")
+                .arg(arg!(<file_path> "Path to the input file").forbid_empty_values(true))
+                .arg(
+                    arg!(-f --format <format> "Format of the knowledge base")
+                        .required(false)
+                        .possible_values(["json", "dot", "mermaid", "dimacs", "smtlib", "prolog"])
+                        .default_value("json"),
+                )
+                .arg(
+                    arg!(-q --query <fact> "Only export the proof of a fact")
+                        .required(false)
+                        .forbid_empty_values(true),
+                ),
+        )
         .arg(
             arg!(<file_paths> "Path to the input file(s)")
                 .takes_value(true)
                 .multiple_values(true)
                 .forbid_empty_values(true),
         )
+        .arg(
+            arg!(-o --output <format> "Format of the query results")
+                .required(false)
+                .possible_values(["text", "json"])
+                .default_value("text")
+                .conflicts_with_all(&["interactive", "ask"]),
+        )
+        .arg(
+            arg!(-n --notation <notation> "Notation of the operators in the rules and query expressions")
+                .required(false)
+                .possible_values(["ascii", "unicode", "words"]),
+        )
         .arg(
             arg!(-v --visualize ... "Visualize the path to resolve a query")
                 .required(false)
@@ -38,19 +415,83 @@ fn main() {
                 .takes_value(false)
                 .multiple_values(false),
         )
+        .arg(
+            arg!(-a --ask ... "Ask for the value of askable facts")
+                .required(false)
+                .takes_value(false)
+                .multiple_values(false),
+        )
+        .arg(
+            arg!(--deny <level> "Fail on the lints of this level")
+                .required(false)
+                .possible_values(["warnings"]),
+        )
         .get_matches();
 
+    if let Some(matches) = matches.subcommand_matches("equiv") {
+        equivalence(
+            matches.value_of("first").unwrap(),
+            matches.value_of("second").unwrap(),
+        );
+        return;
+    }
+    if let Some(matches) = matches.subcommand_matches("test") {
+        let file_paths: Vec<_> = matches.values_of("file_paths").unwrap().collect();
+        test_scenarios(&file_paths);
+        return;
+    }
+    if let Some(matches) = matches.subcommand_matches("fmt") {
+        let file_paths: Vec<_> = matches.values_of("file_paths").unwrap().collect();
+        format_files(&file_paths, matches.is_present("check"));
+        return;
+    }
+    if matches.subcommand_matches("lsp").is_some() {
+        if let Err(error) = lsp::serve(io::stdin().lock(), io::stdout()) {
+            eprintln!("Language server error {}", error);
+            process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(matches) = matches.subcommand_matches("export") {
+        export(
+            matches.value_of("file_path").unwrap(),
+            matches.value_of("format").unwrap(),
+            matches.value_of("query"),
+        );
+        return;
+    }
+
+    let notation = match matches.value_of("notation") {
+        Some("unicode") => Notation::Unicode,
+        Some("words") => Notation::Words,
+        _ => Notation::Ascii,
+    };
+
     // Parse input and convert the rules to a tree
     let file_paths: Vec<_> = matches.values_of("file_paths").unwrap().collect();
+    let deny_warnings = matches.value_of("deny") == Some("warnings");
+    if matches.value_of("output") == Some("json") {
+        json_results(&file_paths, deny_warnings);
+        return;
+    }
+    let mut failed = false;
     for file_path in file_paths {
         println!("{}", format!("#  {}", file_path).black().on_white());
         let mut input = Input::new();
-        let load_result = input.load_file(file_path);
-        if load_result.is_err() {
-            eprintln!("Failed to parse input file: {}", load_result.unwrap_err());
+        input.deny_warnings = deny_warnings;
+        input.notation = notation;
+        if input.load_file(file_path).is_err() {
+            input.show_warnings();
+            input.show_errors();
+            failed = true;
             continue;
         }
+        if matches.is_present("ask") {
+            input.set_answers(Some(Rc::new(Terminal)));
+        }
         input.show_warnings();
+        input.show_descriptions();
         input.show_rules();
         input.show_initial_facts();
 
@@ -63,48 +504,50 @@ fn main() {
                 let mut path: Vec<String> = vec![];
                 let fact = RefCell::borrow(input.facts.get(query).unwrap().as_ref());
                 let result = fact.resolve(&mut path);
-                if visualize {
-                    path.iter()
-                        .map(|path| println!("{}  {}", "?".purple().on_black(), path))
-                        .for_each(drop);
-                }
-                if let Ok(result) = result {
-                    println!(
-                        "{}{} {}",
-                        "?".normal().on_purple(),
-                        format!("{}", query).bright_cyan().on_purple(),
-                        if result.is_true() {
-                            format!("{}", "true".cyan())
-                        } else if result.is_ambiguous() {
-                            format!("{}", "ambiguous".purple())
-                        } else {
-                            format!("{}", "false".yellow())
-                        }
-                    );
-                } else {
+                if result.is_err() {
                     fact.cleanup();
-                    println!(
-                        "{}{} {}",
-                        "?".normal().on_purple(),
-                        format!("{}", query).bright_cyan().on_purple(),
-                        result.unwrap_err().to_string().red()
-                    );
                 }
+                show_result(
+                    &query.to_string(),
+                    fact.description.as_deref(),
+                    result,
+                    &path,
+                    visualize,
+                );
+            }
+            for expression in input.query_expressions.iter() {
+                let mut path: Vec<String> = vec![];
+                let expression = RefCell::borrow(expression);
+                let result = expression.resolve_query(&mut path);
+                if result.is_err() {
+                    for fact in expression.all_facts() {
+                        RefCell::borrow(&fact).cleanup();
+                    }
+                }
+                show_result(
+                    &expression.short_in(input.notation),
+                    None,
+                    result,
+                    &path,
+                    visualize,
+                );
             }
 
             // Interactive mode to update rules, facts and queries
             if matches.is_present("interactive") {
                 let mut search_command = true;
+                // The rules and queries of the interactive mode are on its own lines
+                let mut line_number = 0;
                 while search_command {
                     interactive_line(
-                        "[e]xec [r]ule [?]query [f]act [n]ext [v]isualize [h]elp [q]uit",
+                        "[e]xec [r]ule [d]isable [i]nspect [x]remove [?]query [f]act [a]sk [n]ext [v]isualize [h]elp [q]uit",
                     );
                     interactive_input();
                     let mut command: String = String::new();
                     let read_input = io::stdin().read_line(&mut command);
                     command = command.trim().to_string();
-                    if read_input.is_err() {
-                        eprintln!("Error while reading input {}", read_input.unwrap_err());
+                    if let Err(error) = read_input {
+                        eprintln!("Error while reading input {}", error);
                         return;
                     }
                     // Resolve all of the current queries
@@ -117,6 +560,7 @@ fn main() {
                     }
                     // Show the current rules, initial facts and queries
                     else if command == "s" || command == "show" {
+                        input.show_descriptions();
                         input.show_rules();
                         input.show_initial_facts();
                         input.show_queries();
@@ -126,21 +570,70 @@ fn main() {
                         interactive_line("Add a rule, example: `A => B`");
                         interactive_input();
                         let mut rule: String = String::new();
-                        let read_input = io::stdin().read_line(&mut rule);
-                        if read_input.is_ok() {
-                            let result = input.parse_rule(rule.trim());
-                            if result.is_err() {
+                        if let Err(error) = io::stdin().read_line(&mut rule) {
+                            eprintln!("Error while reading command {}", error);
+                            return;
+                        }
+                        line_number += 1;
+                        input.set_line(Some("<interactive>"), line_number, rule.trim());
+                        input.clear_diagnostics();
+                        if let Err(error) = input.parse_rule(rule.trim()) {
+                            interactive_line(&format!("{}", error.to_string().red()));
+                        } else {
+                            input.show_warnings();
+                            input.show_rules();
+                        }
+                    }
+                    // Disable or enable a labelled rule
+                    else if command == "d" || command == "disable" {
+                        let label = match interactive_read("Toggle a rule, example: `overheat`") {
+                            Ok(label) => label,
+                            Err(error) => {
+                                eprintln!("Error while reading command {}", error);
+                                return;
+                            }
+                        };
+                        match input.toggle_rule(&label) {
+                            Ok(disabled) => {
                                 interactive_line(&format!(
-                                    "{}",
-                                    result.unwrap_err().to_string().red()
+                                    "rule {} toggled {}",
+                                    label,
+                                    if disabled {
+                                        "off".yellow()
+                                    } else {
+                                        "on".cyan()
+                                    }
                                 ));
-                            } else {
-                                input.show_warnings();
                                 input.show_rules();
                             }
-                        } else {
-                            eprintln!("Error while reading command {}", read_input.unwrap_err());
-                            return;
+                            Err(error) => interactive_line(&format!("{}", error.red())),
+                        }
+                    }
+                    // Show a labelled rule and its metadata
+                    else if command == "i" || command == "inspect" {
+                        let label = match interactive_read("Inspect a rule, example: `overheat`") {
+                            Ok(label) => label,
+                            Err(error) => {
+                                eprintln!("Error while reading command {}", error);
+                                return;
+                            }
+                        };
+                        if let Err(error) = input.show_rule(&label) {
+                            interactive_line(&format!("{}", error.red()));
+                        }
+                    }
+                    // Remove a labelled rule
+                    else if command == "x" || command == "remove" {
+                        let label = match interactive_read("Remove a rule, example: `overheat`") {
+                            Ok(label) => label,
+                            Err(error) => {
+                                eprintln!("Error while reading command {}", error);
+                                return;
+                            }
+                        };
+                        match input.remove_rule(&label) {
+                            Ok(_) => input.show_rules(),
+                            Err(error) => interactive_line(&format!("{}", error.red())),
                         }
                     }
                     // Set *all* of the initial facts
@@ -148,44 +641,39 @@ fn main() {
                         interactive_line("Set all initial facts, example: `ABC`");
                         interactive_input();
                         let mut facts: String = String::new();
-                        let read_input = io::stdin().read_line(&mut facts);
-                        if read_input.is_ok() {
-                            let result = input.reparse_initial_facts(&format!("={}", facts.trim()));
-                            if result.is_err() {
-                                interactive_line(&format!(
-                                    "{}",
-                                    result.unwrap_err().to_string().red()
-                                ));
-                            } else {
-                                input.show_warnings();
-                                input.show_initial_facts();
-                            }
-                        } else {
-                            eprintln!("Error while reading command {}", read_input.unwrap_err());
+                        if let Err(error) = io::stdin().read_line(&mut facts) {
+                            eprintln!("Error while reading command {}", error);
                             return;
                         }
+                        if let Err(error) =
+                            input.reparse_initial_facts(&format!("={}", facts.trim()))
+                        {
+                            interactive_line(&format!("{}", error.to_string().red()));
+                        } else {
+                            input.show_warnings();
+                            input.show_initial_facts();
+                        }
                     }
                     // Set *all* of the queries
                     else if command == "?" || command == "queries" {
-                        interactive_line("Set all queries, example: `ABC`");
+                        interactive_line(
+                            "Set all queries, example: `ABC` or `(A + B) | !C, A => D`",
+                        );
                         interactive_input();
                         let mut queries: String = String::new();
-                        let read_input = io::stdin().read_line(&mut queries);
-                        if read_input.is_ok() {
-                            let result = input.reparse_queries(&format!("?{}", queries.trim()));
-                            if result.is_err() {
-                                interactive_line(&format!(
-                                    "{}",
-                                    result.unwrap_err().to_string().red()
-                                ));
-                            } else {
-                                input.show_warnings();
-                                input.show_queries();
-                            }
-                        } else {
-                            eprintln!("Error while reading command {}", read_input.unwrap_err());
+                        if let Err(error) = io::stdin().read_line(&mut queries) {
+                            eprintln!("Error while reading command {}", error);
                             return;
                         }
+                        let queries = format!("?{}", queries.trim());
+                        line_number += 1;
+                        input.set_line(Some("<interactive>"), line_number, &queries);
+                        if let Err(error) = input.reparse_queries(&queries) {
+                            interactive_line(&format!("{}", error.to_string().red()));
+                        } else {
+                            input.show_warnings();
+                            input.show_queries();
+                        }
                     }
                     // Next file
                     else if command == "n" || command == "next" {
@@ -201,6 +689,18 @@ fn main() {
                             interactive_line(&format!("visualization toggled {}", "off".yellow()));
                         }
                     }
+                    // Toggle asking for askable facts
+                    else if command == "a" || command == "ask" {
+                        input.set_answers(match input.answers {
+                            Some(_) => None,
+                            None => Some(Rc::new(Terminal)),
+                        });
+                        if input.answers.is_some() {
+                            interactive_line(&format!("asking toggled {}", "on".cyan()));
+                        } else {
+                            interactive_line(&format!("asking toggled {}", "off".yellow()));
+                        }
+                    }
                     // Quit the program
                     else if command == "q" || command == "quit" || command == "exit" {
                         return;
@@ -212,8 +712,12 @@ fn main() {
                             "s, show\tShow the current rules, initial facts and queries",
                         );
                         interactive_line("r, rule\tAdd a rule");
+                        interactive_line("d, disable\tDisable or enable a rule by its label");
+                        interactive_line("i, inspect\tShow a rule by its label");
+                        interactive_line("x, remove\tRemove a rule by its label");
                         interactive_line("f, facts\tSet the initial facts");
                         interactive_line("?, queries\tSet the queries to resolve");
+                        interactive_line("a, ask\tToggle asking for askable facts");
                         interactive_line("n, next\tGo to the next file");
                         interactive_line("v, visualize\tToggle visualization");
                         interactive_line("h, help\tPrint this help");
@@ -228,4 +732,7 @@ fn main() {
             }
         }
     }
+    if failed {
+        process::exit(1);
+    }
 }
@@ -1,21 +1,96 @@
-use colored::Colorize;
+use crate::error::Span;
+use colored::{ColoredString, Colorize};
+use serde::Serialize;
 use std::{
     cell::RefCell,
+    collections::HashMap,
     fmt::{self, Debug},
     rc::Rc,
 };
 
-#[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+// An error of the resolution, a rule that depends on itself is kept apart
+// as it makes an IfAndOnlyIf rule false
+#[derive(Clone, Debug, PartialEq)]
+pub enum ResolveError {
+    InfiniteRule(String),
+    Message(String),
+}
+
+impl fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ResolveError::InfiniteRule(rule) => write!(f, "Infinite rule {}", rule),
+            ResolveError::Message(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl From<String> for ResolveError {
+    fn from(message: String) -> Self {
+        ResolveError::Message(message)
+    }
+}
+
+// Number of unknown facts above which the models of an IfAndOnlyIf rule are not searched
+pub const MAX_EQUIVALENCE_FACTS: usize = 12;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum Operator {
     Not,
     And,
     Or,
     Xor,
+    Nand,
+    Nor,
+    Xnor,
     Implies,
     IfAndOnlyIf,
 }
 
+// Spelling of the operators in the output
 #[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Notation {
+    Ascii,
+    Unicode,
+    Words,
+}
+
+impl Notation {
+    pub fn spelling(&self, operator: Operator) -> &'static str {
+        match (self, operator) {
+            (Notation::Ascii, Operator::Not) => "!",
+            (Notation::Ascii, Operator::And) => "+",
+            (Notation::Ascii, Operator::Or) => "|",
+            (Notation::Ascii, Operator::Xor) => "^",
+            (Notation::Ascii, Operator::Nand) => "!+",
+            (Notation::Ascii, Operator::Nor) => "!|",
+            (Notation::Ascii, Operator::Xnor) => "!^",
+            (Notation::Ascii, Operator::Implies) => "=>",
+            (Notation::Ascii, Operator::IfAndOnlyIf) => "<=>",
+            (Notation::Unicode, Operator::Not) => "¬",
+            (Notation::Unicode, Operator::And) => "∧",
+            (Notation::Unicode, Operator::Or) => "∨",
+            (Notation::Unicode, Operator::Xor) => "⊕",
+            (Notation::Unicode, Operator::Nand) => "⊼",
+            (Notation::Unicode, Operator::Nor) => "⊽",
+            (Notation::Unicode, Operator::Xnor) => "⊙",
+            (Notation::Unicode, Operator::Implies) => "→",
+            (Notation::Unicode, Operator::IfAndOnlyIf) => "↔",
+            (Notation::Words, Operator::Not) => "NOT",
+            (Notation::Words, Operator::And) => "AND",
+            (Notation::Words, Operator::Or) => "OR",
+            (Notation::Words, Operator::Xor) => "XOR",
+            (Notation::Words, Operator::Nand) => "NAND",
+            (Notation::Words, Operator::Nor) => "NOR",
+            (Notation::Words, Operator::Xnor) => "XNOR",
+            (Notation::Words, Operator::Implies) => "IMPLIES",
+            (Notation::Words, Operator::IfAndOnlyIf) => "IFF",
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Resolve {
     True,
     Ambiguous,
@@ -43,6 +118,94 @@ impl Resolve {
     pub fn is_false(&self) -> bool {
         *self == Resolve::False
     }
+
+    pub fn and(&self, other: Resolve) -> Resolve {
+        if self.is_ambiguous() || other.is_ambiguous() {
+            Resolve::Ambiguous
+        } else if self.is_true() && other.is_true() {
+            Resolve::True
+        } else {
+            Resolve::False
+        }
+    }
+
+    pub fn or(&self, other: Resolve) -> Resolve {
+        if self.is_ambiguous() || other.is_ambiguous() {
+            Resolve::Ambiguous
+        } else if self.is_true() || other.is_true() {
+            Resolve::True
+        } else {
+            Resolve::False
+        }
+    }
+
+    pub fn xor(&self, other: Resolve) -> Resolve {
+        if self.is_ambiguous() || other.is_ambiguous() {
+            Resolve::Ambiguous
+        } else if self.is_true() != other.is_true() {
+            Resolve::True
+        } else {
+            Resolve::False
+        }
+    }
+
+    pub fn nand(&self, other: Resolve) -> Resolve {
+        self.and(other).not()
+    }
+
+    pub fn nor(&self, other: Resolve) -> Resolve {
+        self.or(other).not()
+    }
+
+    pub fn xnor(&self, other: Resolve) -> Resolve {
+        self.xor(other).not()
+    }
+
+    // A false premise always implies the conclusion
+    pub fn implies(&self, other: Resolve) -> Resolve {
+        if self.is_false() || other.is_true() {
+            Resolve::True
+        } else if self.is_ambiguous() || other.is_ambiguous() {
+            Resolve::Ambiguous
+        } else {
+            Resolve::False
+        }
+    }
+
+    pub fn if_and_only_if(&self, other: Resolve) -> Resolve {
+        if self.is_ambiguous() || other.is_ambiguous() {
+            Resolve::Ambiguous
+        } else if *self == other {
+            Resolve::True
+        } else {
+            Resolve::False
+        }
+    }
+}
+
+impl Resolve {
+    pub fn colored(&self) -> ColoredString {
+        match self {
+            Resolve::True => "true".cyan(),
+            Resolve::Ambiguous => "ambiguous".purple(),
+            Resolve::False => "false".yellow(),
+        }
+    }
+}
+
+impl fmt::Display for Resolve {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Resolve::True => write!(f, "true"),
+            Resolve::Ambiguous => write!(f, "ambiguous"),
+            Resolve::False => write!(f, "false"),
+        }
+    }
+}
+
+// Answers the value of an askable fact when it's resolved, the CLI asks on the terminal
+pub trait Answers: Debug {
+    fn answer(&self, fact: &Fact) -> Result<Resolve, String>;
 }
 
 #[derive(Clone, Debug)]
@@ -51,6 +214,10 @@ pub struct Fact {
     pub value: RefCell<Resolve>,
     pub resolved: RefCell<bool>,
     pub rules: Vec<Rc<RefCell<Node>>>,
+    // Only set for the askable facts while asking is enabled
+    pub answers: Option<Rc<dyn Answers>>,
+    pub prompt: Option<String>,
+    pub description: Option<String>,
 }
 
 impl Fact {
@@ -63,31 +230,94 @@ impl Fact {
         *RefCell::borrow_mut(&self.value) = value;
     }
 
+    // A resolved fact that is not true is stated false or unknown, or answered
+    pub fn is_explicit(&self) -> bool {
+        *self.resolved.borrow() && !self.value.borrow().is_true()
+    }
+
+    pub fn conflict(&self, rule: &Node) -> String {
+        format!(
+            "Conflict in rule {}, {} is explicitly false",
+            rule.located(),
+            self.subject()
+        )
+    }
+
     pub fn cleanup(&self) {
         for rule in self.rules.iter() {
             RefCell::borrow(rule).cleanup()
         }
     }
 
-    pub fn resolve(&self, path: &mut Vec<String>) -> Result<Resolve, String> {
+    // Symbol of the fact followed by its description
+    pub fn subject(&self) -> String {
+        match &self.description {
+            Some(description) => format!("{} ({})", self.repr, description),
+            None => self.repr.to_string(),
+        }
+    }
+
+    pub fn answer(answer: &str) -> Option<Resolve> {
+        match answer.trim().to_lowercase().as_str() {
+            "y" | "yes" | "t" | "true" => Some(Resolve::True),
+            "n" | "no" | "f" | "false" => Some(Resolve::False),
+            "u" | "unknown" | "?" => Some(Resolve::Ambiguous),
+            _ => None,
+        }
+    }
+
+    // Question to ask for the value of the fact
+    pub fn question(&self) -> String {
+        match &self.prompt {
+            Some(prompt) => prompt.clone(),
+            None => format!("Is {} true?", self.subject()),
+        }
+    }
+
+    pub fn resolve(&self, path: &mut Vec<String>) -> Result<Resolve, ResolveError> {
         if *self.resolved.borrow() {
+            // A rule that concludes an explicitly false fact is a conflict,
+            // a rule that concludes an explicitly unknown fact makes it true
+            let value = *self.value.borrow();
+            if !value.is_true() {
+                for rule in self.rules.iter().filter(|rule| {
+                    let rule = RefCell::borrow(rule);
+                    !rule.disabled && !*rule.visited.borrow()
+                }) {
+                    let rule = RefCell::borrow(rule);
+                    if value.is_false() {
+                        if rule.resolve(&self.repr, &mut vec![])?.is_true() {
+                            return Err(ResolveError::Message(self.conflict(&rule)));
+                        }
+                    } else if rule.resolve(&self.repr, path)?.is_true() {
+                        self.set(Resolve::True);
+                        path.push(format!(
+                            "{} is {} because {}",
+                            self.subject(),
+                            "true".cyan(),
+                            Explained(&RefCell::borrow(&rule.premise(&self.repr)))
+                        ));
+                        return Ok(Resolve::True);
+                    }
+                }
+            }
             path.push(format!(
                 "{} is {}",
-                self.repr,
-                if *self.value.borrow() == Resolve::True {
-                    "true".cyan()
-                } else if *self.value.borrow() == Resolve::Ambiguous {
-                    "ambiguous".purple()
-                } else {
-                    "false".yellow()
-                }
+                self.subject(),
+                self.value.borrow().colored()
             ));
             return Ok(*self.value.borrow());
         }
-        if !self.rules.is_empty() {
+        // A fact with only disabled rules is a leaf
+        let rules: Vec<&Rc<RefCell<Node>>> = self
+            .rules
+            .iter()
+            .filter(|rule| !RefCell::borrow(rule).disabled)
+            .collect();
+        if !rules.is_empty() {
             let mut final_result: Option<Resolve> = None;
-            let rules_len = self.rules.len();
-            for (index, rule) in self.rules.iter().enumerate() {
+            let rules_len = rules.len();
+            for (index, rule) in rules.into_iter().enumerate() {
                 // Skip infinite rules for Operator::IfAndOnlyIf if there is multiple rules that *could* resolve
                 if *RefCell::borrow(rule).visited.borrow()
                     && rules_len > 1
@@ -100,7 +330,12 @@ impl Fact {
                 if let Ok(result) = result {
                     if result.is_true() {
                         *RefCell::borrow_mut(&self.value) = result;
-                        path.push(format!("{} is {}", self.repr, "true".cyan()));
+                        path.push(format!(
+                            "{} is {} because {}",
+                            self.subject(),
+                            "true".cyan(),
+                            Explained(&RefCell::borrow(&RefCell::borrow(rule).premise(&self.repr)))
+                        ));
                         return Ok(result);
                     } else if final_result.is_none()
                         || (final_result.unwrap().is_ambiguous() && result.is_false())
@@ -108,8 +343,10 @@ impl Fact {
                         final_result = Some(result);
                     }
                 }
-                // Infinite IfAndOnlyIf implications resolve to false
-                else if RefCell::borrow(rule).operator_eq(&Operator::IfAndOnlyIf) {
+                // Infinite IfAndOnlyIf implications resolve to false, the other errors are kept
+                else if RefCell::borrow(rule).operator_eq(&Operator::IfAndOnlyIf)
+                    && matches!(result, Err(ResolveError::InfiniteRule(_)))
+                {
                     return Ok(Resolve::False);
                 } else {
                     return result;
@@ -117,27 +354,26 @@ impl Fact {
             }
             path.push(format!(
                 "{} is {}",
-                self.repr,
-                if *self.value.borrow() == Resolve::True {
-                    "true".cyan()
-                } else if *self.value.borrow() == Resolve::Ambiguous {
-                    "ambiguous".purple()
-                } else {
-                    "false".yellow()
-                }
+                self.subject(),
+                self.value.borrow().colored()
             ));
             return Ok(final_result.unwrap());
         }
+        // Ask the user for the value of a leaf fact
+        if let Some(answers) = &self.answers {
+            let value = answers.answer(self)?;
+            self.set(value);
+            path.push(format!(
+                "{} is {} (answered)",
+                self.subject(),
+                value.colored()
+            ));
+            return Ok(value);
+        }
         path.push(format!(
             "{} is {}",
-            self.repr,
-            if *self.value.borrow() == Resolve::True {
-                "true".cyan()
-            } else if *self.value.borrow() == Resolve::Ambiguous {
-                "ambiguous".purple()
-            } else {
-                "false".yellow()
-            }
+            self.subject(),
+            self.value.borrow().colored()
         ));
         Ok(*self.value.borrow())
     }
@@ -150,42 +386,70 @@ pub struct Node {
     pub left: Option<Rc<RefCell<Node>>>,
     pub right: Option<Rc<RefCell<Node>>>,
     pub operator: Option<Operator>,
+    // Name and `key: value` metadata of a rule, a disabled rule concludes nothing
+    pub label: Option<String>,
+    pub metadata: Vec<(String, String)>,
+    pub disabled: bool,
+    // Where the node is written, nodes of other formats have no span
+    pub span: Option<Span>,
 }
 
+// The facts are written with their symbol, the explanations also write their description
 impl fmt::Display for Node {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_facts(f, &|fact: &Fact| fact.repr.to_string())
+    }
+}
+
+// Node with the subject of each fact, for the explanations
+struct Explained<'a>(&'a Node);
+
+impl fmt::Display for Explained<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt_facts(f, &Fact::subject)
+    }
+}
+
+impl Node {
+    fn fmt_facts(&self, f: &mut fmt::Formatter, fact: &dyn Fn(&Fact) -> String) -> fmt::Result {
+        if let Some(label) = &self.label {
+            write!(f, "[{}] ", label)?;
+        }
         if self.has_fact() {
             if self.operator_eq(&Operator::Not) {
                 write!(
                     f,
                     "not {}",
-                    RefCell::borrow(self.fact.as_ref().unwrap()).repr
+                    fact(&RefCell::borrow(self.fact.as_ref().unwrap()))
                 )?;
             } else {
-                write!(f, "{}", RefCell::borrow(self.fact.as_ref().unwrap()).repr)?;
+                write!(f, "{}", fact(&RefCell::borrow(self.fact.as_ref().unwrap())))?;
             }
         } else if self.has_operator() {
-            std::fmt::Display::fmt(&RefCell::borrow(self.left.as_ref().unwrap()), f)?;
+            Node::fmt_operand(self.left.as_ref().unwrap(), f, fact)?;
             write!(f, " ")?;
             match self.operator.unwrap() {
                 Operator::And => write!(f, "and"),
                 Operator::Or => write!(f, "or"),
                 Operator::Xor => write!(f, "xor"),
+                Operator::Nand => write!(f, "nand"),
+                Operator::Nor => write!(f, "nor"),
+                Operator::Xnor => write!(f, "xnor"),
                 Operator::Not => write!(f, "not"),
                 Operator::Implies => write!(f, "implies"),
                 Operator::IfAndOnlyIf => write!(f, "if and only if"),
             }?;
             if self.has_right() {
                 write!(f, " ")?;
-                std::fmt::Display::fmt(&RefCell::borrow(self.right.as_ref().unwrap()), f)?;
+                Node::fmt_operand(self.right.as_ref().unwrap(), f, fact)?;
             }
         } else {
             if self.has_left() {
-                std::fmt::Display::fmt(&RefCell::borrow(self.left.as_ref().unwrap()), f)?;
+                Node::fmt_operand(self.left.as_ref().unwrap(), f, fact)?;
             }
             if self.has_right() {
                 write!(f, " ")?;
-                std::fmt::Display::fmt(&RefCell::borrow(self.right.as_ref().unwrap()), f)?;
+                Node::fmt_operand(self.right.as_ref().unwrap(), f, fact)?;
             }
         }
         Ok(())
@@ -206,6 +470,10 @@ impl Node {
             left: None,
             right: None,
             operator: None,
+            label: None,
+            metadata: vec![],
+            disabled: false,
+            span: None,
         }
     }
 
@@ -216,20 +484,34 @@ impl Node {
             left: None,
             right: None,
             operator: Some(operator),
+            label: None,
+            metadata: vec![],
+            disabled: false,
+            span: None,
         }
     }
 
     pub fn match_operator(op: char) -> Option<Operator> {
-        if op == '+' {
+        if op == '+' || op == '∧' {
             return Some(Operator::And);
-        } else if op == '|' {
+        } else if op == '|' || op == '∨' {
             return Some(Operator::Or);
-        } else if op == '^' {
+        } else if op == '^' || op == '⊕' {
             return Some(Operator::Xor);
         }
         None
     }
 
+    // Side of a rule that concludes the given fact
+    pub fn premise(&self, for_fact: &char) -> Rc<RefCell<Node>> {
+        if self.operator_eq(&Operator::IfAndOnlyIf)
+            && RefCell::borrow(self.left.as_ref().unwrap()).contains_fact(for_fact)
+        {
+            return Rc::clone(self.right.as_ref().unwrap());
+        }
+        Rc::clone(self.left.as_ref().unwrap())
+    }
+
     pub fn has_fact(&self) -> bool {
         self.fact.is_some()
     }
@@ -272,6 +554,10 @@ impl Node {
         false
     }
 
+    pub fn is_implication(&self) -> bool {
+        self.operator_eq(&Operator::Implies) || self.operator_eq(&Operator::IfAndOnlyIf)
+    }
+
     pub fn all_facts(&self) -> Vec<Rc<RefCell<Fact>>> {
         let mut facts = vec![];
         if let Some(value) = &self.fact {
@@ -288,57 +574,199 @@ impl Node {
         facts
     }
 
-    pub fn print_short(&self) {
-        if self.has_fact() {
-            let repr = if *RefCell::borrow(self.fact.as_ref().unwrap())
-                .resolved
-                .borrow()
-            {
-                format!(
-                    "{}",
-                    format!("{}", RefCell::borrow(self.fact.as_ref().unwrap()).repr).green()
-                )
+    // Facts of a conclusion, the facts of the premise of a nested implication are only checked
+    pub fn conclusion_facts(&self) -> Vec<Rc<RefCell<Fact>>> {
+        if self.operator_eq(&Operator::Implies) {
+            return RefCell::borrow(self.right.as_ref().unwrap()).conclusion_facts();
+        }
+        let mut facts = vec![];
+        if let Some(value) = &self.fact {
+            facts.push(Rc::clone(value));
+        }
+        if let Some(left) = &self.left {
+            facts.extend(RefCell::borrow(left).conclusion_facts());
+        }
+        if let Some(right) = &self.right {
+            facts.extend(RefCell::borrow(right).conclusion_facts());
+        }
+        facts
+    }
+
+    // The premise of a nested implication is used with a negation, `A => B` is `!A | B`
+    pub fn negated_facts(&self, negated: bool, facts: &mut Vec<char>) {
+        let negated = negated || self.operator_eq(&Operator::Not);
+        if let Some(fact) = &self.fact {
+            let repr = RefCell::borrow(fact).repr;
+            if negated && !facts.contains(&repr) {
+                facts.push(repr);
+            }
+        }
+        if let Some(left) = &self.left {
+            RefCell::borrow(left)
+                .negated_facts(negated || self.operator_eq(&Operator::Implies), facts);
+        }
+        if let Some(right) = &self.right {
+            RefCell::borrow(right).negated_facts(negated, facts);
+        }
+    }
+
+    // Evaluate the Node with a value for each fact, missing facts are false
+    pub fn evaluate(&self, values: &HashMap<char, bool>) -> bool {
+        if let Some(fact) = &self.fact {
+            let value = *values.get(&RefCell::borrow(fact).repr).unwrap_or(&false);
+            return if self.operator_eq(&Operator::Not) {
+                !value
             } else {
-                format!("{}", RefCell::borrow(self.fact.as_ref().unwrap()).repr)
+                value
             };
-            if self.operator_eq(&Operator::Not) {
-                print!("!{}", repr);
+        }
+        let left = match &self.left {
+            Some(left) => RefCell::borrow(left).evaluate(values),
+            None => false,
+        };
+        let right = match &self.right {
+            Some(right) => RefCell::borrow(right).evaluate(values),
+            None => false,
+        };
+        match self.operator {
+            Some(Operator::Not) => !left,
+            Some(Operator::And) => left && right,
+            Some(Operator::Or) => left || right,
+            Some(Operator::Xor) => left != right,
+            Some(Operator::Nand) => !(left && right),
+            Some(Operator::Nor) => !(left || right),
+            Some(Operator::Xnor) => left == right,
+            Some(Operator::Implies) => !left || right,
+            Some(Operator::IfAndOnlyIf) => left == right,
+            None => left,
+        }
+    }
+
+    // Node followed by where it's written
+    pub fn located(&self) -> String {
+        match &self.span {
+            Some(span) => format!("{} at {}", self, span),
+            None => self.to_string(),
+        }
+    }
+
+    // Node in the explanations, with the description of its facts
+    pub fn explained(&self) -> String {
+        match &self.span {
+            Some(span) => format!("{} at {}", Explained(self), span),
+            None => Explained(self).to_string(),
+        }
+    }
+
+    pub fn print_short(&self, notation: Notation) {
+        print!("{}", self.short_in(notation));
+    }
+
+    pub fn short(&self) -> String {
+        self.short_in(Notation::Ascii)
+    }
+
+    // Short form in a notation, with the resolved facts in green
+    pub fn short_in(&self, notation: Notation) -> String {
+        let written = self.written(notation, &|fact: &Fact| {
+            if *fact.resolved.borrow() {
+                format!("{}", fact.repr.to_string().green())
             } else {
-                print!("{}", repr);
+                fact.repr.to_string()
+            }
+        });
+        match &self.label {
+            Some(label) => format!("[{}] {}", label, written),
+            None => written,
+        }
+    }
+
+    // Node in a notation with a context around each binary operator
+    fn written(&self, notation: Notation, fact: &dyn Fn(&Fact) -> String) -> String {
+        let mut written = String::new();
+        // The word of a negation is separated from its operand
+        let not = match notation {
+            Notation::Words => format!("{} ", notation.spelling(Operator::Not)),
+            _ => notation.spelling(Operator::Not).to_string(),
+        };
+        if self.has_fact() {
+            if self.operator_eq(&Operator::Not) {
+                written.push_str(&not);
             }
+            written.push_str(&fact(&RefCell::borrow(self.fact.as_ref().unwrap())));
         } else if self.has_operator() {
             if self.operator_eq(&Operator::Not) {
-                print!("!");
+                written.push_str(&not);
             }
-            if !self.operator_eq(&Operator::Implies) && !self.operator_eq(&Operator::IfAndOnlyIf) {
-                print!("(");
+            if !self.is_implication() {
+                written.push('(');
             }
-            RefCell::borrow(self.left.as_ref().unwrap()).print_short();
+            written.push_str(&Node::written_operand(
+                self.left.as_ref().unwrap(),
+                notation,
+                fact,
+            ));
             if self.has_right() {
-                print!(" ");
-                match self.operator.unwrap() {
-                    Operator::And => print!("+"),
-                    Operator::Or => print!("|"),
-                    Operator::Xor => print!("^"),
-                    Operator::Not => (),
-                    Operator::Implies => print!("=>"),
-                    Operator::IfAndOnlyIf => print!("<=>"),
-                };
-                print!(" ");
-                RefCell::borrow(self.right.as_ref().unwrap()).print_short();
+                written.push(' ');
+                written.push_str(notation.spelling(self.operator.unwrap()));
+                written.push(' ');
+                written.push_str(&Node::written_operand(
+                    self.right.as_ref().unwrap(),
+                    notation,
+                    fact,
+                ));
             }
-            if !self.operator_eq(&Operator::Implies) && !self.operator_eq(&Operator::IfAndOnlyIf) {
-                print!(")");
+            if !self.is_implication() {
+                written.push(')');
             }
         } else {
             if self.has_left() {
-                RefCell::borrow(self.left.as_ref().unwrap()).print_short();
+                written.push_str(&Node::written_operand(
+                    self.left.as_ref().unwrap(),
+                    notation,
+                    fact,
+                ));
             }
             if self.has_right() {
-                print!(" ");
-                RefCell::borrow(self.right.as_ref().unwrap()).print_short();
+                written.push(' ');
+                written.push_str(&Node::written_operand(
+                    self.right.as_ref().unwrap(),
+                    notation,
+                    fact,
+                ));
             }
         }
+        written
+    }
+
+    // Only the root implication of a rule has no context
+    fn written_operand(
+        node: &Rc<RefCell<Node>>,
+        notation: Notation,
+        fact: &dyn Fn(&Fact) -> String,
+    ) -> String {
+        let node = RefCell::borrow(node);
+        match node.is_implication() && !node.has_fact() {
+            true => format!("({})", node.written(notation, fact)),
+            false => node.written(notation, fact),
+        }
+    }
+
+    fn fmt_operand(
+        node: &Rc<RefCell<Node>>,
+        f: &mut fmt::Formatter,
+        fact: &dyn Fn(&Fact) -> String,
+    ) -> fmt::Result {
+        let node = RefCell::borrow(node);
+        let parenthesized = node.is_implication() && !node.has_fact();
+        if parenthesized {
+            write!(f, "(")?;
+        }
+        node.fmt_facts(f, fact)?;
+        if parenthesized {
+            write!(f, ")")?;
+        }
+        Ok(())
     }
 
     pub fn cleanup(&self) {
@@ -353,191 +781,258 @@ impl Node {
         }
     }
 
-    pub fn resolve(&self, for_query: &char, path: &mut Vec<String>) -> Result<Resolve, String> {
+    pub fn resolve(
+        &self,
+        for_query: &char,
+        path: &mut Vec<String>,
+    ) -> Result<Resolve, ResolveError> {
         if *self.visited.borrow() {
-            return Err(format!("Infinite rule {}", self));
+            return Err(ResolveError::InfiniteRule(self.located()));
         }
         *RefCell::borrow_mut(&self.visited) = true;
-        if self.fact.is_some() {
-            let result = RefCell::borrow(self.fact.as_ref().unwrap()).resolve(path)?;
-            if self.operator_eq(&Operator::Not) {
-                *RefCell::borrow_mut(&self.visited) = false;
-                return Ok(result.not());
-            }
-            *RefCell::borrow_mut(&self.visited) = false;
-            return Ok(result);
-        } else if let Some(op) = &self.operator {
-            path.push(self.to_string());
-            let result = match op {
-                Operator::Implies => {
-                    let result =
-                        RefCell::borrow(self.left.as_ref().unwrap()).resolve(for_query, path)?;
-                    if result.is_true() {
-                        let mut facts: Vec<Rc<RefCell<Fact>>> = vec![];
-                        let result = RefCell::borrow(self.right.as_ref().unwrap())
-                            .resolve_conclusion(result, &mut facts)?;
-                        for fact in facts {
-                            if result.is_true() {
-                                RefCell::borrow(&fact).set(result);
-                            } else if !*RefCell::borrow(&fact).resolved.borrow()
-                                || (result.is_false()
-                                    && RefCell::borrow(&fact).value.borrow().is_ambiguous())
-                            {
-                                RefCell::borrow(&fact).set_value(result);
-                            }
-                        }
-                        Ok(result)
-                    } else {
-                        Ok(result)
-                    }
-                }
-                Operator::IfAndOnlyIf => {
-                    // Resolve left if for_query is on the right
-                    if RefCell::borrow(self.right.as_ref().unwrap()).contains_fact(for_query) {
-                        let left = RefCell::borrow(self.left.as_ref().unwrap())
-                            .resolve(for_query, path)?;
-                        if left.is_true() {
-                            let mut facts: Vec<Rc<RefCell<Fact>>> = vec![];
-                            let right_result = RefCell::borrow(self.right.as_ref().unwrap())
-                                .resolve_conclusion(left, &mut facts)?;
-                            if right_result.is_true() {
-                                for fact in facts {
-                                    RefCell::borrow(&fact).set(Resolve::True);
-                                }
-                                Ok(Resolve::True)
-                            } else {
-                                Ok(right_result)
-                            }
-                        } else {
-                            Ok(left)
-                        }
-                    }
-                    // -- else resolve right if for_query is on the left
-                    else {
-                        let right = RefCell::borrow(self.right.as_ref().unwrap())
-                            .resolve(for_query, path)?;
-                        if right.is_true() {
-                            let mut facts: Vec<Rc<RefCell<Fact>>> = vec![];
-                            let left_result = RefCell::borrow(self.left.as_ref().unwrap())
-                                .resolve_conclusion(right, &mut facts)?;
-                            if left_result.is_true() {
-                                for fact in facts {
-                                    RefCell::borrow(&fact).set(Resolve::True);
-                                }
-                                Ok(Resolve::True)
-                            } else {
-                                Ok(left_result)
+        let result = match self.operator {
+            Some(Operator::Implies) if !self.has_fact() => {
+                path.push(self.explained());
+                let result = RefCell::borrow(self.left.as_ref().unwrap()).resolve_query(path)?;
+                if result.is_true() {
+                    let mut facts: Vec<(Rc<RefCell<Fact>>, Resolve)> = vec![];
+                    RefCell::borrow(self.right.as_ref().unwrap())
+                        .resolve_conclusion(result, &mut facts, path)?;
+                    let mut conflict: Option<String> = None;
+                    for (fact, result) in facts.iter() {
+                        let fact = RefCell::borrow(fact);
+                        // The facts stated false keep their value, the facts stated unknown can be true
+                        if fact.is_explicit() {
+                            if result.is_true() && fact.value.borrow().is_false() {
+                                conflict = Some(fact.conflict(self));
+                            } else if result.is_true() {
+                                fact.set(*result);
                             }
-                        } else {
-                            Ok(right)
+                        } else if result.is_true() {
+                            fact.set(*result);
+                        } else if !*fact.resolved.borrow() {
+                            fact.set_value(*result);
                         }
                     }
-                }
-                Operator::And => {
-                    let left =
-                        RefCell::borrow(self.left.as_ref().unwrap()).resolve(for_query, path)?;
-                    let right =
-                        RefCell::borrow(self.right.as_ref().unwrap()).resolve(for_query, path)?;
-                    if left.is_ambiguous() || right.is_ambiguous() {
-                        Ok(Resolve::Ambiguous)
-                    } else if left.is_true() && right.is_true() {
-                        Ok(Resolve::True)
-                    } else {
-                        Ok(Resolve::False)
-                    }
-                }
-                Operator::Or => {
-                    let left =
-                        RefCell::borrow(self.left.as_ref().unwrap()).resolve(for_query, path)?;
-                    let right =
-                        RefCell::borrow(self.right.as_ref().unwrap()).resolve(for_query, path)?;
-                    if left.is_ambiguous() || right.is_ambiguous() {
-                        Ok(Resolve::Ambiguous)
-                    } else if left.is_true() || right.is_true() {
-                        Ok(Resolve::True)
-                    } else {
-                        Ok(Resolve::False)
-                    }
-                }
-                Operator::Xor => {
-                    let left =
-                        RefCell::borrow(self.left.as_ref().unwrap()).resolve(for_query, path)?;
-                    let right =
-                        RefCell::borrow(self.right.as_ref().unwrap()).resolve(for_query, path)?;
-                    if left.is_ambiguous() || right.is_ambiguous() {
-                        Ok(Resolve::Ambiguous)
-                    } else if (left.is_true() && right.is_false())
-                        || (left.is_false() && right.is_true())
+                    // The premise of a nested implication of the fact is not true
+                    if let Some(conflict) = conflict {
+                        Err(ResolveError::Message(conflict))
+                    } else if let Some((_, result)) = facts
+                        .iter()
+                        .find(|(fact, _)| RefCell::borrow(fact).repr == *for_query)
                     {
-                        Ok(Resolve::True)
+                        Ok(*result)
                     } else {
                         Ok(Resolve::False)
                     }
+                } else {
+                    Ok(result)
                 }
-                Operator::Not => {
-                    let left =
-                        RefCell::borrow(self.left.as_ref().unwrap()).resolve(for_query, path)?;
-                    Ok(left.not())
+            }
+            Some(Operator::IfAndOnlyIf) if !self.has_fact() => {
+                path.push(self.explained());
+                self.resolve_equivalence(for_query, path)
+            }
+            // The premise of a rule, nested implications are only checked
+            _ => self.resolve_query(path),
+        };
+        *RefCell::borrow_mut(&self.visited) = false;
+        result
+    }
+
+    // Resolve a query expression or a premise without concluding anything
+    // -- implications are only checked with the current value of each side
+    pub fn resolve_query(&self, path: &mut Vec<String>) -> Result<Resolve, ResolveError> {
+        if let Some(fact) = &self.fact {
+            let result = RefCell::borrow(fact).resolve(path)?;
+            if self.operator_eq(&Operator::Not) {
+                return Ok(result.not());
+            }
+            return Ok(result);
+        } else if let Some(op) = &self.operator {
+            path.push(Explained(self).to_string());
+            let left = RefCell::borrow(self.left.as_ref().unwrap()).resolve_query(path)?;
+            if op == &Operator::Not {
+                return Ok(left.not());
+            }
+            let right = RefCell::borrow(self.right.as_ref().unwrap()).resolve_query(path)?;
+            return Ok(match op {
+                Operator::And => left.and(right),
+                Operator::Or => left.or(right),
+                Operator::Xor => left.xor(right),
+                Operator::Nand => left.nand(right),
+                Operator::Nor => left.nor(right),
+                Operator::Xnor => left.xnor(right),
+                Operator::Implies => left.implies(right),
+                Operator::IfAndOnlyIf => left.if_and_only_if(right),
+                Operator::Not => left.not(),
+            });
+        } else if self.has_left() {
+            return RefCell::borrow(self.left.as_ref().unwrap()).resolve_query(path);
+        }
+        Err(ResolveError::Message("Empty Node".to_string()))
+    }
+
+    // Resolve a fact of an IfAndOnlyIf rule by searching all assignments of its unknown facts
+    // -- Known facts (initial facts or resolved by another rule) are fixed
+    // -- Unknown facts are false unless the rule requires them,
+    // -- facts used with a negation are assumed false before the others
+    // -- Ambiguous facts can take any value
+    fn resolve_equivalence(
+        &self,
+        for_query: &char,
+        path: &mut Vec<String>,
+    ) -> Result<Resolve, ResolveError> {
+        let mut fixed: HashMap<char, bool> = HashMap::new();
+        let mut free: Vec<Rc<RefCell<Fact>>> = vec![];
+        let mut ambiguous_mask = 0;
+        for fact in self.all_facts() {
+            let repr = RefCell::borrow(&fact).repr;
+            if fixed.contains_key(&repr) || free.iter().any(|f| RefCell::borrow(f).repr == repr) {
+                continue;
+            }
+            if repr != *for_query {
+                let result = RefCell::borrow(&fact).resolve(path)?;
+                if result.is_true()
+                    || (result.is_false() && *RefCell::borrow(&fact).resolved.borrow())
+                {
+                    fixed.insert(repr, result.is_true());
+                    continue;
+                } else if result.is_ambiguous() {
+                    ambiguous_mask |= 1 << free.len();
                 }
+            }
+            free.push(fact);
+        }
+        if free.len() > MAX_EQUIVALENCE_FACTS {
+            return Err(ResolveError::Message(format!(
+                "Too many unknown facts in rule {} ({}), the limit is {}",
+                self.located(),
+                free.len(),
+                MAX_EQUIVALENCE_FACTS
+            )));
+        }
+        let mut negated: Vec<char> = vec![];
+        self.negated_facts(false, &mut negated);
+        let mut negated_mask = 0;
+        for (index, fact) in free.iter().enumerate() {
+            if negated.contains(&RefCell::borrow(fact).repr) {
+                negated_mask |= 1 << index;
+            }
+        }
+        negated_mask &= !ambiguous_mask;
+        let others_mask = !negated_mask & !ambiguous_mask;
+
+        // Find all assignments that satisfy the rule
+        let mut models: Vec<usize> = vec![];
+        for model in 0..(1_usize << free.len()) {
+            let mut values = fixed.clone();
+            for (index, fact) in free.iter().enumerate() {
+                values.insert(RefCell::borrow(fact).repr, model & (1 << index) != 0);
+            }
+            if self.evaluate(&values) {
+                models.push(model);
+            }
+        }
+        // Only keep the models with the least true facts
+        let is_subset =
+            |a: usize, b: usize, mask: usize| a & mask != b & mask && a & b & mask == a & mask;
+        let models: Vec<usize> = models
+            .iter()
+            .filter(|&&model| {
+                !models.iter().any(|&other| {
+                    other & ambiguous_mask == model & ambiguous_mask
+                        && (is_subset(other, model, negated_mask)
+                            || (other & negated_mask == model & negated_mask
+                                && is_subset(other, model, others_mask)))
+                })
+            })
+            .cloned()
+            .collect();
+        if models.is_empty() {
+            return Err(ResolveError::Message(format!(
+                "Contradiction in rule {}",
+                self.located()
+            )));
+        }
+
+        // Each fact is true or false if it has the same value in all models
+        let mut result = Resolve::False;
+        for (index, fact) in free.iter().enumerate() {
+            let value = if models.iter().all(|model| model & (1 << index) != 0) {
+                Resolve::True
+            } else if models.iter().any(|model| model & (1 << index) != 0) {
+                Resolve::Ambiguous
+            } else {
+                Resolve::False
             };
-            *RefCell::borrow_mut(&self.visited) = false;
-            return result;
-        } else if self.has_left() {
-            let result = RefCell::borrow(self.left.as_ref().unwrap()).resolve(for_query, path)?;
-            *RefCell::borrow_mut(&self.visited) = false;
-            return Ok(result);
+            let fact = RefCell::borrow(fact);
+            if fact.repr == *for_query {
+                result = value;
+            } else if value.is_true() {
+                fact.set(value);
+            }
         }
-        *RefCell::borrow_mut(&self.visited) = false;
-        Err("Empty Node".to_string())
+        Ok(result)
     }
 
+    // Collect the facts of a conclusion with the value it gives each of them,
+    // a negation flips the value of its operand
     pub fn resolve_conclusion(
         &self,
         result: Resolve,
-        facts: &mut Vec<Rc<RefCell<Fact>>>,
-    ) -> Result<Resolve, String> {
-        if self.fact.is_some() {
-            facts.push(Rc::clone(self.fact.as_ref().unwrap()));
+        facts: &mut Vec<(Rc<RefCell<Fact>>, Resolve)>,
+        path: &mut Vec<String>,
+    ) -> Result<(), ResolveError> {
+        if let Some(fact) = &self.fact {
             if self.operator_eq(&Operator::Not) {
-                return Ok(result.not());
+                facts.push((Rc::clone(fact), result.not()));
+            } else {
+                facts.push((Rc::clone(fact), result));
             }
-            return Ok(result);
+            return Ok(());
         } else if let Some(op) = &self.operator {
-            let result = match op {
-                Operator::And => {
-                    RefCell::borrow(self.left.as_ref().unwrap())
-                        .resolve_conclusion(result, facts)?;
-                    RefCell::borrow(self.right.as_ref().unwrap())
-                        .resolve_conclusion(result, facts)?;
-                    Ok(result)
+            let (left, right) = match op {
+                Operator::And => (result, result),
+                // The conclusion doesn't tell which facts are true
+                Operator::Or | Operator::Xor | Operator::Nand | Operator::Xnor => {
+                    (Resolve::Ambiguous, Resolve::Ambiguous)
                 }
-                Operator::Or => {
-                    RefCell::borrow(self.left.as_ref().unwrap())
-                        .resolve_conclusion(Resolve::Ambiguous, facts)?;
-                    RefCell::borrow(self.right.as_ref().unwrap())
-                        .resolve_conclusion(Resolve::Ambiguous, facts)?;
-                    Ok(Resolve::Ambiguous)
+                // Neither side is true, the facts are concluded with the opposite value
+                Operator::Nor => (result.not(), result.not()),
+                Operator::Not => {
+                    return RefCell::borrow(self.left.as_ref().unwrap()).resolve_conclusion(
+                        result.not(),
+                        facts,
+                        path,
+                    );
                 }
-                Operator::Xor => {
-                    RefCell::borrow(self.left.as_ref().unwrap())
-                        .resolve_conclusion(Resolve::Ambiguous, facts)?;
-                    RefCell::borrow(self.right.as_ref().unwrap())
-                        .resolve_conclusion(Resolve::Ambiguous, facts)?;
-                    Ok(Resolve::Ambiguous)
+                // The conclusion of a nested implication only applies when its premise is true,
+                // an ambiguous premise makes it ambiguous
+                Operator::Implies => {
+                    let premise =
+                        RefCell::borrow(self.left.as_ref().unwrap()).resolve_query(path)?;
+                    let result = match premise {
+                        Resolve::True => result,
+                        Resolve::Ambiguous => Resolve::Ambiguous,
+                        Resolve::False => return Ok(()),
+                    };
+                    return RefCell::borrow(self.right.as_ref().unwrap())
+                        .resolve_conclusion(result, facts, path);
                 }
-                Operator::Not => {
-                    let left = RefCell::borrow(self.left.as_ref().unwrap())
-                        .resolve_conclusion(result, facts)?;
-                    Ok(left.not())
+                _ => {
+                    return Err(ResolveError::Message(
+                        "Unallowed operator in conclusion".to_string(),
+                    ))
                 }
-                _ => Err("Unallowed operator in conclusion".to_string()),
-            }?;
-            return Ok(result);
+            };
+            RefCell::borrow(self.left.as_ref().unwrap()).resolve_conclusion(left, facts, path)?;
+            RefCell::borrow(self.right.as_ref().unwrap()).resolve_conclusion(right, facts, path)?;
+            return Ok(());
         } else if self.has_left() {
-            let result =
-                RefCell::borrow(self.left.as_ref().unwrap()).resolve_conclusion(result, facts)?;
-            return Ok(result);
+            return RefCell::borrow(self.left.as_ref().unwrap())
+                .resolve_conclusion(result, facts, path);
         }
-        Err("Empty Node".to_string())
+        Err(ResolveError::Message("Empty Node".to_string()))
     }
 }
@@ -0,0 +1,362 @@
+use crate::{
+    error::{Error, ErrorKind},
+    node::Operator,
+};
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{anychar, char, satisfy},
+    combinator::{eof, map, peek},
+    error::{ErrorKind as NomErrorKind, ParseError},
+    sequence::preceded,
+    IResult,
+};
+
+// Expression of a block, before it's converted to a tree of Node
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expression {
+    Fact(char),
+    Not(Box<Expression>),
+    Group(Box<Expression>),
+    Binary(Operator, Box<Expression>, Box<Expression>),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Associativity {
+    Left,
+    Right,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BinaryOperator {
+    pub symbol: &'static str,
+    pub operator: Operator,
+    pub precedence: u8,
+    pub associativity: Associativity,
+}
+
+// Binary operators of a block, a greater precedence binds tighter
+// -- the NOT operator and the contexts bind tighter than all of them,
+// -- a negated operator has the precedence of its operator, `!` can't follow an operand otherwise
+// -- the implications bind looser than all of them, `<=>` is before `<=` that it contains
+pub const BINARY_OPERATORS: [BinaryOperator; 9] = [
+    BinaryOperator {
+        symbol: "+",
+        operator: Operator::And,
+        precedence: 4,
+        associativity: Associativity::Left,
+    },
+    BinaryOperator {
+        symbol: "!+",
+        operator: Operator::Nand,
+        precedence: 4,
+        associativity: Associativity::Left,
+    },
+    BinaryOperator {
+        symbol: "|",
+        operator: Operator::Or,
+        precedence: 3,
+        associativity: Associativity::Left,
+    },
+    BinaryOperator {
+        symbol: "!|",
+        operator: Operator::Nor,
+        precedence: 3,
+        associativity: Associativity::Left,
+    },
+    BinaryOperator {
+        symbol: "^",
+        operator: Operator::Xor,
+        precedence: 2,
+        associativity: Associativity::Left,
+    },
+    BinaryOperator {
+        symbol: "!^",
+        operator: Operator::Xnor,
+        precedence: 2,
+        associativity: Associativity::Left,
+    },
+    BinaryOperator {
+        symbol: "=>",
+        operator: Operator::Implies,
+        precedence: 1,
+        associativity: Associativity::Right,
+    },
+    BinaryOperator {
+        symbol: "<=>",
+        operator: Operator::IfAndOnlyIf,
+        precedence: 0,
+        associativity: Associativity::Right,
+    },
+    // Reverse implication, its operands are swapped
+    BinaryOperator {
+        symbol: "<=",
+        operator: Operator::Implies,
+        precedence: 1,
+        associativity: Associativity::Left,
+    },
+];
+
+// Alternative spellings of the operators of a block, replaced by their symbol before parsing
+// -- a word is only an operator when it's not part of a longer word
+pub const OPERATOR_SPELLINGS: [(&str, &str); 18] = [
+    ("∧", "+"),
+    ("∨", "|"),
+    ("⊕", "^"),
+    ("⊼", "!+"),
+    ("⊽", "!|"),
+    ("⊙", "!^"),
+    ("¬", "!"),
+    ("→", "=>"),
+    ("↔", "<=>"),
+    ("AND", "+"),
+    ("OR", "|"),
+    ("XOR", "^"),
+    ("NAND", "!+"),
+    ("NOR", "!|"),
+    ("XNOR", "!^"),
+    ("NOT", "!"),
+    ("IMPLIES", "=>"),
+    ("IFF", "<=>"),
+];
+
+pub fn find_binary_operator(operator: &Operator) -> Option<BinaryOperator> {
+    BINARY_OPERATORS
+        .iter()
+        .find(|binary_operator| binary_operator.operator == *operator)
+        .copied()
+}
+
+// Error with the remaining input where it happened
+#[derive(Debug, PartialEq)]
+pub struct Failure<'a> {
+    input: &'a str,
+    kind: ErrorKind,
+    message: &'static str,
+}
+
+impl<'a> Failure<'a> {
+    fn new(input: &'a str, kind: ErrorKind, message: &'static str) -> nom::Err<Failure<'a>> {
+        nom::Err::Failure(Failure {
+            input,
+            kind,
+            message,
+        })
+    }
+}
+
+impl<'a> ParseError<&'a str> for Failure<'a> {
+    fn from_error_kind(input: &'a str, _: NomErrorKind) -> Self {
+        Failure {
+            input,
+            kind: ErrorKind::InvalidCharacter,
+            message: "Invalid character",
+        }
+    }
+
+    fn append(_: &'a str, _: NomErrorKind, other: Self) -> Self {
+        other
+    }
+}
+
+type ParseResult<'a, O> = IResult<&'a str, O, Failure<'a>>;
+
+// What is before an operand, to explain why it's missing
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum After {
+    Start,
+    Operator,
+    Not,
+}
+
+fn binary_operator(input: &str) -> ParseResult<'_, BinaryOperator> {
+    for binary_operator in BINARY_OPERATORS.iter() {
+        if let Ok((input, _)) = tag::<&str, &str, Failure>(binary_operator.symbol)(input) {
+            return Ok((input, *binary_operator));
+        }
+    }
+    Err(nom::Err::Error(Failure::from_error_kind(
+        input,
+        NomErrorKind::Tag,
+    )))
+}
+
+fn fact(input: &str) -> ParseResult<'_, Expression> {
+    map(satisfy(|c| c.is_ascii_uppercase()), Expression::Fact)(input)
+}
+
+fn negation(input: &str) -> ParseResult<'_, Expression> {
+    map(
+        preceded(char('!'), |input| operand(input, After::Not)),
+        |expression| Expression::Not(Box::new(expression)),
+    )(input)
+}
+
+fn group(input: &str) -> ParseResult<'_, Expression> {
+    let (rest, _) = char('(')(input)?;
+    if peek(char::<&str, Failure>(')'))(rest).is_ok() {
+        return Err(Failure::new(
+            rest,
+            ErrorKind::InvalidContext,
+            "Unused context",
+        ));
+    }
+    let (rest, expression) = expression(rest, 0, After::Start)?;
+    match char::<&str, Failure>(')')(rest) {
+        Ok((rest, _)) => Ok((rest, Expression::Group(Box::new(expression)))),
+        Err(_) if rest.is_empty() => Err(Failure::new(
+            input,
+            ErrorKind::InvalidContext,
+            "Unclosed context",
+        )),
+        Err(_) => Err(unexpected(rest)),
+    }
+}
+
+// Error for a missing operand, the failure is at the remaining input
+fn missing_operand(input: &str, after: After) -> nom::Err<Failure<'_>> {
+    match peek(anychar::<&str, Failure>)(input) {
+        Err(_) => Failure::new(input, ErrorKind::IncompleteExpression, "Incomplete symbol"),
+        Ok((_, ')')) => Failure::new(
+            input,
+            ErrorKind::IncompleteExpression,
+            "Closing context on incomplete symbol",
+        ),
+        Ok(_) if binary_operator(input).is_ok() => match after {
+            After::Start => Failure::new(
+                input,
+                ErrorKind::InvalidOperator,
+                "Adding operator to empty symbol",
+            ),
+            After::Operator => Failure::new(
+                input,
+                ErrorKind::InvalidOperator,
+                "Operator on already set symbol",
+            ),
+            After::Not => Failure::new(
+                input,
+                ErrorKind::InvalidNegation,
+                "Closing Operator Not on empty symbol",
+            ),
+        },
+        Ok(_) => Failure::new(input, ErrorKind::InvalidCharacter, "Invalid character"),
+    }
+}
+
+// Error for a character that can't follow a complete expression
+fn unexpected(input: &str) -> nom::Err<Failure<'_>> {
+    match peek(anychar::<&str, Failure>)(input) {
+        Ok((_, ')')) => Failure::new(
+            input,
+            ErrorKind::InvalidContext,
+            "Closing context on root symbol",
+        ),
+        Ok((_, c)) if c.is_ascii_uppercase() || c == '(' || c == '!' => Failure::new(
+            input,
+            ErrorKind::MissingOperator,
+            "Missing operator between symbols",
+        ),
+        _ => Failure::new(input, ErrorKind::InvalidCharacter, "Invalid character"),
+    }
+}
+
+fn operand(input: &str, after: After) -> ParseResult<'_, Expression> {
+    match alt((fact, negation, group))(input) {
+        Err(nom::Err::Error(_)) => Err(missing_operand(input, after)),
+        result => result,
+    }
+}
+
+// Precedence climbing, only the operators with at least the minimum precedence are parsed
+fn expression(input: &str, min_precedence: u8, after: After) -> ParseResult<'_, Expression> {
+    let (mut input, mut left) = operand(input, after)?;
+    while let Ok((rest, binary_operator)) = binary_operator(input) {
+        if binary_operator.precedence < min_precedence {
+            break;
+        }
+        let next_precedence = match binary_operator.associativity {
+            Associativity::Left => binary_operator.precedence + 1,
+            Associativity::Right => binary_operator.precedence,
+        };
+        let (rest, right) = expression(rest, next_precedence, After::Operator)?;
+        left = match binary_operator.symbol {
+            "<=" => Expression::Binary(binary_operator.operator, Box::new(right), Box::new(left)),
+            _ => Expression::Binary(binary_operator.operator, Box::new(left), Box::new(right)),
+        };
+        input = rest;
+    }
+    Ok((input, left))
+}
+
+fn block(input: &str) -> ParseResult<'_, Expression> {
+    let (rest, expression) = expression(input, 0, After::Start)?;
+    match eof::<&str, Failure>(rest) {
+        Ok(_) => Ok((rest, expression)),
+        Err(_) => Err(unexpected(rest)),
+    }
+}
+
+// Where an expression is written in a block without whitespaces, with the start of its operand or operands
+// -- the operands of a reverse implication are swapped in the block
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Located {
+    pub end: usize,
+    pub operands: (usize, usize),
+}
+
+// Find the written expression from an index of the block, it's checked character by character
+pub fn locate(expression: &Expression, block: &str, start: usize) -> Option<Located> {
+    let after = |index: usize, symbol: &str| {
+        block
+            .get(index..)
+            .filter(|rest| rest.starts_with(symbol))
+            .map(|_| index + symbol.len())
+    };
+    let operand = |inner: &Expression, inner_start: usize| {
+        locate(inner, block, inner_start).map(|inner| (inner.end, (inner_start, inner_start)))
+    };
+    let (end, operands) = match expression {
+        Expression::Fact(symbol) => (after(start, &symbol.to_string())?, (start, start)),
+        Expression::Not(inner) => operand(inner, after(start, "!")?)?,
+        Expression::Group(inner) => {
+            let (end, operands) = operand(inner, after(start, "(")?)?;
+            (after(end, ")")?, operands)
+        }
+        Expression::Binary(operator, left, right) => {
+            let symbol = find_binary_operator(operator)?.symbol;
+            let written = locate(left, block, start)
+                .and_then(|left| after(left.end, symbol))
+                .and_then(|right_start| {
+                    locate(right, block, right_start).map(|right| (right.end, (start, right_start)))
+                });
+            match written {
+                Some(written) => written,
+                None if *operator == Operator::Implies => {
+                    let left_start = after(locate(right, block, start)?.end, "<=")?;
+                    (locate(left, block, left_start)?.end, (left_start, start))
+                }
+                None => return None,
+            }
+        }
+    };
+    Some(Located { end, operands })
+}
+
+// Parse a block without whitespaces, the column of the error is in the block
+pub fn parse_block(string: &str) -> Result<Expression, Error> {
+    match block(string) {
+        Ok((_, expression)) => Ok(expression),
+        Err(nom::Err::Error(failure)) | Err(nom::Err::Failure(failure)) => {
+            let length = string.chars().count();
+            let column = string[..(string.len() - failure.input.len())]
+                .chars()
+                .count()
+                + 1;
+            Err(Error::new(failure.kind, failure.message).at_column(column.min(length.max(1))))
+        }
+        Err(nom::Err::Incomplete(_)) => Err(Error::new(
+            ErrorKind::IncompleteExpression,
+            "Incomplete symbol",
+        )),
+    }
+}
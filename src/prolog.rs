@@ -0,0 +1,411 @@
+use crate::{
+    error::{Error, ErrorKind},
+    format::format_node,
+    input::Input,
+    node::{Node, Operator},
+    parser::Expression,
+};
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take_while},
+    character::complete::{char, multispace0, satisfy},
+    combinator::{eof, map, opt, recognize},
+    multi::separated_list1,
+    sequence::{delimited, pair, preceded, terminated, tuple},
+    IResult,
+};
+use std::cell::RefCell;
+
+// A fact and if it's negated
+type Literal = (char, bool);
+
+// Head of a clause with its body if it's a rule, each atom of the body is negated or not
+type Clause<'a> = (&'a str, Option<Vec<(&'a str, bool)>>);
+
+// Disjunction of conjunctions equivalent to the node, with the negations on the facts
+fn dnf(node: &Node, negated: bool) -> Vec<Vec<Literal>> {
+    if let Some(fact) = &node.fact {
+        let negated = negated != node.operator_eq(&Operator::Not);
+        return vec![vec![(RefCell::borrow(fact).repr, negated)]];
+    }
+    let left = || dnf(&RefCell::borrow(node.left.as_ref().unwrap()), negated);
+    let right = || dnf(&RefCell::borrow(node.right.as_ref().unwrap()), negated);
+    let side = |side: &Option<std::rc::Rc<RefCell<Node>>>, negated: bool| {
+        dnf(&RefCell::borrow(side.as_ref().unwrap()), negated)
+    };
+    let product = |first: Vec<Vec<Literal>>, second: Vec<Vec<Literal>>| {
+        let mut conjunctions = vec![];
+        for a in first.iter() {
+            for b in second.iter() {
+                conjunctions.push([a.clone(), b.clone()].concat());
+            }
+        }
+        conjunctions
+    };
+    match (node.operator, negated) {
+        (None, _) => left(),
+        (Some(Operator::Not), _) => side(&node.left, !negated),
+        (Some(Operator::And), false) | (Some(Operator::Or), true) => product(left(), right()),
+        (Some(Operator::Or), false) | (Some(Operator::And), true) => [left(), right()].concat(),
+        // Exactly one side is true, or both sides have the same value when negated
+        (Some(Operator::Xor), _) => [
+            product(side(&node.left, false), side(&node.right, !negated)),
+            product(side(&node.left, true), side(&node.right, negated)),
+        ]
+        .concat(),
+        // A negated operator is the negation of its operator
+        (Some(Operator::Nand), false) | (Some(Operator::Nor), true) => {
+            [side(&node.left, !negated), side(&node.right, !negated)].concat()
+        }
+        (Some(Operator::Nand), true) | (Some(Operator::Nor), false) => {
+            product(side(&node.left, !negated), side(&node.right, !negated))
+        }
+        (Some(Operator::Implies), false) => {
+            [side(&node.left, true), side(&node.right, false)].concat()
+        }
+        (Some(Operator::Implies), true) => {
+            product(side(&node.left, false), side(&node.right, true))
+        }
+        (Some(Operator::IfAndOnlyIf), _) | (Some(Operator::Xnor), _) => [
+            product(side(&node.left, false), side(&node.right, negated)),
+            product(side(&node.left, true), side(&node.right, !negated)),
+        ]
+        .concat(),
+    }
+}
+
+// Conjunctions without duplicate facts, a conjunction with a fact and its negation is never true
+fn simplify(conjunctions: Vec<Vec<Literal>>) -> Vec<Vec<Literal>> {
+    let mut simplified: Vec<Vec<Literal>> = vec![];
+    for conjunction in conjunctions {
+        let mut literals: Vec<Literal> = vec![];
+        for literal in conjunction {
+            if !literals.contains(&literal) {
+                literals.push(literal);
+            }
+        }
+        if literals
+            .iter()
+            .any(|(fact, negated)| literals.contains(&(*fact, !negated)))
+        {
+            continue;
+        }
+        if !simplified.contains(&literals) {
+            simplified.push(literals);
+        }
+    }
+    simplified
+}
+
+// Facts of a conclusion that is a conjunction of facts, the only conclusions of a Horn clause
+fn heads(node: &Node) -> Option<Vec<char>> {
+    if let Some(fact) = &node.fact {
+        if node.operator_eq(&Operator::Not) {
+            return None;
+        }
+        return Some(vec![RefCell::borrow(fact).repr]);
+    }
+    let left = heads(&RefCell::borrow(node.left.as_ref().unwrap()));
+    match node.operator {
+        None => left,
+        Some(Operator::And) => {
+            let right = heads(&RefCell::borrow(node.right.as_ref().unwrap()));
+            Some([left?, right?].concat())
+        }
+        _ => None,
+    }
+}
+
+fn atom(symbol: char) -> char {
+    symbol.to_ascii_lowercase()
+}
+
+fn clauses(prolog: &mut String, premise: &Node, conclusion: &Node, rule: &str) {
+    let heads = match heads(conclusion) {
+        Some(heads) => heads,
+        None => {
+            prolog.push_str(&format!("% Not a Horn clause: {}\n", rule));
+            return;
+        }
+    };
+    for head in heads {
+        for conjunction in simplify(dnf(premise, false)) {
+            let body: Vec<String> = conjunction
+                .iter()
+                .map(|(fact, negated)| {
+                    if *negated {
+                        format!("\\+ {}", atom(*fact))
+                    } else {
+                        atom(*fact).to_string()
+                    }
+                })
+                .collect();
+            prolog.push_str(&format!("{} :- {}.\n", atom(head), body.join(", ")));
+        }
+    }
+}
+
+// Prolog clauses of the knowledge base, negations are negations as failure like in the engine
+// -- a premise is expanded to a clause for each conjunction, a conclusion can only be a conjunction of facts
+pub fn to_prolog(input: &Input) -> String {
+    let mut prolog = String::from("% expert-system knowledge base\n");
+    let mut symbols: Vec<&char> = input.facts.keys().collect();
+    symbols.sort_unstable();
+    for symbol in symbols.iter() {
+        if let Some(description) = &RefCell::borrow(input.facts.get(symbol).unwrap()).description {
+            prolog.push_str(&format!("% {}: {}\n", atom(**symbol), description));
+        }
+    }
+    // A fact without clauses is false instead of an error
+    let atoms: Vec<String> = symbols
+        .iter()
+        .map(|symbol| format!("{}/0", atom(**symbol)))
+        .collect();
+    prolog.push_str(&format!(":- dynamic {}.\n", atoms.join(", ")));
+
+    for rule in input.rules.iter() {
+        let rule = RefCell::borrow(rule);
+        let text = format_node(&rule);
+        prolog.push_str(&format!("\n% {}\n", text));
+        let left = RefCell::borrow(rule.left.as_ref().unwrap());
+        let right = RefCell::borrow(rule.right.as_ref().unwrap());
+        clauses(&mut prolog, &left, &right, &text);
+        if rule.operator_eq(&Operator::IfAndOnlyIf) {
+            clauses(&mut prolog, &right, &left, &text);
+        }
+    }
+
+    if !input.initial_facts.is_empty() {
+        prolog.push('\n');
+    }
+    for symbol in input.initial_facts.iter() {
+        prolog.push_str(&format!("{}.\n", atom(*symbol)));
+    }
+    if !input.queries.is_empty() {
+        let queries: Vec<String> = input
+            .queries
+            .iter()
+            .map(|query| atom(*query).to_string())
+            .collect();
+        prolog.push_str(&format!(
+            "\n?- forall(member(Query, [{}]), (call(Query) -> format(\"~w true~n\", [Query]) ; format(\"~w false~n\", [Query]))).\n",
+            queries.join(", ")
+        ));
+    }
+    prolog
+}
+
+// Each clause of the content with the offset of its first character, the comments are blanked
+// -- a clause ends with a `.` followed by a whitespace or the end of the content
+fn split_clauses(content: &str) -> Vec<(usize, String)> {
+    let mut clauses: Vec<(usize, String)> = vec![];
+    let mut clause = String::new();
+    let mut start = 0;
+    let mut quote: Option<char> = None;
+    let mut comment = false;
+    let blank = "    ";
+    let mut characters = content.char_indices().peekable();
+    while let Some((offset, c)) = characters.next() {
+        if clause.is_empty() {
+            start = offset;
+        }
+        if comment {
+            comment = c != '\n';
+            clause.push_str(if c == '\n' {
+                "\n"
+            } else {
+                &blank[..c.len_utf8()]
+            });
+            continue;
+        }
+        match quote {
+            Some(opening) if c == opening => quote = None,
+            Some(_) => {}
+            None if c == '%' => {
+                comment = true;
+                clause.push(' ');
+                continue;
+            }
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '.'
+                && characters
+                    .peek()
+                    .map_or(true, |(_, next)| next.is_whitespace()) =>
+            {
+                clause.push(c);
+                clauses.push((start, std::mem::take(&mut clause)));
+                continue;
+            }
+            None => {}
+        }
+        clause.push(c);
+    }
+    if !clause.trim().is_empty() {
+        clauses.push((start, clause));
+    }
+    clauses
+}
+
+fn name(i: &str) -> IResult<&str, &str> {
+    recognize(pair(
+        satisfy(|c| c.is_ascii_lowercase()),
+        take_while(|c: char| c.is_ascii_alphanumeric() || c == '_'),
+    ))(i)
+}
+
+// regex: (\\+\s*name|not\(\s*name\s*\)|name)
+fn literal(i: &str) -> IResult<&str, (&str, bool)> {
+    alt((
+        map(preceded(pair(tag("\\+"), multispace0), name), |name| {
+            (name, true)
+        }),
+        map(
+            delimited(
+                pair(tag("not("), multispace0),
+                name,
+                pair(multispace0, char(')')),
+            ),
+            |name| (name, true),
+        ),
+        map(name, |name| (name, false)),
+    ))(i)
+}
+
+// regex: \s*name\s*(:-\s*literal(\s*,\s*literal)*)?\s*\.\s*
+fn clause(i: &str) -> IResult<&str, Clause<'_>> {
+    terminated(
+        tuple((
+            preceded(multispace0, name),
+            opt(preceded(
+                tuple((multispace0, tag(":-"), multispace0)),
+                separated_list1(tuple((multispace0, char(','), multispace0)), literal),
+            )),
+        )),
+        tuple((multispace0, char('.'), multispace0, eof)),
+    )(i)
+}
+
+fn invalid(content: &str, offset: usize, message: &str) -> Error {
+    Error::new(ErrorKind::InvalidProlog, message).at_offset(content, offset)
+}
+
+// Load the Horn clauses of a Prolog file as rules, the facts are the initial facts
+// and the heads of the rules are the queries
+// -- directives are ignored, all errors are kept in `errors`
+// -- the facts are named by the single letter atoms first, so an export is loaded with the same facts
+pub fn parse_prolog(input: &mut Input, content: &str) -> Result<(), Error> {
+    let errors = input.errors.len();
+    let mut parsed: Vec<Clause> = vec![];
+    let mut atoms: Vec<(usize, &str)> = vec![];
+
+    let clauses = split_clauses(content);
+    for (start, text) in clauses.iter() {
+        let trimmed = text.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with(":-") || trimmed.starts_with("?-") {
+            continue;
+        }
+        match clause(text) {
+            Ok((_, (head, body))) => {
+                for name in
+                    std::iter::once(head).chain(body.iter().flatten().map(|(name, _)| *name))
+                {
+                    if !atoms.iter().any(|(_, atom)| *atom == name) {
+                        atoms.push((
+                            start + name.as_ptr() as usize - text.as_ptr() as usize,
+                            name,
+                        ));
+                    }
+                }
+                parsed.push((head, body));
+            }
+            Err(nom::Err::Error(error)) | Err(nom::Err::Failure(error)) => {
+                let offset = *start + text.len() - error.input.len();
+                let message = if text.contains(';') || text.contains("->") {
+                    "Only Horn clauses are supported, like `c :- a, \\+ b.`"
+                } else {
+                    "Invalid clause, expected `fact.` or `fact :- a, \\+ b.`"
+                };
+                input.errors.push(invalid(content, offset, message));
+            }
+            Err(nom::Err::Incomplete(_)) => unreachable!(),
+        }
+    }
+    if input.errors.len() > errors {
+        return Err(input.errors[errors].clone());
+    }
+    let symbols = match input.name_facts(&atoms, "atoms") {
+        Ok(symbols) => symbols,
+        Err((offset, message)) => {
+            let error = invalid(content, offset, &message);
+            input.errors.push(error.clone());
+            return Err(error);
+        }
+    };
+    // The comments of an export, like `% a: description`, describe the single letter atoms
+    for line in content.lines() {
+        if let Some((name, description)) = line
+            .strip_prefix("% ")
+            .and_then(|line| line.split_once(": "))
+        {
+            if let (1, Some(symbol)) = (name.len(), symbols.get(name)) {
+                let fact = input.get_or_insert_fact(symbol);
+                RefCell::borrow_mut(&fact).description = Some(description.trim().to_string());
+            }
+        }
+    }
+
+    let mut initial_facts: Vec<char> = vec![];
+    let mut queries: Vec<char> = vec![];
+    for (head, body) in parsed {
+        let head = symbols[head];
+        let body = match body {
+            Some(body) => body,
+            None => {
+                if !initial_facts.contains(&head) {
+                    initial_facts.push(head);
+                }
+                continue;
+            }
+        };
+        let premise = body
+            .iter()
+            .map(|(name, negated)| {
+                let fact = Expression::Fact(symbols[*name]);
+                if *negated {
+                    Expression::Not(Box::new(fact))
+                } else {
+                    fact
+                }
+            })
+            .reduce(|left, right| {
+                Expression::Binary(Operator::And, Box::new(left), Box::new(right))
+            })
+            .unwrap();
+        let rule = input.expression_rule(Operator::Implies, &premise, &Expression::Fact(head));
+        input.add_rule(rule);
+        if !queries.contains(&head) {
+            queries.push(head);
+        }
+    }
+
+    if !initial_facts.is_empty() {
+        let initial_facts: String = initial_facts.iter().collect();
+        if let Err(error) = input.parse_initial_facts(&format!("={}", initial_facts)) {
+            input.errors.push(error.clone());
+            return Err(error);
+        }
+    }
+    // Without rules the facts are queried
+    if queries.is_empty() {
+        queries = initial_facts;
+    }
+    if !queries.is_empty() {
+        let queries: String = queries.iter().collect();
+        if let Err(error) = input.parse_queries(&format!("?{}", queries)) {
+            input.errors.push(error.clone());
+            return Err(error);
+        }
+    }
+    input.reset();
+    Ok(())
+}
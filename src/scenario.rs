@@ -0,0 +1,65 @@
+use crate::{equivalence::resolve_with, input::Input, node::Node, node::Resolve};
+use std::{cell::RefCell, mem, rc::Rc};
+
+// Named set of initial facts and queries with the expected answers
+#[derive(Debug)]
+pub struct Scenario {
+    pub name: String,
+    pub line: usize,
+    pub initial_facts: Vec<char>,
+    pub explicit_facts: Vec<(char, Resolve)>,
+    pub queries: Vec<char>,
+    pub query_expressions: Vec<Rc<RefCell<Node>>>,
+    pub expectations: Vec<(char, Resolve)>,
+}
+
+impl Scenario {
+    pub fn new(name: &str, line: usize) -> Scenario {
+        Scenario {
+            name: name.to_string(),
+            line,
+            initial_facts: vec![],
+            explicit_facts: vec![],
+            queries: vec![],
+            query_expressions: vec![],
+            expectations: vec![],
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Outcome {
+    pub fact: char,
+    pub expected: Resolve,
+    pub actual: Result<Resolve, String>,
+}
+
+impl Outcome {
+    pub fn passed(&self) -> bool {
+        matches!(&self.actual, Ok(actual) if *actual == self.expected)
+    }
+}
+
+// Resolve each expected fact from a clean state with the initial facts of the scenario
+// -- the initial facts of the Input are restored after
+pub fn run_scenario(input: &mut Input, scenario: &Scenario) -> Vec<Outcome> {
+    let initial_facts = input.initial_facts.clone();
+    let explicit_facts = mem::replace(&mut input.explicit_facts, scenario.explicit_facts.clone());
+    let outcomes = scenario
+        .expectations
+        .iter()
+        .map(|(fact, expected)| Outcome {
+            fact: *fact,
+            expected: *expected,
+            actual: if input.facts.contains_key(fact) {
+                resolve_with(input, &scenario.initial_facts, fact)
+            } else {
+                Err(format!("No fact {} in the knowledge base", fact))
+            },
+        })
+        .collect();
+    input.initial_facts = initial_facts;
+    input.explicit_facts = explicit_facts;
+    input.reset();
+    outcomes
+}
@@ -0,0 +1,114 @@
+use crate::{
+    format::format_node,
+    input::Input,
+    node::{Node, Operator},
+};
+use std::cell::RefCell;
+
+// S-expression of a node, an equivalence is an equality of booleans
+pub fn smtlib_term(node: &Node) -> String {
+    if let Some(fact) = &node.fact {
+        let symbol = RefCell::borrow(fact).repr;
+        if node.operator_eq(&Operator::Not) {
+            return format!("(not {})", symbol);
+        }
+        return symbol.to_string();
+    }
+    let left = smtlib_term(&RefCell::borrow(node.left.as_ref().unwrap()));
+    // The negated operators are the negation of their function
+    let (function, negated) = match node.operator {
+        None => return left,
+        Some(Operator::Not) => return format!("(not {})", left),
+        Some(Operator::And) => ("and", false),
+        Some(Operator::Or) => ("or", false),
+        Some(Operator::Xor) => ("xor", false),
+        Some(Operator::Nand) => ("and", true),
+        Some(Operator::Nor) => ("or", true),
+        Some(Operator::Xnor) => ("xor", true),
+        Some(Operator::Implies) => ("=>", false),
+        Some(Operator::IfAndOnlyIf) => ("=", false),
+    };
+    let right = smtlib_term(&RefCell::borrow(node.right.as_ref().unwrap()));
+    let term = format!("({} {} {})", function, left, right);
+    if negated {
+        return format!("(not {})", term);
+    }
+    term
+}
+
+// Commands of a query: a model, then the entailment checks for true and for false
+fn query_block(smtlib: &mut String, query: &str, term: &str) {
+    smtlib.push_str(&format!("(echo \"?{}\")\n", query));
+    smtlib.push_str("(check-sat)\n");
+    smtlib.push_str(&format!("(get-value ({}))\n", term));
+    smtlib.push_str(&format!("(check-sat-assuming ((not {})))\n", term));
+    smtlib.push_str(&format!("(check-sat-assuming ({}))\n", term));
+}
+
+// Boolean declarations and assertions of the knowledge base, with a block for each query
+// -- the facts that can't be concluded and are not initial facts are false like in the engine,
+// -- a fact stated unknown has no value
+// -- a fact concluded by rules is only false if the rules make it false
+pub fn to_smtlib(input: &Input) -> String {
+    let mut smtlib = String::from("; expert-system knowledge base\n");
+    smtlib.push_str(
+        "; a query is true if the first check-sat-assuming is unsat, false if the second is unsat\n",
+    );
+    smtlib.push_str("(set-option :produce-models true)\n(set-logic QF_UF)\n");
+
+    let mut symbols: Vec<&char> = input.facts.keys().collect();
+    symbols.sort_unstable();
+    for symbol in symbols.iter() {
+        let fact = RefCell::borrow(input.facts.get(symbol).unwrap());
+        match &fact.description {
+            Some(description) => smtlib.push_str(&format!(
+                "(declare-const {} Bool) ; {}\n",
+                symbol, description
+            )),
+            None => smtlib.push_str(&format!("(declare-const {} Bool)\n", symbol)),
+        }
+    }
+    for rule in input.rules.iter() {
+        let rule = RefCell::borrow(rule);
+        smtlib.push_str(&format!("; {}\n", format_node(&rule)));
+        smtlib.push_str(&format!("(assert {})\n", smtlib_term(&rule)));
+    }
+    for symbol in input.initial_facts.iter() {
+        smtlib.push_str(&format!("(assert {}) ; initial fact\n", symbol));
+    }
+    for (symbol, value) in input.explicit_facts.iter() {
+        if value.is_false() {
+            smtlib.push_str(&format!("(assert (not {})) ; false initial fact\n", symbol));
+        }
+    }
+    for symbol in symbols.iter() {
+        let fact = RefCell::borrow(input.facts.get(symbol).unwrap());
+        if fact.rules.is_empty()
+            && !input.initial_facts.contains(symbol)
+            && !input
+                .explicit_facts
+                .iter()
+                .any(|(explicit, _)| explicit == *symbol)
+            && !input.askable.contains(symbol)
+        {
+            smtlib.push_str(&format!("(assert (not {})) ; closed world\n", symbol));
+        }
+    }
+
+    for symbol in input.queries.iter() {
+        query_block(&mut smtlib, &symbol.to_string(), &symbol.to_string());
+    }
+    // An assumption can only be a constant, so each expression has a constant equal to it
+    for (index, expression) in input.query_expressions.iter().enumerate() {
+        let expression = RefCell::borrow(expression);
+        let name = format!("query_{}", index + 1);
+        smtlib.push_str(&format!("(declare-const {} Bool)\n", name));
+        smtlib.push_str(&format!(
+            "(assert (= {} {}))\n",
+            name,
+            smtlib_term(&expression)
+        ));
+        query_block(&mut smtlib, &format_node(&expression), &name);
+    }
+    smtlib
+}
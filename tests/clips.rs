@@ -0,0 +1,98 @@
+use expert_system::{clips::parse_clips, error::ErrorKind, input::Input, node::Resolve};
+use std::cell::RefCell;
+
+fn resolve(input: &Input, query: char) -> Resolve {
+    RefCell::borrow(input.facts.get(&query).unwrap())
+        .resolve(&mut vec![])
+        .unwrap()
+}
+
+#[test]
+fn clips_1() {
+    let mut input = Input::new();
+    assert!(input.load_file("examples/clips/clips_1.clp").is_ok());
+    assert_eq!(input.rules.len(), 3);
+    assert_eq!(input.initial_facts, vec!['F', 'C']);
+    assert_eq!(input.queries, vec!['A', 'M', 'B', 'D']);
+    assert_eq!(
+        RefCell::borrow(input.facts.get(&'A').unwrap()).description,
+        Some("flu".to_string())
+    );
+    assert_eq!(
+        RefCell::borrow(&input.rules[2]).to_string(),
+        "[rest] A or M implies B and D"
+    );
+    assert!(RefCell::borrow(&input.rules[2])
+        .explained()
+        .starts_with("[rest] A (flu) or M (measles) implies B (rest) and D (fluids)"));
+    assert_eq!(resolve(&input, 'A'), Resolve::True);
+    assert_eq!(resolve(&input, 'M'), Resolve::False);
+    assert_eq!(resolve(&input, 'D'), Resolve::True);
+}
+
+#[test]
+fn clips_patterns() {
+    // The single letter facts keep their letter, a rule without pattern asserts initial facts
+    let mut input = Input::new();
+    let result = parse_clips(
+        &mut input,
+        "(defrule start => (assert (a)))\n\
+         (defrule r1 (a) (or (b) (not (and (c) (d)))) => (assert (e)))\n\
+         (defrule r2 \"comment\" (not (e)) => (assert (f)))",
+    );
+    assert!(result.is_ok());
+    assert_eq!(input.initial_facts, vec!['A']);
+    assert_eq!(input.queries, vec!['A', 'E', 'F']);
+    assert_eq!(input.rules.len(), 2);
+    assert_eq!(resolve(&input, 'E'), Resolve::True);
+    assert_eq!(resolve(&input, 'F'), Resolve::False);
+}
+
+#[test]
+fn clips_errors() {
+    let mut input = Input::new();
+    let error = parse_clips(
+        &mut input,
+        "(deffacts start (a))\n\
+         (defrule r1 (a) (test (> 1 0)) => (assert (b)))\n\
+         (defrule r2 (a) => (printout t \"b\" crlf))\n\
+         (deftemplate person (slot name))\n\
+         (defrule r3 (a ?x) => (assert (b)))\n\
+         (defrule r4 (a) (assert (b)))",
+    )
+    .unwrap_err();
+    assert_eq!(error.kind, ErrorKind::InvalidClips);
+    assert_eq!((error.line, error.column), (Some(2), Some(18)));
+    assert_eq!(error.message, "Unsupported conditional element `test`");
+    let errors: Vec<(Option<usize>, Option<usize>, &str)> = input
+        .errors
+        .iter()
+        .map(|error| (error.line, error.column, error.message.as_str()))
+        .collect();
+    assert_eq!(
+        errors[1..],
+        [
+            (
+                Some(3),
+                Some(21),
+                "Unsupported action `printout`, only `assert` is supported"
+            ),
+            (
+                Some(4),
+                Some(2),
+                "Unsupported construct `deftemplate`, expected `defrule` or `deffacts`"
+            ),
+            (Some(5), Some(16), "Variables are not supported"),
+            (
+                Some(6),
+                Some(1),
+                "Missing `=>` between the patterns and the actions"
+            ),
+        ]
+    );
+
+    let mut input = Input::new();
+    let error = parse_clips(&mut input, "(defrule r (a)\n  => (assert (b))").unwrap_err();
+    assert_eq!((error.line, error.column), (Some(1), Some(1)));
+    assert_eq!(error.message, "Unclosed `(`");
+}
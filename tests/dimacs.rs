@@ -0,0 +1,191 @@
+use expert_system::{
+    dimacs::{parse_dimacs, to_dimacs, MAX_VARIABLES},
+    error::ErrorKind,
+    input::Input,
+    node::Resolve,
+};
+use std::{cell::RefCell, collections::HashMap};
+
+fn input(content: &str) -> Input {
+    let mut input = Input::new();
+    assert!(input.parse_content(content).is_ok());
+    input
+}
+
+fn resolve(input: &Input, query: char) -> Resolve {
+    RefCell::borrow(input.facts.get(&query).unwrap())
+        .resolve(&mut vec![])
+        .unwrap()
+}
+
+fn clauses(dimacs: &str) -> (usize, Vec<Vec<i64>>) {
+    let mut variables = 0;
+    let mut clauses = vec![];
+    for line in dimacs.lines() {
+        if line.starts_with('c') {
+            continue;
+        } else if line.starts_with('p') {
+            variables = line.split_whitespace().nth(2).unwrap().parse().unwrap();
+        } else {
+            let clause: Vec<i64> = line
+                .split_whitespace()
+                .map(|literal| literal.parse().unwrap())
+                .collect();
+            assert_eq!(clause.last(), Some(&0));
+            clauses.push(clause[..clause.len() - 1].to_vec());
+        }
+    }
+    (variables, clauses)
+}
+
+fn satisfies(clauses: &[Vec<i64>], model: usize) -> bool {
+    clauses.iter().all(|clause| {
+        clause.iter().any(|literal| {
+            let value = model & (1 << (literal.unsigned_abs() - 1)) != 0;
+            value == (*literal > 0)
+        })
+    })
+}
+
+#[test]
+fn dimacs_export() {
+    let input = input("@A \"the first fact\"\nA + !B => C\n=A\n?C");
+    assert_eq!(
+        to_dimacs(&input),
+        "c expert-system knowledge base
+c variables 1 to 3 are facts, the others are subexpressions
+c 1 A the first fact
+c 2 B
+c 3 C
+p cnf 4 5
+-4 1 0
+-4 -2 0
+4 -1 2 0
+-4 3 0
+1 0
+"
+    );
+}
+
+// The models of the CNF over the facts are the assignments that satisfy the rules and the initial facts
+#[test]
+fn dimacs_tseitin() {
+    for content in [
+        "A + !B => C\n=A\n?C",
+        "A | B ^ C => D + !E\n=\n?D",
+        "!(A + B) <=> C | D\n=C\n?A",
+        "A ^ B => C\nC <=> !D + (E | A)\n=\n?D",
+    ] {
+        let input = input(content);
+        let (variables, clauses) = clauses(&to_dimacs(&input));
+        let mut symbols: Vec<char> = input.facts.keys().copied().collect();
+        symbols.sort_unstable();
+        for facts in 0..(1_usize << symbols.len()) {
+            let values: HashMap<char, bool> = symbols
+                .iter()
+                .enumerate()
+                .map(|(index, symbol)| (*symbol, facts & (1 << index) != 0))
+                .collect();
+            let expected = input
+                .rules
+                .iter()
+                .all(|rule| RefCell::borrow(rule).evaluate(&values))
+                && input.initial_facts.iter().all(|symbol| values[symbol]);
+            let found = (0..(1_usize << (variables - symbols.len())))
+                .any(|gates| satisfies(&clauses, facts | (gates << symbols.len())));
+            assert_eq!(found, expected, "{} with {:?}", content, values);
+        }
+    }
+}
+
+#[test]
+fn dimacs_1() {
+    let mut input = Input::new();
+    let result = input.load_file("examples/dimacs/dimacs_1.cnf");
+    assert!(result.is_ok());
+    assert_eq!(input.rules.len(), 4);
+    assert_eq!(input.initial_facts, vec!['A', 'B']);
+    assert_eq!(input.queries, vec!['A', 'B', 'C', 'D', 'E', 'F']);
+    assert_eq!(
+        RefCell::borrow(input.facts.get(&'D').unwrap()).description,
+        Some("the engine must stop".to_string())
+    );
+    assert_eq!(resolve(&input, 'D'), Resolve::True);
+    assert_eq!(resolve(&input, 'F'), Resolve::False);
+}
+
+#[test]
+fn dimacs_names() {
+    // The named variables keep their letter and the others take the unused letters
+    let mut input = Input::new();
+    let result = parse_dimacs(&mut input, "c 2 A\np cnf 3 2\n2 0\n-2 1 3 0\n");
+    assert!(result.is_ok());
+    assert_eq!(input.initial_facts, vec!['A']);
+    assert_eq!(input.queries, vec!['B', 'A', 'C']);
+    assert_eq!(
+        RefCell::borrow(&input.rules[0]).to_string(),
+        "A and not B implies C"
+    );
+    assert_eq!(resolve(&input, 'C'), Resolve::True);
+}
+
+#[test]
+fn dimacs_scale() {
+    // A chain over every letter, the last clause ends without 0
+    let mut content = String::from("p cnf 26 26\n1 0\n");
+    for variable in 1..25 {
+        content.push_str(&format!("-{} {} 0\n", variable, variable + 1));
+    }
+    content.push_str("-25 26");
+    let mut input = Input::new();
+    assert!(parse_dimacs(&mut input, &content).is_ok());
+    assert!(input.warnings.is_empty());
+    assert_eq!(resolve(&input, 'Z'), Resolve::True);
+}
+
+#[test]
+fn dimacs_errors() {
+    let mut input = Input::new();
+    let result = parse_dimacs(&mut input, "1 -2 0\n");
+    assert_eq!(
+        result.unwrap_err().message,
+        "Clause before the problem line `p cnf <variables> <clauses>`"
+    );
+
+    let mut input = Input::new();
+    let error = parse_dimacs(&mut input, "p cnf 2 1\n1 x2 0\n").unwrap_err();
+    assert_eq!(error.kind, ErrorKind::InvalidDimacs);
+    assert_eq!((error.line, error.column), (Some(2), Some(3)));
+    assert_eq!(error.message, "Invalid literal `x2`");
+    assert_eq!(input.errors.len(), 1);
+
+    let mut input = Input::new();
+    let error = parse_dimacs(&mut input, "p cnf 2 1\n1 -3 0\n").unwrap_err();
+    assert_eq!(
+        error.message,
+        "Literal -3 is not a variable of the problem line"
+    );
+
+    let mut input = Input::new();
+    let error = parse_dimacs(&mut input, "c too many\np cnf 27 1\n27 0\n").unwrap_err();
+    assert_eq!(error.line, Some(2));
+    assert_eq!(
+        error.message,
+        "Too many variables (27), the limit is 26 as facts are the uppercase letters"
+    );
+    assert_eq!(MAX_VARIABLES, 26);
+}
+
+#[test]
+fn dimacs_negative_unit_clause() {
+    // A negative unit clause is an explicitly false initial fact
+    let mut input = Input::new();
+    let result = parse_dimacs(&mut input, "p cnf 3 3\n1 0\n-3 0\n-1 2 0\n");
+    assert!(result.is_ok());
+    assert_eq!(input.initial_facts, vec!['A']);
+    assert_eq!(input.explicit_facts, vec![('C', Resolve::False)]);
+    assert_eq!(resolve(&input, 'B'), Resolve::True);
+    assert_eq!(resolve(&input, 'C'), Resolve::False);
+    // It is exported back as the same clause
+    assert!(to_dimacs(&input).ends_with("1 0\n-3 0\n"));
+}
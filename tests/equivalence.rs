@@ -0,0 +1,106 @@
+use expert_system::{
+    equivalence::{check_equivalence, shared_facts, MAX_SHARED_FACTS},
+    input::Input,
+    node::Resolve,
+};
+
+fn inputs(first: &str, second: &str) -> (Input, Input) {
+    let mut first_input = Input::new();
+    assert!(first_input.parse_content(first).is_ok());
+    let mut second_input = Input::new();
+    assert!(second_input.parse_content(second).is_ok());
+    (first_input, second_input)
+}
+
+#[test]
+fn shared_facts_1() {
+    let (first, second) = inputs("A + B => C\n=\n?C", "A => C\nC => D\n=\n?D");
+    assert_eq!(shared_facts(&first, &second), vec!['A', 'C']);
+}
+
+#[test]
+fn equivalent_1() {
+    let (mut first, mut second) = inputs("A + B => C\n=\n?C", "B + A => C\n=\n?C");
+    let result = check_equivalence(&mut first, &mut second);
+    assert!(result.is_ok());
+    assert!(result.unwrap().is_none());
+}
+
+#[test]
+fn equivalent_2() {
+    let (mut first, mut second) = inputs("A => B + C\n=\n?BC", "A => B\nA => C\n=\n?BC");
+    let result = check_equivalence(&mut first, &mut second);
+    assert!(result.is_ok());
+    assert!(result.unwrap().is_none());
+}
+
+#[test]
+fn equivalent_3() {
+    // The contradictions of both inputs are the same error
+    let (mut first, mut second) = inputs("A + !B <=> C\n=\n?C", "A + !B <=> C\nC => D\n=\n?D");
+    let result = check_equivalence(&mut first, &mut second);
+    assert!(result.is_ok());
+    assert!(result.unwrap().is_none());
+    // The same contradiction in a different rule is not the same answer
+    let (mut first, mut second) = inputs("A + !B <=> C\n=\n?C", "C <=> !B + A\n=\n?C");
+    let result = check_equivalence(&mut first, &mut second);
+    let counterexample = result.unwrap().unwrap();
+    assert!(counterexample.first.is_err());
+}
+
+#[test]
+fn not_equivalent_1() {
+    let (mut first, mut second) = inputs("A + B => C\n=\n?C", "A | B => C\n=\n?C");
+    let result = check_equivalence(&mut first, &mut second);
+    assert!(result.is_ok());
+    let counterexample = result.unwrap();
+    assert!(counterexample.is_some());
+    let counterexample = counterexample.unwrap();
+    assert_eq!(counterexample.initial_facts, vec!['A']);
+    assert_eq!(counterexample.query, 'C');
+    assert_eq!(counterexample.first, Ok(Resolve::False));
+    assert_eq!(counterexample.second, Ok(Resolve::True));
+}
+
+#[test]
+fn not_equivalent_2() {
+    let (mut first, mut second) = inputs("A => B | C\n=\n?B", "A => B\n=\n?B");
+    let result = check_equivalence(&mut first, &mut second);
+    assert!(result.is_ok());
+    let counterexample = result.unwrap().unwrap();
+    assert_eq!(counterexample.initial_facts, vec!['A']);
+    assert_eq!(counterexample.first, Ok(Resolve::Ambiguous));
+}
+
+#[test]
+fn restore_initial_facts() {
+    let (mut first, mut second) = inputs("A => B\n=A\n?B", "A => B\n=\n?B");
+    let result = check_equivalence(&mut first, &mut second);
+    assert!(result.is_ok());
+    assert_eq!(first.initial_facts, vec!['A']);
+    assert!(second.initial_facts.is_empty());
+}
+
+#[test]
+fn different_errors() {
+    // The same infinite rule is reported with a different location
+    let (mut first, mut second) = inputs("A => B\nB => A\n=\n?A", "B => A\nA => B\n=\n?A");
+    let result = check_equivalence(&mut first, &mut second);
+    assert!(result.is_ok());
+    let counterexample = result.unwrap().unwrap();
+    assert!(counterexample.first.is_err());
+    assert!(counterexample.second.is_err());
+    assert_ne!(counterexample.first, counterexample.second);
+}
+
+#[test]
+fn too_many_shared_facts() {
+    let facts: String = ('A'..='Z').take(MAX_SHARED_FACTS + 1).collect();
+    let content = format!("=\n?{}", facts);
+    let (mut first, mut second) = inputs(&content, &content);
+    let result = check_equivalence(&mut first, &mut second);
+    assert_eq!(
+        result.unwrap_err(),
+        "Too many shared facts to check (13), the limit is 12"
+    );
+}
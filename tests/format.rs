@@ -0,0 +1,200 @@
+use expert_system::{format::format_content, input::Input, node::Node};
+use std::{cell::RefCell, collections::HashMap, fs, path::PathBuf, rc::Rc};
+
+fn facts(node: &Rc<RefCell<Node>>) -> Vec<char> {
+    let mut facts: Vec<char> = RefCell::borrow(node)
+        .short()
+        .chars()
+        .filter(char::is_ascii_uppercase)
+        .collect();
+    facts.sort_unstable();
+    facts.dedup();
+    facts
+}
+
+fn same_node(first: &Rc<RefCell<Node>>, second: &Rc<RefCell<Node>>) -> bool {
+    let symbols = facts(first);
+    if symbols != facts(second) {
+        return false;
+    }
+    (0..(1_usize << symbols.len())).all(|set| {
+        let values: HashMap<char, bool> = symbols
+            .iter()
+            .enumerate()
+            .map(|(index, symbol)| (*symbol, set & (1 << index) != 0))
+            .collect();
+        RefCell::borrow(first).evaluate(&values) == RefCell::borrow(second).evaluate(&values)
+    })
+}
+
+// Both sides of each rule and each query expression have the same truth table
+fn same_knowledge_base(first: &Input, second: &Input) -> bool {
+    let side = |rule: &Rc<RefCell<Node>>, left: bool| {
+        let rule = RefCell::borrow(rule);
+        Rc::clone(if left {
+            rule.left.as_ref().unwrap()
+        } else {
+            rule.right.as_ref().unwrap()
+        })
+    };
+    first.initial_facts == second.initial_facts
+        && first.queries == second.queries
+        && first.rules.len() == second.rules.len()
+        && first.rules.iter().zip(second.rules.iter()).all(|(a, b)| {
+            RefCell::borrow(a).operator == RefCell::borrow(b).operator
+                && same_node(&side(a, true), &side(b, true))
+                && same_node(&side(a, false), &side(b, false))
+        })
+        && first.query_expressions.len() == second.query_expressions.len()
+        && first
+            .query_expressions
+            .iter()
+            .zip(second.query_expressions.iter())
+            .all(|(a, b)| same_node(a, b))
+        && first.scenarios.len() == second.scenarios.len()
+}
+
+// The text input files of the examples, with the included files
+fn examples() -> Vec<String> {
+    let mut paths: Vec<String> = vec![];
+    let mut directories = vec![PathBuf::from("examples")];
+    while let Some(directory) = directories.pop() {
+        for entry in fs::read_dir(directory).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                directories.push(path);
+            } else if path.extension().is_some_and(|extension| extension == "txt") {
+                paths.push(path.display().to_string());
+            }
+        }
+    }
+    paths.sort();
+    paths
+}
+
+#[test]
+fn format_examples() {
+    for path in examples() {
+        let content = fs::read_to_string(&path).unwrap();
+        // Only the files without includes can be parsed from their content
+        if content.contains("include") || Input::new().parse_content(&content).is_err() {
+            continue;
+        }
+        let formatted = format_content(&content);
+        assert!(formatted.is_ok(), "{}", path);
+        let formatted = formatted.unwrap();
+        assert_eq!(format_content(&formatted).unwrap(), formatted, "{}", path);
+        let mut first = Input::new();
+        let mut second = Input::new();
+        assert!(first.parse_content(&content).is_ok());
+        assert!(second.parse_content(&formatted).is_ok(), "{}", path);
+        assert!(same_knowledge_base(&first, &second), "{}", path);
+    }
+}
+
+#[test]
+fn format_idempotence() {
+    for path in examples() {
+        // Only the examples of parsing errors can't be formatted
+        if path.contains("parsing/error_") {
+            continue;
+        }
+        let content = fs::read_to_string(&path).unwrap();
+        let formatted = format_content(&content);
+        assert!(formatted.is_ok(), "{}", path);
+        let formatted = formatted.unwrap();
+        assert_eq!(format_content(&formatted).unwrap(), formatted, "{}", path);
+    }
+}
+
+#[test]
+fn format_spacing() {
+    let result = format_content("A+!B=>C|  D\n=  AB\n?C,D   ,(A+B)|C\n");
+    assert_eq!(
+        result.unwrap(),
+        "A + !B => C | D\n\n=AB\n?C, D, A + B | C\n"
+    );
+}
+
+#[test]
+fn format_contexts() {
+    let result = format_content(
+        "(A + B) | C => !(D) + !(E | F)\nA | (B | C) => ((D))\nA + (B | C) => D\n=\n?D",
+    );
+    assert_eq!(
+        result.unwrap(),
+        "A + B | C => !D + !(E | F)\nA | (B | C) => D\nA + (B | C) => D\n\n=\n?D\n"
+    );
+}
+
+#[test]
+fn format_nested_implications() {
+    // The nested implications keep their context, `<=` is written as `=>`
+    let result = format_content(
+        "((A => B)) + C => D\nA => (B <=> C)\nA <=> (B IMPLIES (C => D))\nD <= (A <= B)\n=\n?(A => B), C => (D => A)",
+    );
+    assert_eq!(
+        result.unwrap(),
+        "(A => B) + C => D\nA => (B <=> C)\nA <=> (B => C => D)\n(B => A) => D\n\n=\n?(A => B), C => (D => A)\n"
+    );
+}
+
+#[test]
+fn format_comments() {
+    let result = format_content(
+        "# Header\n\nA => B # first\n# About the next rule\nA + C => D   # second\n          # continued\n=A # facts\n?BD\n\n# Footer\n",
+    );
+    assert_eq!(
+        result.unwrap(),
+        "# Header\n\nA => B     # first\n# About the next rule\nA + C => D # second\n           # continued\n\n=A # facts\n?BD\n\n# Footer\n"
+    );
+}
+
+#[test]
+fn format_sections() {
+    let result = format_content(
+        "A => B\nask A \"Is A true?\"\n@B  \"the fact B\"\ninclude  \"rules.txt\"\n=\n?B\nexpect B false\nscenario \"with A\"\n=A\n?B\nexpect B true",
+    );
+    assert_eq!(
+        result.unwrap(),
+        "include \"rules.txt\"\n\n@B \"the fact B\"\n\nask A \"Is A true?\"\n\nA => B\n\n=\n?B\nexpect B false\n\nscenario \"with A\"\n=A\n?B\nexpect B true\n"
+    );
+}
+
+#[test]
+fn format_error() {
+    let result = format_content("A => B\nA + => C\n=A\n?B");
+    assert!(result.is_err());
+    assert_eq!(result.unwrap_err().line, Some(2));
+}
+
+#[test]
+fn format_groups() {
+    // The blank lines between rules are kept, several blank lines are one
+    let result = format_content("A => B\nC => D\n\n\nE => F\n=A\n?F\n");
+    assert_eq!(result.unwrap(), "A => B\nC => D\n\nE => F\n\n=A\n?F\n");
+    // The directives are moved before the rules with the comments above them
+    let result = format_content(
+        "A => B\n\n# About the alarm\n@A \"the alarm\"\nC => D\nask C # asked\n=A\n?D\n",
+    );
+    assert_eq!(
+        result.unwrap(),
+        "# About the alarm\n@A \"the alarm\"\n\nask C # asked\n\nA => B\nC => D\n\n=A\n?D\n"
+    );
+}
+
+#[test]
+fn format_comment_placement() {
+    // A comment just below a line stays with it, a comment between blank lines goes with the next line
+    let result = format_content("A => B\n# About A => B\n\n# About C => D\n\nC => D\n=A\n?D\n");
+    assert_eq!(
+        result.unwrap(),
+        "A => B\n# About A => B\n\n# About C => D\n\nC => D\n\n=A\n?D\n"
+    );
+    // The trailing comments are aligned in each group of lines
+    let result = format_content("A => B # one\nA + B + C => D # two\n\nA => E # three\n=A\n?E");
+    assert_eq!(
+        result.unwrap(),
+        "A => B         # one\nA + B + C => D # two\n\nA => E # three\n\n=A\n?E\n"
+    );
+}
@@ -0,0 +1,125 @@
+use expert_system::{
+    graph::{proof_graph, rule_graph, to_dot, to_mermaid, Shape},
+    input::Input,
+    json::fact_result,
+    node::Resolve,
+};
+
+fn input(content: &str) -> Input {
+    let mut input = Input::new();
+    assert!(input.parse_content(content).is_ok());
+    input
+}
+
+#[test]
+fn graph_edges() {
+    let input = input("A + !(B | C) => D\nD <=> !E\n=A\n?E");
+    let graph = rule_graph(&input);
+    let edges: Vec<(&str, &str, Option<&str>, bool, bool)> = graph
+        .edges
+        .iter()
+        .map(|edge| {
+            (
+                edge.from.as_str(),
+                edge.to.as_str(),
+                edge.label,
+                edge.negated,
+                edge.both,
+            )
+        })
+        .collect();
+    assert_eq!(
+        edges,
+        vec![
+            ("A", "R1", Some("+"), false, false),
+            ("B", "R1", Some("|"), true, false),
+            ("C", "R1", Some("|"), true, false),
+            ("R1", "D", None, false, false),
+            ("D", "R2", None, false, true),
+            ("R2", "E", None, true, true),
+        ]
+    );
+    let highlighted: Vec<(&str, bool, bool)> = graph
+        .nodes
+        .iter()
+        .filter(|node| node.shape == Shape::Fact)
+        .map(|node| (node.id.as_str(), node.initial, node.query))
+        .collect();
+    assert_eq!(
+        highlighted,
+        vec![
+            ("A", true, false),
+            ("B", false, false),
+            ("C", false, false),
+            ("D", false, false),
+            ("E", false, true),
+        ]
+    );
+}
+
+#[test]
+fn graph_dot() {
+    let input = input("@B \"the b fact\"\nA + !C => B\n=A\n?B");
+    assert_eq!(
+        to_dot(&rule_graph(&input)),
+        "digraph rules {
+    rankdir=LR;
+    A [label=\"A\", shape=ellipse, style=filled, fillcolor=\"#c8e6c9\"];
+    B [label=\"B (the b fact)\", shape=ellipse, color=\"#7b1fa2\", penwidth=2];
+    C [label=\"C\", shape=ellipse];
+    R1 [label=\"A + !C => B\", shape=box];
+    A -> R1 [label=\"+\"];
+    C -> R1 [label=\"+\", style=dashed];
+    R1 -> B;
+}
+"
+    );
+}
+
+#[test]
+fn graph_mermaid() {
+    let input = input("A | !C <=> B\n=A\n?B");
+    assert_eq!(
+        to_mermaid(&rule_graph(&input)),
+        "flowchart LR
+    A([\"A\"])
+    B([\"B\"])
+    C([\"C\"])
+    R1[\"A #124; !C #lt;=#gt; B\"]
+    A <-->|#124;| R1
+    C <-.->|#124;| R1
+    R1 <--> B
+    classDef c0 fill:#c8e6c9
+    class A c0
+    classDef c1 stroke:#7b1fa2,stroke-width:3px
+    class B c1
+"
+    );
+}
+
+#[test]
+fn graph_proof() {
+    let input = input("A => B\nB + C => D\nE => D\nB => F\n=AC\n?D");
+    let result = fact_result(&input, &'D');
+    let graph = proof_graph(&input, &result);
+    let nodes: Vec<(&str, &str, Option<Resolve>)> = graph
+        .nodes
+        .iter()
+        .map(|node| (node.id.as_str(), node.label.as_str(), node.value))
+        .collect();
+    // Only the rule that proves D is in the graph
+    assert_eq!(
+        nodes,
+        vec![
+            ("D", "D: true", Some(Resolve::True)),
+            ("R1", "B + C => D", Some(Resolve::True)),
+            ("B", "B: true", Some(Resolve::True)),
+            ("R2", "A => B", Some(Resolve::True)),
+            ("A", "A: true", Some(Resolve::True)),
+            ("C", "C: true", Some(Resolve::True)),
+        ]
+    );
+    assert_eq!(graph.edges.len(), 5);
+    assert!(graph.nodes[0].query);
+    assert!(graph.nodes[4].initial);
+}
@@ -0,0 +1,317 @@
+use expert_system::{
+    input::Input,
+    node::{Resolve, ResolveError},
+};
+use std::collections::BTreeSet;
+
+const FACTS: [char; 4] = ['A', 'B', 'C', 'D'];
+
+// Side of a rule with two facts X and Y
+// -- the facts used with a negation are listed to find the expected model
+struct Side {
+    repr: &'static str,
+    evaluate: fn(bool, bool) -> bool,
+    negated: &'static str,
+}
+
+const SIDES: [Side; 10] = [
+    Side {
+        repr: "X",
+        evaluate: |x, _| x,
+        negated: "",
+    },
+    Side {
+        repr: "!X",
+        evaluate: |x, _| !x,
+        negated: "X",
+    },
+    Side {
+        repr: "X + Y",
+        evaluate: |x, y| x && y,
+        negated: "",
+    },
+    Side {
+        repr: "X | Y",
+        evaluate: |x, y| x || y,
+        negated: "",
+    },
+    Side {
+        repr: "X ^ Y",
+        evaluate: |x, y| x != y,
+        negated: "",
+    },
+    Side {
+        repr: "X + !Y",
+        evaluate: |x, y| x && !y,
+        negated: "Y",
+    },
+    Side {
+        repr: "!X | Y",
+        evaluate: |x, y| !x || y,
+        negated: "X",
+    },
+    Side {
+        repr: "!(X + Y)",
+        evaluate: |x, y| !(x && y),
+        negated: "XY",
+    },
+    Side {
+        repr: "!X ^ Y",
+        evaluate: |x, y| x == y,
+        negated: "X",
+    },
+    Side {
+        repr: "(X | !Y) ^ (X + Y)",
+        evaluate: |x, y| (x || !y) != (x && y),
+        negated: "Y",
+    },
+];
+
+// Facts of the left and right sides, the sides can share facts
+const SIDE_FACTS: [((char, char), (char, char)); 4] = [
+    (('A', 'B'), ('C', 'D')),
+    (('A', 'B'), ('B', 'C')),
+    (('A', 'B'), ('A', 'B')),
+    (('A', 'B'), ('B', 'A')),
+];
+
+fn side_repr(side: &Side, (x, y): (char, char)) -> String {
+    side.repr
+        .chars()
+        .map(|c| match c {
+            'X' => x,
+            'Y' => y,
+            _ => c,
+        })
+        .collect()
+}
+
+fn side_negated(side: &Side, (x, y): (char, char)) -> BTreeSet<char> {
+    let mut negated = BTreeSet::new();
+    if side.negated.contains('X') {
+        negated.insert(x);
+    }
+    if side.negated.contains('Y') {
+        negated.insert(y);
+    }
+    negated
+}
+
+fn side_evaluate(side: &Side, (x, y): (char, char), model: &BTreeSet<char>) -> bool {
+    (side.evaluate)(model.contains(&x), model.contains(&y))
+}
+
+// The models of a set of facts with the least true facts, a model is a set of true facts
+fn least_models(models: Vec<BTreeSet<char>>, facts: &BTreeSet<char>) -> Vec<BTreeSet<char>> {
+    let true_facts: Vec<BTreeSet<char>> = models
+        .iter()
+        .map(|model| model.intersection(facts).cloned().collect())
+        .collect();
+    models
+        .iter()
+        .zip(true_facts.iter())
+        .filter(|(_, facts)| {
+            !true_facts
+                .iter()
+                .any(|other| other.len() < facts.len() && other.is_subset(facts))
+        })
+        .map(|(model, _)| model.clone())
+        .collect()
+}
+
+// Expected value from the truth table of the rule and the closed world of the engine:
+// the initial facts are true and the other facts are false unless the rule requires them,
+// the facts used with a negation are the first to be false, then the other facts
+// -- initial facts that contradict the rule have no expected result
+fn oracle(
+    (left, left_facts): (&Side, (char, char)),
+    (right, right_facts): (&Side, (char, char)),
+    initial_facts: &BTreeSet<char>,
+    query: char,
+) -> Option<Resolve> {
+    let models: Vec<BTreeSet<char>> = FACTS
+        .iter()
+        .fold(vec![BTreeSet::new()], |models, fact| {
+            models
+                .into_iter()
+                .flat_map(|model| {
+                    let mut with_fact = model.clone();
+                    with_fact.insert(*fact);
+                    [model, with_fact]
+                })
+                .collect()
+        })
+        .into_iter()
+        .filter(|model| model.is_superset(initial_facts))
+        .filter(|model| {
+            side_evaluate(left, left_facts, model) == side_evaluate(right, right_facts, model)
+        })
+        .collect();
+    let negated: BTreeSet<char> = side_negated(left, left_facts)
+        .union(&side_negated(right, right_facts))
+        .filter(|fact| !initial_facts.contains(fact))
+        .cloned()
+        .collect();
+    let others: BTreeSet<char> = FACTS
+        .iter()
+        .filter(|fact| !negated.contains(fact) && !initial_facts.contains(fact))
+        .cloned()
+        .collect();
+    // The least negated facts first, then for each of their values the least other facts
+    let models = least_models(models, &negated);
+    let mut groups: Vec<Vec<BTreeSet<char>>> = vec![];
+    for model in models {
+        match groups.iter_mut().find(|group| {
+            group[0]
+                .intersection(&negated)
+                .eq(model.intersection(&negated))
+        }) {
+            Some(group) => group.push(model),
+            None => groups.push(vec![model]),
+        }
+    }
+    let models: Vec<BTreeSet<char>> = groups
+        .into_iter()
+        .flat_map(|group| least_models(group, &others))
+        .collect();
+    if models.is_empty() {
+        None
+    } else if models.iter().all(|model| model.contains(&query)) {
+        Some(Resolve::True)
+    } else if models.iter().any(|model| model.contains(&query)) {
+        Some(Resolve::Ambiguous)
+    } else {
+        Some(Resolve::False)
+    }
+}
+
+#[test]
+fn if_and_only_if_truth_table() {
+    for (left_facts, right_facts) in SIDE_FACTS {
+        for left in SIDES.iter() {
+            for right in SIDES.iter() {
+                let rule = format!(
+                    "{} <=> {}",
+                    side_repr(left, left_facts),
+                    side_repr(right, right_facts)
+                );
+                for set in 0..16 {
+                    let initial_facts: BTreeSet<char> = FACTS
+                        .iter()
+                        .enumerate()
+                        .filter(|(index, _)| set & (1 << index) != 0)
+                        .map(|(_, fact)| *fact)
+                        .collect();
+                    let initial_facts_repr: String = initial_facts.iter().collect();
+                    for query in FACTS {
+                        let expected = oracle(
+                            (left, left_facts),
+                            (right, right_facts),
+                            &initial_facts,
+                            query,
+                        );
+                        if expected.is_none() {
+                            continue;
+                        }
+                        let mut input = Input::new();
+                        let result = input.parse_content(&format!(
+                            "{}\n={}\n?{}",
+                            rule, initial_facts_repr, query
+                        ));
+                        assert!(result.is_ok());
+                        let mut path: Vec<String> = vec![];
+                        let query_result = input
+                            .facts
+                            .get(&query)
+                            .unwrap()
+                            .as_ref()
+                            .borrow()
+                            .resolve(&mut path);
+                        assert_eq!(
+                            query_result,
+                            Ok(expected.unwrap()),
+                            "`{}` with ={} ?{}",
+                            rule,
+                            initial_facts_repr,
+                            query
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn if_and_only_if_false_conjunction() {
+    let mut input = Input::new();
+    let result = input.parse_content("A <=> B + C\n=B\n?C");
+    assert!(result.is_ok());
+    let mut path: Vec<String> = vec![];
+    let query_result = input
+        .facts
+        .get(input.queries.first().unwrap())
+        .unwrap()
+        .as_ref()
+        .borrow()
+        .resolve(&mut path);
+    assert!(query_result.is_ok());
+    assert!(query_result.unwrap().is_false());
+}
+
+// In the closed world C is used with a negation so it's the first to be false,
+// then A | B has to be true: C is false in every least model ({A} and {B}) and not ambiguous
+#[test]
+fn if_and_only_if_false_negation() {
+    let mut input = Input::new();
+    let result = input.parse_content("A | B <=> !C\n=\n?C");
+    assert!(result.is_ok());
+    let mut path: Vec<String> = vec![];
+    let query_result = input
+        .facts
+        .get(input.queries.first().unwrap())
+        .unwrap()
+        .as_ref()
+        .borrow()
+        .resolve(&mut path);
+    assert!(query_result.is_ok());
+    assert!(query_result.unwrap().is_false());
+}
+
+#[test]
+fn if_and_only_if_query_in_compound_left() {
+    let mut input = Input::new();
+    let result = input.parse_content("A + B <=> C | D\n=AD\n?B");
+    assert!(result.is_ok());
+    let mut path: Vec<String> = vec![];
+    let query_result = input
+        .facts
+        .get(input.queries.first().unwrap())
+        .unwrap()
+        .as_ref()
+        .borrow()
+        .resolve(&mut path);
+    assert!(query_result.is_ok());
+    assert!(query_result.unwrap().is_true());
+}
+
+#[test]
+fn if_and_only_if_too_many_facts() {
+    let mut input = Input::new();
+    let result = input.parse_content(
+        "A + B + C + D + E + F + G <=> H | I | J | K | L | M | N\n=?A?B?C?D?E?F?G?H?I?J?K?L?M\n?N",
+    );
+    assert!(result.is_ok());
+    let mut path: Vec<String> = vec![];
+    let query_result = input
+        .facts
+        .get(&'N')
+        .unwrap()
+        .as_ref()
+        .borrow()
+        .resolve(&mut path);
+    assert_eq!(
+        query_result,
+        Err(ResolveError::Message("Too many unknown facts in rule A and B and C and D and E and F and G if and only if H or I or J or K or L or M or N at 1:1 (14), the limit is 12".to_string()))
+    );
+}
@@ -1,4 +1,10 @@
-use expert_system::input::Input;
+use expert_system::{
+    error::{ErrorKind, Span},
+    input::Input,
+    lint::LintKind,
+    node::{Answers, Fact, Resolve, ResolveError},
+};
+use std::{cell::RefCell, rc::Rc};
 
 #[test]
 fn context_unclosed() {
@@ -232,8 +238,8 @@ fn nested_a_lot_2() {
     let mut input = Input::new();
     let result =
         input.parse_rule_block("!(!(A+!(!C)))|(!(!(A+!(!C))))+!(!(A+!(!C)))|(!(!(A+!(!C))))");
-    if result.is_err() {
-        println!("{}", result.as_ref().unwrap_err());
+    if let Err(error) = &result {
+        println!("{}", error);
     }
     assert!(result.is_ok())
 }
@@ -242,8 +248,599 @@ fn nested_a_lot_2() {
 fn nested_negation() {
     let mut input = Input::new();
     let result = input.parse_rule_block("!!!!!!!!!!!!!!!!!!!!A");
-    if result.is_err() {
-        println!("{}", result.as_ref().unwrap_err());
+    if let Err(error) = &result {
+        println!("{}", error);
     }
     assert!(result.is_ok())
 }
+
+#[test]
+fn query_expression_1() {
+    let mut input = Input::new();
+    let result = input.parse_content("A => B\n=\n?(A + B) | !C");
+    assert!(result.is_ok());
+    assert!(input.queries.is_empty());
+    assert_eq!(input.query_expressions.len(), 1);
+}
+
+#[test]
+fn query_expression_2() {
+    let mut input = Input::new();
+    let result = input.parse_content("A => B\n=\n?AB, A => B, !A");
+    assert!(result.is_ok());
+    assert_eq!(input.queries, vec!['A', 'B']);
+    assert_eq!(input.query_expressions.len(), 2);
+    assert_eq!(input.rules.len(), 1);
+}
+
+#[test]
+fn query_expression_3() {
+    let mut input = Input::new();
+    let result = input.parse_content("A => B\n=\n?A +");
+    assert!(result.is_err());
+}
+
+#[test]
+fn reparse_queries_expression() {
+    let mut input = Input::new();
+    let result = input.parse_content("A => B\n=\n?B");
+    assert!(result.is_ok());
+    let result = input.reparse_queries("?A ^ B");
+    assert!(result.is_ok());
+    assert!(input.queries.is_empty());
+    assert_eq!(input.query_expressions.len(), 1);
+}
+
+#[test]
+fn askable_fact_1() {
+    let mut input = Input::new();
+    let result = input.parse_content("ask A\nA => B\n=\n?B");
+    assert!(result.is_ok());
+    assert_eq!(input.askable, vec!['A']);
+    assert!(input.facts.get(&'A').unwrap().borrow().prompt.is_none());
+    assert!(input.facts.get(&'A').unwrap().borrow().answers.is_none());
+}
+
+#[test]
+fn askable_fact_2() {
+    let mut input = Input::new();
+    let result =
+        input.parse_content("ask A \"Is the engine overheating?\" # comment\nA => B\n=\n?B");
+    assert!(result.is_ok());
+    assert_eq!(
+        input.facts.get(&'A').unwrap().borrow().prompt,
+        Some("Is the engine overheating?".to_string())
+    );
+    input.set_answers(Some(Rc::new(Answered::new(vec![]))));
+    assert!(input.facts.get(&'A').unwrap().borrow().answers.is_some());
+    assert!(input.facts.get(&'B').unwrap().borrow().answers.is_none());
+    input.set_answers(None);
+    assert!(input.facts.get(&'A').unwrap().borrow().answers.is_none());
+}
+
+#[test]
+fn askable_fact_3() {
+    let mut input = Input::new();
+    let result = input.parse_content("ask a\nA => B\n=\n?B");
+    assert!(result.is_err());
+}
+
+#[test]
+fn askable_fact_4() {
+    let mut input = Input::new();
+    let result = input.parse_content("ask A \"Unclosed\nA => B\n=\n?B");
+    assert!(result.is_err());
+}
+
+// Fixed answers for the askable facts, with the facts that were asked
+#[derive(Debug)]
+struct Answered(Vec<(char, Resolve)>, RefCell<Vec<char>>);
+
+impl Answered {
+    fn new(answers: Vec<(char, Resolve)>) -> Self {
+        Answered(answers, RefCell::new(vec![]))
+    }
+}
+
+impl Answers for Answered {
+    fn answer(&self, fact: &Fact) -> Result<Resolve, String> {
+        self.1.borrow_mut().push(fact.repr);
+        self.0
+            .iter()
+            .find(|(symbol, _)| *symbol == fact.repr)
+            .map(|(_, value)| *value)
+            .ok_or(format!("No answer for fact {}", fact.repr))
+    }
+}
+
+#[test]
+fn askable_fact_resolve() {
+    let mut input = Input::new();
+    let result = input.parse_content("ask A \"Is it hot?\"\nask C\nA + C => B\nA | C => D\n=\n?BD");
+    assert!(result.is_ok());
+    assert_eq!(
+        input.facts.get(&'A').unwrap().borrow().question(),
+        "Is it hot?"
+    );
+    assert_eq!(
+        input.facts.get(&'C').unwrap().borrow().question(),
+        "Is C true?"
+    );
+    // Without answers the askable facts are false
+    let resolve = |input: &Input, query: char| {
+        let mut path: Vec<String> = vec![];
+        let result = RefCell::borrow(input.facts.get(&query).unwrap()).resolve(&mut path);
+        (result, path)
+    };
+    assert_eq!(resolve(&input, 'D').0, Ok(Resolve::False));
+    input.reset();
+    let answered = Rc::new(Answered::new(vec![
+        ('A', Resolve::True),
+        ('C', Resolve::Ambiguous),
+    ]));
+    input.set_answers(Some(answered.clone()));
+    let (result, path) = resolve(&input, 'B');
+    assert_eq!(result, Ok(Resolve::Ambiguous));
+    assert!(path
+        .iter()
+        .any(|step| step.starts_with("A is ") && step.ends_with("(answered)")));
+    // An answered fact is not asked again
+    assert_eq!(resolve(&input, 'D').0, Ok(Resolve::Ambiguous));
+    assert_eq!(*answered.1.borrow(), vec!['A', 'C']);
+    assert!(*input.facts.get(&'C').unwrap().borrow().resolved.borrow());
+    // A missing answer is an error of the resolution
+    input.reset();
+    input.set_answers(Some(Rc::new(Answered::new(vec![('C', Resolve::False)]))));
+    assert_eq!(
+        resolve(&input, 'B').0,
+        Err(ResolveError::Message("No answer for fact A".to_string()))
+    );
+}
+
+#[test]
+fn askable_fact_answer() {
+    assert_eq!(Fact::answer("y\n"), Some(Resolve::True));
+    assert_eq!(Fact::answer("No"), Some(Resolve::False));
+    assert_eq!(Fact::answer("unknown"), Some(Resolve::Ambiguous));
+    assert_eq!(Fact::answer("maybe"), None);
+}
+
+#[test]
+fn fact_description_1() {
+    let mut input = Input::new();
+    let result = input.parse_content("@A \"the engine is overheating\"\nA => B\n=\n?B");
+    assert!(result.is_ok());
+    assert_eq!(
+        input.facts.get(&'A').unwrap().borrow().description,
+        Some("the engine is overheating".to_string())
+    );
+    assert!(input
+        .facts
+        .get(&'B')
+        .unwrap()
+        .borrow()
+        .description
+        .is_none());
+}
+
+#[test]
+fn fact_description_2() {
+    let mut input = Input::new();
+    let result = input.parse_content("@A \"first\"\n@A \"second\"\nA => B\n=\n?B");
+    assert!(result.is_ok());
+    assert_eq!(input.warnings.len(), 1);
+    assert_eq!(
+        input.facts.get(&'A').unwrap().borrow().description,
+        Some("second".to_string())
+    );
+}
+
+#[test]
+fn fact_description_3() {
+    let mut input = Input::new();
+    let result = input.parse_content("@A the engine\nA => B\n=\n?B");
+    assert!(result.is_err());
+}
+
+#[test]
+fn error_location_1() {
+    let mut input = Input::new();
+    let result = input.parse_content("A => B\n\n  A +  + B => C\n=A\n?C");
+    assert!(result.is_err());
+    let error = result.unwrap_err();
+    assert_eq!(error.kind, ErrorKind::InvalidOperator);
+    assert_eq!(error.line, Some(3));
+    assert_eq!(error.column, Some(8));
+    assert_eq!(error.source, Some("  A +  + B => C".to_string()));
+}
+
+#[test]
+fn error_location_2() {
+    let mut input = Input::new();
+    let result = input.parse_content("A => B\n=A\n?B, C + d");
+    assert!(result.is_err());
+    let error = result.unwrap_err();
+    assert_eq!(error.kind, ErrorKind::InvalidCharacter);
+    assert_eq!(error.line, Some(3));
+    assert_eq!(error.column, Some(9));
+}
+
+#[test]
+fn error_location_3() {
+    let mut input = Input::new();
+    let result = input.parse_content("A => B\n=Ab\n?B");
+    assert!(result.is_err());
+    let error = result.unwrap_err();
+    assert_eq!(error.kind, ErrorKind::InvalidInitialFacts);
+    assert_eq!(error.line, Some(2));
+    assert_eq!(error.column, Some(3));
+}
+
+#[test]
+fn error_recovery_1() {
+    let mut input = Input::new();
+    let result = input.parse_content("A => B\nA + => C\nD => B\n(E => F\n=A\n?B");
+    assert!(result.is_err());
+    assert_eq!(input.errors.len(), 2);
+    assert_eq!(input.errors[0].line, Some(2));
+    assert_eq!(input.errors[1].line, Some(4));
+    assert_eq!(result.unwrap_err(), input.errors[0]);
+    // The valid rules are still in the knowledge base
+    assert_eq!(input.rules.len(), 2);
+    assert_eq!(input.initial_facts, vec!['A']);
+    assert_eq!(input.queries, vec!['B']);
+}
+
+#[test]
+fn error_recovery_2() {
+    let mut input = Input::new();
+    let result = input.parse_content("?B\nA => B\n=a\n?B\nC => D");
+    assert!(result.is_err());
+    let kinds: Vec<ErrorKind> = input.errors.iter().map(|error| error.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            ErrorKind::MisplacedLine,
+            ErrorKind::InvalidInitialFacts,
+            ErrorKind::MisplacedLine
+        ]
+    );
+    assert_eq!(input.summary(), "3 errors and 0 warnings");
+}
+
+#[test]
+fn error_recovery_3() {
+    let mut input = Input::new();
+    let result = input.parse_content("A => \nB => C");
+    assert!(result.is_err());
+    let kinds: Vec<ErrorKind> = input.errors.iter().map(|error| error.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            ErrorKind::MissingRightSide,
+            ErrorKind::MissingInitialFacts,
+            ErrorKind::MissingQueries
+        ]
+    );
+}
+
+#[test]
+fn include_1() {
+    let mut input = Input::new();
+    let result = input.load_file("examples/include/include_1.txt");
+    assert!(result.is_ok());
+    assert_eq!(input.files.len(), 2);
+    assert_eq!(input.rules.len(), 3);
+    assert!(input
+        .facts
+        .get(&'E')
+        .unwrap()
+        .borrow()
+        .description
+        .is_some());
+}
+
+#[test]
+fn include_2() {
+    let mut input = Input::new();
+    let result = input.load_file("examples/include/include_2.txt");
+    assert!(result.is_err());
+    assert_eq!(input.errors.len(), 1);
+    let error = result.unwrap_err();
+    assert_eq!(error.kind, ErrorKind::IncludeCycle);
+    assert_eq!(
+        error.file,
+        Some("examples/include/include_3.txt".to_string())
+    );
+    assert_eq!(error.line, Some(4));
+    // The rules before the cycle are still included
+    assert_eq!(input.rules.len(), 1);
+}
+
+#[test]
+fn include_3() {
+    let mut input = Input::new();
+    let result = input.load_file("examples/include/include_3.txt");
+    assert!(result.is_err());
+    let kinds: Vec<ErrorKind> = input.errors.iter().map(|error| error.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            ErrorKind::IncludeCycle,
+            ErrorKind::MisplacedLine,
+            ErrorKind::MisplacedLine,
+            ErrorKind::MissingInitialFacts,
+            ErrorKind::MissingQueries
+        ]
+    );
+    assert_eq!(
+        input.errors[1].file,
+        Some("examples/include/include_2.txt".to_string())
+    );
+}
+
+#[test]
+fn include_4() {
+    let mut input = Input::new();
+    let result = input.parse_content("include \"missing.txt\"\nA => B\n=A\n?B");
+    assert!(result.is_err());
+    let error = result.unwrap_err();
+    assert_eq!(error.kind, ErrorKind::Io);
+    assert_eq!(error.line, Some(1));
+    assert_eq!(error.column, Some(10));
+}
+
+#[test]
+fn unicode_operators() {
+    let mut input = Input::new();
+    let result = input.parse_content("A ∧ ¬B → C\nC ∨ D ↔ E ⊕ ¬(F ∧ A)\n=A\n?CE");
+    assert!(result.is_ok());
+    let mut ascii = Input::new();
+    assert!(ascii
+        .parse_content("A + !B => C\nC | D <=> E ^ !(F + A)\n=A\n?CE")
+        .is_ok());
+    for (rule, expected) in input.rules.iter().zip(ascii.rules.iter()) {
+        assert_eq!(
+            RefCell::borrow(rule).short(),
+            RefCell::borrow(expected).short()
+        );
+    }
+}
+
+#[test]
+fn word_operators() {
+    let mut input = Input::new();
+    let result =
+        input.parse_content("A AND NOT B IMPLIES C\nNOT(C OR D) IFF E XOR A\n=A\n?CE, C AND NOT E");
+    assert!(result.is_ok());
+    assert!(input.warnings.is_empty());
+    assert_eq!(RefCell::borrow(&input.rules[0]).short(), "(A + !B) => C");
+    assert_eq!(
+        RefCell::borrow(&input.rules[1]).short(),
+        "!((C | D)) <=> (E ^ A)"
+    );
+    assert_eq!(input.query_expressions.len(), 1);
+    // A query without operators is still a list of facts
+    let mut input = Input::new();
+    assert!(input.parse_content("A => N + D\n=A\n?AND").is_ok());
+    assert_eq!(input.queries, vec!['A', 'N', 'D']);
+}
+
+#[test]
+fn word_operator_errors() {
+    // A word is only an operator when it's not part of a longer word
+    let mut input = Input::new();
+    let error = input.parse_content("A ANDB => C\n=A\n?C").unwrap_err();
+    assert_eq!(error.kind, ErrorKind::MissingOperator);
+    assert_eq!(error.column, Some(3));
+
+    let mut input = Input::new();
+    let error = input.parse_content("A IMPLIESB\n=A\n?C").unwrap_err();
+    assert_eq!(error.kind, ErrorKind::InvalidRule);
+
+    let mut input = Input::new();
+    let error = input.parse_content("IFF A\n=A\n?A").unwrap_err();
+    assert_eq!(error.kind, ErrorKind::MissingLeftSide);
+
+    let mut input = Input::new();
+    let error = input.parse_content("A ∧ → C\n=A\n?C").unwrap_err();
+    assert_eq!(error.kind, ErrorKind::IncompleteExpression);
+    assert_eq!(error.column, Some(3));
+}
+
+#[test]
+fn bonus_8() {
+    let mut input = Input::new();
+    assert!(input.load_file("examples/bonus/bonus_8.txt").is_ok());
+    let mut ascii = Input::new();
+    assert!(ascii.load_file("examples/bonus/bonus_7.txt").is_ok());
+    for query in ascii.queries.iter() {
+        assert_eq!(
+            RefCell::borrow(input.facts.get(query).unwrap()).resolve(&mut vec![]),
+            RefCell::borrow(ascii.facts.get(query).unwrap()).resolve(&mut vec![])
+        );
+    }
+}
+
+#[test]
+fn reparse_clears_diagnostics() {
+    let mut input = Input::new();
+    let result = input.parse_content("A => B\nC =>\n=AA\n?B");
+    assert!(result.is_err());
+    assert_eq!(input.errors.len(), 1);
+    assert_eq!(input.warnings.len(), 1);
+    // The fixed initial facts and queries don't report the errors and warnings of the load
+    assert!(input.reparse_initial_facts("=A").is_ok());
+    assert!(input.errors.is_empty());
+    assert!(input.warnings.is_empty());
+    assert!(input.reparse_queries("?C").is_ok());
+    assert_eq!(input.warnings.len(), 1);
+    assert!(input.reparse_queries("?B").is_ok());
+    assert!(input.warnings.is_empty());
+    // A new content only reports its own errors
+    let mut input = Input::new();
+    assert!(input.parse_content("A =>\n=A\n?B").is_err());
+    assert!(input.parse_content("A => B\n=A\n?B").is_ok());
+    assert!(input.errors.is_empty());
+}
+
+#[test]
+fn explicit_initial_facts() {
+    let mut input = Input::new();
+    let result = input.parse_content("A => B\nC => D\n=A !C ?D\n?BD");
+    assert!(result.is_ok());
+    assert_eq!(input.initial_facts, vec!['A']);
+    assert_eq!(
+        input.explicit_facts,
+        vec![('C', Resolve::False), ('D', Resolve::Ambiguous)]
+    );
+    // The explicit facts are resolved, a rule can't change them
+    for (symbol, value) in [('C', Resolve::False), ('D', Resolve::Ambiguous)] {
+        let fact = RefCell::borrow(input.facts.get(&symbol).unwrap());
+        assert!(*fact.resolved.borrow());
+        assert_eq!(*fact.value.borrow(), value);
+    }
+    let result = input.reparse_initial_facts("=!A?A");
+    assert!(result.is_ok());
+    assert!(input.initial_facts.is_empty());
+    assert_eq!(input.explicit_facts, vec![('A', Resolve::False)]);
+    assert_eq!(input.warnings.len(), 1);
+    assert_eq!(input.warnings[0].kind, LintKind::DuplicateInitialFact);
+    assert_eq!(
+        input.warnings[0].message,
+        "Duplicate initial fact for symbol A"
+    );
+    input.reset();
+    assert!(!*RefCell::borrow(input.facts.get(&'C').unwrap())
+        .resolved
+        .borrow());
+}
+
+#[test]
+fn explicit_initial_fact_errors() {
+    for (line, column, message) in [
+        ("=A!", 3, "Missing fact after `!`"),
+        ("=A? B", 3, "Missing fact after `?`"),
+        ("=!?A", 2, "Missing fact after `!`"),
+        ("=A!b", 4, "Initial facts can only be uppercase letters"),
+    ] {
+        let mut input = Input::new();
+        let error = input.reparse_initial_facts(line).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::InvalidInitialFacts);
+        assert_eq!(error.column, Some(column), "{}", line);
+        assert_eq!(error.message, message);
+    }
+}
+
+#[test]
+fn rule_labels() {
+    let mut input = Input::new();
+    let result = input.parse_content(
+        "[overheat] T + !F => O  # owner: powertrain, reviewed: yes\n\
+         [fan_2] P => F # Only a comment\n\
+         O => X\n=TP\n?OX",
+    );
+    assert!(result.is_ok());
+    let rule = RefCell::borrow(&input.rules[0]);
+    assert_eq!(rule.label, Some("overheat".to_string()));
+    assert_eq!(
+        rule.metadata,
+        vec![
+            ("owner".to_string(), "powertrain".to_string()),
+            ("reviewed".to_string(), "yes".to_string())
+        ]
+    );
+    assert_eq!(rule.to_string(), "[overheat] T and not F implies O");
+    let rule = RefCell::borrow(&input.rules[1]);
+    assert_eq!(rule.label, Some("fan_2".to_string()));
+    assert!(rule.metadata.is_empty());
+    assert_eq!(RefCell::borrow(&input.rules[2]).label, None);
+    assert!(input.find_rule("fan_2").is_some());
+    assert!(input.find_rule("fan").is_none());
+}
+
+#[test]
+fn rule_label_errors() {
+    for (content, column, message) in [
+        (
+            "[a b] A => B",
+            1,
+            "Invalid rule label, expected `[name]` with letters, digits, `_` or `-`",
+        ),
+        (
+            "  [a A => B",
+            3,
+            "Invalid rule label, expected `[name]` with letters, digits, `_` or `-`",
+        ),
+        ("[a] A => B\n [a] B => C", 3, "Duplicate rule label `a`"),
+    ] {
+        let mut input = Input::new();
+        let error = input.parse_content(content).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::InvalidRule);
+        assert_eq!(error.column, Some(column), "{}", content);
+        assert_eq!(error.message, message);
+    }
+    let mut input = Input::new();
+    let error = input.parse_rule("[a] => B").unwrap_err();
+    assert_eq!(error.kind, ErrorKind::MissingLeftSide);
+    assert_eq!(error.column, Some(5));
+}
+
+#[test]
+fn node_spans() {
+    let span = |line, column, end| {
+        Some(Span {
+            file: None,
+            line,
+            column,
+            end,
+        })
+    };
+    let mut input = Input::new();
+    let result = input.parse_content("A + B => C\n  (D | !E) <= F  # comment\n=A\n?C, A + !B");
+    assert!(result.is_ok());
+    let rule = RefCell::borrow(&input.rules[0]);
+    assert_eq!(rule.span, span(1, 1, 11));
+    assert_eq!(
+        RefCell::borrow(rule.left.as_ref().unwrap()).span,
+        span(1, 1, 6)
+    );
+    assert_eq!(
+        RefCell::borrow(rule.right.as_ref().unwrap()).span,
+        span(1, 10, 11)
+    );
+    // The premise of a reverse implication is written on the right
+    let rule = RefCell::borrow(&input.rules[1]);
+    assert_eq!(rule.span, span(2, 3, 16));
+    assert_eq!(
+        RefCell::borrow(rule.left.as_ref().unwrap()).span,
+        span(2, 15, 16)
+    );
+    let conclusion = RefCell::borrow(rule.right.as_ref().unwrap());
+    let or = RefCell::borrow(conclusion.left.as_ref().unwrap());
+    assert_eq!(or.span, span(2, 4, 10));
+    assert_eq!(
+        RefCell::borrow(or.right.as_ref().unwrap()).span,
+        span(2, 8, 10)
+    );
+    // The expressions of the queries are in the line of the queries
+    let query = RefCell::borrow(&input.query_expressions[0]);
+    assert_eq!(query.span, span(4, 5, 11));
+    assert_eq!(
+        RefCell::borrow(query.right.as_ref().unwrap()).span,
+        span(4, 9, 11)
+    );
+    // The rules of an included file keep their file
+    let mut input = Input::new();
+    let result = input.load_file("examples/include/include_1.txt");
+    assert!(result.is_ok());
+    let span = RefCell::borrow(&input.rules[1]).span.clone().unwrap();
+    assert_eq!(
+        span.file.unwrap(),
+        "examples/include/common/vehicle_rules.txt"
+    );
+    assert_eq!((span.line, span.column, span.end), (8, 1, 7));
+    let span = RefCell::borrow(&input.rules[2]).span.clone().unwrap();
+    assert_eq!(span.file.unwrap(), "examples/include/include_1.txt");
+    assert_eq!(span.line, 5);
+}
@@ -0,0 +1,138 @@
+use expert_system::{
+    error::ErrorKind,
+    input::Input,
+    json::{export_input, parse_json, query_results, JsonExpression, ProofSource},
+    node::Resolve,
+};
+
+fn values(input: &Input) -> Vec<(String, Option<Resolve>)> {
+    query_results(input)
+        .into_iter()
+        .map(|result| (result.query, result.value))
+        .collect()
+}
+
+#[test]
+fn json_1() {
+    let mut input = Input::new();
+    let result = input.load_file("examples/json/json_1.json");
+    assert!(result.is_ok());
+    assert_eq!(input.rules.len(), 2);
+    assert_eq!(input.initial_facts, vec!['T', 'F']);
+    assert_eq!(input.queries, vec!['S']);
+    assert_eq!(values(&input), vec![("S".to_string(), Some(Resolve::True))]);
+}
+
+#[test]
+fn json_roundtrip() {
+    for file_path in [
+        "examples/mix/mix_1.txt",
+        "examples/queries/query_1.txt",
+        "examples/ask/ask_1.txt",
+        "examples/bonus/bonus_1.txt",
+        "examples/bonus/bonus_9.txt",
+    ] {
+        let mut input = Input::new();
+        assert!(input.load_file(file_path).is_ok(), "{}", file_path);
+        let content = serde_json::to_string(&export_input(&input)).unwrap();
+        let mut imported = Input::new();
+        assert!(parse_json(&mut imported, &content).is_ok(), "{}", file_path);
+        assert_eq!(
+            export_input(&imported),
+            export_input(&input),
+            "{}",
+            file_path
+        );
+        assert_eq!(values(&imported), values(&input), "{}", file_path);
+    }
+}
+
+#[test]
+fn json_proof() {
+    let mut input = Input::new();
+    let result = input.load_file("examples/json/json_1.json");
+    assert!(result.is_ok());
+    let results = query_results(&input);
+    let proof = &results[0].proof[0];
+    assert_eq!(proof.source, ProofSource::Rules);
+    assert_eq!(proof.rules.len(), 1);
+    assert_eq!(proof.rules[0].rule, "O => S");
+    let premise = &proof.rules[0].facts[0];
+    assert_eq!((premise.fact, premise.value), ('O', Resolve::True));
+    assert_eq!(premise.rules[0].rule, "T + F => O");
+    assert_eq!(premise.rules[0].facts[0].source, ProofSource::InitialFact);
+    // The path has the steps of the resolution in their order
+    assert_eq!(results[0].path.len(), 7);
+    assert_eq!(results[0].path[3], "T (the engine is overheating) is true");
+    assert_eq!(
+        results[0].path.last().unwrap(),
+        "S (the engine must stop) is true because O (the engine will break)"
+    );
+}
+
+#[test]
+fn json_query_expression() {
+    let mut input = Input::new();
+    let result = parse_json(
+        &mut input,
+        r#"{
+            "rules": [{"type": "implies", "left": {"type": "fact", "symbol": "A"}, "right": {"type": "fact", "symbol": "B"}}],
+            "initial_facts": ["A"],
+            "queries": [{"type": "not", "operand": {"type": "fact", "symbol": "B"}}]
+        }"#,
+    );
+    assert!(result.is_ok());
+    assert!(input.queries.is_empty());
+    assert_eq!(
+        values(&input),
+        vec![("!B".to_string(), Some(Resolve::False))]
+    );
+    assert_eq!(
+        export_input(&input).queries,
+        vec![JsonExpression::Not {
+            operand: Box::new(JsonExpression::Fact { symbol: 'B' })
+        }]
+    );
+}
+
+#[test]
+fn json_syntax_error() {
+    let mut input = Input::new();
+    let result = parse_json(&mut input, "{\n  \"rules\": [,]\n}");
+    assert!(result.is_err());
+    let error = result.unwrap_err();
+    assert_eq!(error.kind, ErrorKind::InvalidJson);
+    assert_eq!(error.line, Some(2));
+    assert_eq!(error.column, Some(13));
+    assert_eq!(error.source.as_deref(), Some("  \"rules\": [,]"));
+}
+
+#[test]
+fn json_invalid_rules() {
+    let mut input = Input::new();
+    let result = parse_json(
+        &mut input,
+        r#"{
+            "rules": [
+                {"type": "fact", "symbol": "A"},
+                {"type": "implies", "left": {"type": "fact", "symbol": "a"}, "right": {"type": "fact", "symbol": "B"}},
+                {"type": "implies", "left": {"type": "fact", "symbol": "A"}, "right": {"type": "implies", "left": {"type": "fact", "symbol": "b"}, "right": {"type": "fact", "symbol": "C"}}}
+            ],
+            "queries": [{"type": "fact", "symbol": "B"}]
+        }"#,
+    );
+    assert!(result.is_err());
+    let messages: Vec<&str> = input
+        .errors
+        .iter()
+        .map(|error| error.message.as_str())
+        .collect();
+    assert_eq!(
+        messages,
+        vec![
+            "rules[0]: A rule is an implication or an equivalence",
+            "rules[1].left: Invalid fact `a`, facts are uppercase letters",
+            "rules[2].right.left: Invalid fact `b`, facts are uppercase letters",
+        ]
+    );
+}
@@ -0,0 +1,140 @@
+use expert_system::{
+    error::ErrorKind,
+    input::Input,
+    lint::{LintKind, Severity},
+};
+
+fn lints(content: &str) -> Vec<LintKind> {
+    let mut input = Input::new();
+    assert!(input.parse_content(content).is_ok());
+    input.warnings.iter().map(|warning| warning.kind).collect()
+}
+
+#[test]
+fn lint_codes() {
+    assert_eq!(LintKind::DuplicateInitialFact.code(), "ES0001");
+    assert_eq!(LintKind::TautologicalRule.code(), "ES0004");
+    assert_eq!(
+        LintKind::from_code("ES0007"),
+        Some(LintKind::DuplicateOperand)
+    );
+    assert_eq!(LintKind::from_code("ES9999"), None);
+    assert_eq!(LintKind::SelfImplyingRule.severity(), Severity::Warning);
+    assert_eq!(LintKind::UnusedFact.severity(), Severity::Info);
+}
+
+#[test]
+fn rule_lints() {
+    assert_eq!(lints("A => B\n=A\n?B"), vec![]);
+    // The tautological rules can also be self-implying
+    assert_eq!(
+        lints("A + B => A\n=B\n?A"),
+        vec![LintKind::TautologicalRule, LintKind::SelfImplyingRule]
+    );
+    assert_eq!(
+        lints("A => A\n=A\n?A"),
+        vec![LintKind::TautologicalRule, LintKind::SelfImplyingRule]
+    );
+    assert_eq!(
+        lints("A | B => A\n=B\n?A"),
+        vec![LintKind::SelfImplyingRule]
+    );
+    assert_eq!(
+        lints("A + !A => B\n=A\n?B"),
+        vec![LintKind::UnsatisfiablePremise]
+    );
+    // An unusable rule is not reported as tautological
+    assert_eq!(
+        lints("A + !A => A\n=A\n?A"),
+        vec![LintKind::UnsatisfiablePremise, LintKind::SelfImplyingRule]
+    );
+    assert_eq!(
+        lints("C + C => B\n=C\n?B"),
+        vec![LintKind::DuplicateOperand]
+    );
+    assert_eq!(
+        lints("A => B\nC => !B\n=A\n?B"),
+        vec![LintKind::ContradictoryConclusions]
+    );
+    // A double negation is not a negation
+    assert_eq!(lints("A => B\nC => !!B\n=A\n?B"), vec![]);
+}
+
+#[test]
+fn unused_facts() {
+    assert_eq!(
+        lints("@C \"the cooler\"\nA => B\n=A\n?B"),
+        vec![LintKind::UnusedFact]
+    );
+    assert_eq!(lints("@A \"the alarm\"\nA => B\n=A\n?B"), vec![]);
+    assert_eq!(lints("ask C\nA => B\n=A\n?B"), vec![LintKind::UnusedFact]);
+}
+
+#[test]
+fn contradictory_conclusions() {
+    let mut input = Input::new();
+    assert!(input.parse_content("A => B\nC => !B\n=A\n?B").is_ok());
+    assert_eq!(
+        input.warnings[0].message,
+        "B is concluded by rule A implies B at 1:1 and its negation by rule C implies not B at 2:1"
+    );
+}
+
+#[test]
+fn lint_spans() {
+    let mut input = Input::new();
+    assert!(input.parse_content("A => B\nC + C => B\n=AC\n?B").is_ok());
+    let span = input.warnings[0].span.as_ref().unwrap();
+    assert_eq!((span.line, span.column), (2, 1));
+    assert_eq!(
+        input.warnings[0].to_string(),
+        "info[ES0007]: Duplicate operand C in C + C at 2:1"
+    );
+}
+
+#[test]
+fn allowed_lints() {
+    // In the whole file
+    assert_eq!(lints("# allow(ES0004, ES0005)\nA + B => A\n=B\n?A"), vec![]);
+    assert_eq!(
+        lints("# allow(ES0001, ES0007)\nC + C => B\n=CC\n?B"),
+        vec![]
+    );
+    // Only on its line
+    assert_eq!(
+        lints("A + B => A # allow(ES0004, ES0005)\nB + C => B\n=BC\n?A"),
+        vec![LintKind::TautologicalRule, LintKind::SelfImplyingRule]
+    );
+    assert_eq!(lints("A => B\n=AA # allow(ES0001)\n?B"), vec![]);
+    // A `#` in a description is not a comment
+    assert_eq!(
+        lints("@C \"# allow(ES0009)\"\nA => B\n=A\n?B"),
+        vec![LintKind::UnusedFact]
+    );
+    assert_eq!(
+        lints("# allow(ES9999)\nA => B\n=A\n?B"),
+        vec![LintKind::UnknownLint]
+    );
+}
+
+#[test]
+fn denied_warnings() {
+    let mut input = Input::new();
+    input.deny_warnings = true;
+    let result = input.parse_content("A => B\nA + B => A\nC + C => B\n=AC\n?B");
+    assert!(result.is_err());
+    assert_eq!(input.errors.len(), 2);
+    let error = &input.errors[0];
+    assert_eq!(error.kind, ErrorKind::DeniedWarning);
+    assert_eq!(error.code(), "E0023");
+    assert_eq!((error.line, error.column), (Some(2), Some(1)));
+    // The infos are not denied
+    assert_eq!(input.warnings.len(), 1);
+    assert_eq!(input.warnings[0].kind, LintKind::DuplicateOperand);
+
+    let mut input = Input::new();
+    input.deny_warnings = true;
+    assert!(input
+        .parse_content("# allow(ES0004, ES0005)\nA + B => A\n=B\n?A")
+        .is_ok());
+}
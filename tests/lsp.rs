@@ -0,0 +1,133 @@
+use expert_system::lsp::{path_uri, uri_path, Server};
+use serde_json::{json, Value};
+
+const URI: &str = "file:///rules/vehicle%20rules.txt";
+
+fn request(server: &mut Server, method: &str, params: Value) -> Value {
+    let messages = server
+        .handle(&json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }))
+        .unwrap();
+    assert_eq!(messages.len(), 1);
+    messages[0]["result"].clone()
+}
+
+fn position(line: usize, character: usize) -> Value {
+    json!({ "textDocument": { "uri": URI }, "position": { "line": line, "character": character } })
+}
+
+fn open(server: &mut Server, text: &str) -> Value {
+    let messages = server
+        .handle(&json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didOpen",
+            "params": { "textDocument": { "uri": URI, "languageId": "expert-system", "version": 1, "text": text } },
+        }))
+        .unwrap();
+    assert_eq!(messages[0]["method"], "textDocument/publishDiagnostics");
+    messages[0]["params"]["diagnostics"].clone()
+}
+
+#[test]
+fn lsp_uri() {
+    assert_eq!(uri_path(URI), "/rules/vehicle rules.txt");
+    assert_eq!(path_uri("/rules/vehicle rules.txt"), URI);
+}
+
+#[test]
+fn lsp_diagnostics() {
+    let mut server = Server::new();
+    let result = request(&mut server, "initialize", json!({}));
+    assert_eq!(result["capabilities"]["hoverProvider"], true);
+    let diagnostics = open(&mut server, "A => B\nC =>\n=A\n?B");
+    assert_eq!(diagnostics.as_array().unwrap().len(), 1);
+    assert_eq!(diagnostics[0]["code"], "E0008");
+    assert_eq!(diagnostics[0]["severity"], 1);
+    assert_eq!(
+        diagnostics[0]["range"]["start"],
+        json!({ "line": 1, "character": 4 })
+    );
+    // The warnings are reported with the errors
+    let diagnostics = open(&mut server, "A => B\n=AA\n?B");
+    assert_eq!(diagnostics[0]["severity"], 2);
+    assert_eq!(diagnostics[0]["code"], "ES0001");
+    assert_eq!(
+        diagnostics[0]["range"]["start"],
+        json!({ "line": 1, "character": 0 })
+    );
+    // Unknown requests are an error, notifications have no response
+    let messages = server
+        .handle(&json!({ "jsonrpc": "2.0", "id": 2, "method": "unknown" }))
+        .unwrap();
+    assert_eq!(messages[0]["error"]["code"], -32601);
+    assert!(server
+        .handle(&json!({ "jsonrpc": "2.0", "method": "initialized" }))
+        .unwrap()
+        .is_empty());
+    assert!(server
+        .handle(&json!({ "jsonrpc": "2.0", "method": "exit" }))
+        .is_none());
+}
+
+#[test]
+fn lsp_navigation() {
+    let mut server = Server::new();
+    open(
+        &mut server,
+        "@E \"the engine is overheating\"\n\
+         [overheat] C | F => E\n\
+         E + !F => W\n\
+         =C\n\
+         ?W",
+    );
+    let hover = request(&mut server, "textDocument/hover", position(2, 0));
+    assert_eq!(
+        hover["contents"]["value"],
+        "**E** the engine is overheating\n\nConcluded by:\n\n- `[overheat] C | F => E`"
+    );
+    // A fact of the initial facts and the queries
+    let hover = request(&mut server, "textDocument/hover", position(3, 1));
+    assert_eq!(
+        hover["contents"]["value"],
+        "**C**\n\nInitial fact\n\nNot concluded by any rule"
+    );
+    assert_eq!(
+        request(&mut server, "textDocument/hover", position(2, 2)),
+        Value::Null
+    );
+    let definition = request(&mut server, "textDocument/definition", position(4, 1));
+    assert_eq!(
+        definition,
+        json!([{ "uri": URI, "range": { "start": { "line": 2, "character": 0 }, "end": { "line": 2, "character": 11 } } }])
+    );
+    // The negated fact is found at its symbol
+    let references = request(&mut server, "textDocument/references", position(2, 5));
+    let lines: Vec<(u64, u64)> = references
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|location| {
+            let start = &location["range"]["start"];
+            (
+                start["line"].as_u64().unwrap(),
+                start["character"].as_u64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(lines, vec![(1, 15), (2, 5)]);
+    let completion = request(&mut server, "textDocument/completion", position(0, 0));
+    assert_eq!(completion.as_array().unwrap().len(), 4);
+    assert_eq!(completion[1]["label"], "E");
+    assert_eq!(completion[1]["detail"], "the engine is overheating");
+}
+
+#[test]
+fn lsp_formatting() {
+    let mut server = Server::new();
+    open(&mut server, "A+B=>C\n=A\n?C");
+    let params = json!({ "textDocument": { "uri": URI }, "options": {} });
+    let edits = request(&mut server, "textDocument/formatting", params.clone());
+    assert_eq!(edits[0]["newText"], "A + B => C\n\n=A\n?C\n");
+    open(&mut server, "A + B => C\n\n=A\n?C\n");
+    let edits = request(&mut server, "textDocument/formatting", params);
+    assert_eq!(edits, json!([]));
+}
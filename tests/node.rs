@@ -1,4 +1,8 @@
-use expert_system::input::Input;
+use expert_system::{
+    input::Input,
+    node::{Notation, Operator, Resolve, ResolveError},
+};
+use std::cell::RefCell;
 
 #[test]
 fn basic_query_resolve_1() {
@@ -612,7 +616,8 @@ fn negative_and_conclusion_2() {
         .borrow()
         .resolve(&mut path);
     assert!(query_result.is_ok());
-    assert!(query_result.unwrap().is_true());
+    // The negated fact is concluded false
+    assert!(query_result.unwrap().is_false());
 }
 
 #[test]
@@ -1425,3 +1430,458 @@ fn multiple_if_and_only_if_4() {
     assert!(query_result.is_ok());
     assert!(query_result.unwrap().is_true());
 }
+
+#[test]
+fn query_expression_1() {
+    let mut input = Input::new();
+    let result = input.parse_content("A => B\n=A\n?(A + B) | !C");
+    assert!(result.is_ok());
+    let mut path: Vec<String> = vec![];
+    let query_result = input
+        .query_expressions
+        .first()
+        .unwrap()
+        .as_ref()
+        .borrow()
+        .resolve_query(&mut path);
+    assert!(query_result.is_ok());
+    assert!(query_result.unwrap().is_true());
+}
+
+#[test]
+fn query_expression_2() {
+    let mut input = Input::new();
+    let result = input.parse_content("A => B\n=\n?A + B ^ !C");
+    assert!(result.is_ok());
+    let mut path: Vec<String> = vec![];
+    let query_result = input
+        .query_expressions
+        .first()
+        .unwrap()
+        .as_ref()
+        .borrow()
+        .resolve_query(&mut path);
+    assert!(query_result.is_ok());
+    assert!(query_result.unwrap().is_true());
+}
+
+#[test]
+fn query_expression_implies_1() {
+    let mut input = Input::new();
+    let result = input.parse_content("A => B\n=A\n?A => B");
+    assert!(result.is_ok());
+    let mut path: Vec<String> = vec![];
+    let query_result = input
+        .query_expressions
+        .first()
+        .unwrap()
+        .as_ref()
+        .borrow()
+        .resolve_query(&mut path);
+    assert!(query_result.is_ok());
+    assert!(query_result.unwrap().is_true());
+}
+
+#[test]
+fn query_expression_implies_2() {
+    let mut input = Input::new();
+    let result = input.parse_content("A => B\n=A\n?A => C");
+    assert!(result.is_ok());
+    let mut path: Vec<String> = vec![];
+    let query_result = input
+        .query_expressions
+        .first()
+        .unwrap()
+        .as_ref()
+        .borrow()
+        .resolve_query(&mut path);
+    assert!(query_result.is_ok());
+    assert!(query_result.unwrap().is_false());
+    // The query never concludes anything, and its missing facts are added as false
+    assert!(input
+        .facts
+        .get(&'C')
+        .unwrap()
+        .borrow()
+        .resolve(&mut path)
+        .unwrap()
+        .is_false());
+}
+
+#[test]
+fn query_expression_implies_3() {
+    let mut input = Input::new();
+    let result = input.parse_content("A => B\n=\n?A => C");
+    assert!(result.is_ok());
+    let mut path: Vec<String> = vec![];
+    let query_result = input
+        .query_expressions
+        .first()
+        .unwrap()
+        .as_ref()
+        .borrow()
+        .resolve_query(&mut path);
+    assert!(query_result.is_ok());
+    assert!(query_result.unwrap().is_true());
+}
+
+#[test]
+fn query_expression_missing_fact() {
+    let mut input = Input::new();
+    let result = input.parse_content("A => B\n=A\n?B + !C, B | D");
+    assert!(result.is_ok());
+    assert_eq!(input.warnings.len(), 2);
+    for query in input.query_expressions.iter() {
+        let query_result = RefCell::borrow(query).resolve_query(&mut vec![]);
+        assert_eq!(query_result, Ok(Resolve::True));
+    }
+    let mut facts: Vec<&char> = input.facts.keys().collect();
+    facts.sort_unstable();
+    assert_eq!(facts, vec![&'A', &'B', &'C', &'D']);
+}
+
+#[test]
+fn ambiguous_operands() {
+    use Resolve::{Ambiguous, False, True};
+    // An ambiguous side makes `and` and `or` ambiguous
+    assert_eq!(False.and(Ambiguous), Ambiguous);
+    assert_eq!(Ambiguous.and(True), Ambiguous);
+    assert_eq!(True.and(True), True);
+    assert_eq!(True.or(Ambiguous), Ambiguous);
+    assert_eq!(Ambiguous.or(False), Ambiguous);
+    assert_eq!(False.or(False), False);
+    assert_eq!(False.implies(Ambiguous), True);
+    assert_eq!(Ambiguous.implies(True), True);
+}
+
+#[test]
+fn query_expression_if_and_only_if() {
+    let mut input = Input::new();
+    let result = input.parse_content("A => B | C\n=A\n?B <=> C");
+    assert!(result.is_ok());
+    let mut path: Vec<String> = vec![];
+    let query_result = input
+        .query_expressions
+        .first()
+        .unwrap()
+        .as_ref()
+        .borrow()
+        .resolve_query(&mut path);
+    assert!(query_result.is_ok());
+    assert!(query_result.unwrap().is_ambiguous());
+}
+
+#[test]
+fn fact_description_explanation() {
+    let mut input = Input::new();
+    let result = input.parse_content(
+        "@A \"the engine is overheating\"\n@B \"the fan failed\"\nA + B => C\n=AB\n?C",
+    );
+    assert!(result.is_ok());
+    let mut path: Vec<String> = vec![];
+    let query_result = input
+        .facts
+        .get(input.queries.first().unwrap())
+        .unwrap()
+        .as_ref()
+        .borrow()
+        .resolve(&mut path);
+    assert!(query_result.is_ok());
+    assert!(query_result.unwrap().is_true());
+    assert!(path
+        .last()
+        .unwrap()
+        .ends_with("because A (the engine is overheating) and B (the fan failed)"));
+    // The rules and the errors only write the symbols
+    assert_eq!(
+        input.rules[0].as_ref().borrow().to_string(),
+        "A and B implies C"
+    );
+}
+
+#[test]
+fn fact_description_error() {
+    let mut input = Input::new();
+    let result = input.parse_content("@A \"engine overheats\"\nA + B => A\n=B\n?A");
+    assert!(result.is_ok());
+    let mut path: Vec<String> = vec![];
+    let query_result = RefCell::borrow(input.facts.get(&'A').unwrap()).resolve(&mut path);
+    assert_eq!(
+        query_result,
+        Err(ResolveError::InfiniteRule(
+            "A and B implies A at 2:1".to_string()
+        ))
+    );
+    assert_eq!(
+        path[0],
+        "A (engine overheats) and B implies A (engine overheats) at 2:1"
+    );
+}
+
+#[test]
+fn notation_output() {
+    let mut input = Input::new();
+    let result =
+        input.parse_content("@C \"the conclusion\"\nA + !B => C\nC | D <=> !(E ^ A)\n=A\n?C");
+    assert!(result.is_ok());
+    let rule = |index: usize| input.rules[index].as_ref().borrow().to_string();
+    let short =
+        |index: usize, notation: Notation| input.rules[index].as_ref().borrow().short_in(notation);
+    assert_eq!(short(0, Notation::Unicode), "(A ∧ ¬B) → C");
+    assert_eq!(short(1, Notation::Words), "(C OR D) IFF NOT ((E XOR A))");
+    assert_eq!(short(1, Notation::Ascii), "(C | D) <=> !((E ^ A))");
+    assert_eq!(short(0, Notation::Ascii), "(A + !B) => C");
+    // The notation is only for the short form, Display is always in English
+    assert_eq!(rule(0), "A and not B implies C");
+    assert_eq!(Notation::Words.spelling(Operator::Not), "NOT");
+}
+
+fn resolve(input: &Input, query: char) -> Resolve {
+    input
+        .facts
+        .get(&query)
+        .unwrap()
+        .as_ref()
+        .borrow()
+        .resolve(&mut vec![])
+        .unwrap()
+}
+
+#[test]
+fn negated_operators_truth_tables() {
+    for (initial_facts, nand, nor, xnor) in [
+        ("", Resolve::True, Resolve::True, Resolve::True),
+        ("A", Resolve::True, Resolve::False, Resolve::False),
+        ("B", Resolve::True, Resolve::False, Resolve::False),
+        ("AB", Resolve::False, Resolve::False, Resolve::True),
+    ] {
+        let mut input = Input::new();
+        let content = format!(
+            "A !+ B => C\nA NOR B => D\nA ⊙ B => E\n={}\n?CDE",
+            initial_facts
+        );
+        assert!(input.parse_content(&content).is_ok());
+        assert_eq!(resolve(&input, 'C'), nand, "{}", content);
+        assert_eq!(resolve(&input, 'D'), nor, "{}", content);
+        assert_eq!(resolve(&input, 'E'), xnor, "{}", content);
+    }
+}
+
+#[test]
+fn negated_operators_conclusion() {
+    // A NOR conclusion makes both facts false, the other ones don't tell which facts are true
+    let mut input = Input::new();
+    let result = input.parse_content("A => B !| C\nA => D !+ E\nA => F XNOR G\n=A\n?BCDEFG");
+    assert!(result.is_ok());
+    assert_eq!(resolve(&input, 'B'), Resolve::False);
+    assert_eq!(resolve(&input, 'C'), Resolve::False);
+    for query in ['D', 'E', 'F', 'G'] {
+        assert_eq!(resolve(&input, query), Resolve::Ambiguous);
+    }
+    // A negated operand gets the opposite value of the other one
+    let mut input = Input::new();
+    let result = input.parse_content("A => !B !| C\nA => !D + E\n=A\n?BCDE");
+    assert!(result.is_ok());
+    assert_eq!(resolve(&input, 'B'), Resolve::True);
+    assert_eq!(resolve(&input, 'C'), Resolve::False);
+    assert_eq!(resolve(&input, 'D'), Resolve::False);
+    assert_eq!(resolve(&input, 'E'), Resolve::True);
+}
+
+#[test]
+fn reverse_implication() {
+    let mut input = Input::new();
+    let result = input.parse_content("C <= A + B\nD<=C # comment\n=AB\n?D");
+    assert!(result.is_ok());
+    assert_eq!(input.rules[0].as_ref().borrow().short(), "(A + B) => C");
+    assert_eq!(resolve(&input, 'D'), Resolve::True);
+    let mut input = Input::new();
+    assert!(input.parse_content("C <= A\n=\n?C").is_ok());
+    assert_eq!(resolve(&input, 'C'), Resolve::False);
+}
+
+#[test]
+fn negated_operators_output() {
+    let mut input = Input::new();
+    let result = input.parse_content("A NAND B !| C => D\nD !^ A <=> E\n=A\n?E");
+    assert!(result.is_ok());
+    let rule = |index: usize| input.rules[index].as_ref().borrow().to_string();
+    let short = |index: usize| input.rules[index].as_ref().borrow().short();
+    assert_eq!(short(0), "((A !+ B) !| C) => D");
+    assert_eq!(rule(1), "D xnor A if and only if E");
+    let short =
+        |index: usize, notation: Notation| input.rules[index].as_ref().borrow().short_in(notation);
+    assert_eq!(short(0, Notation::Unicode), "((A ⊼ B) ⊽ C) → D");
+    assert_eq!(short(1, Notation::Words), "(D XNOR A) IFF E");
+}
+
+#[test]
+fn nested_implication_premise() {
+    // A nested implication of a premise is only checked, `A => B` is `!A | B`
+    let mut input = Input::new();
+    let result = input.parse_content("(A => B) + C => D\n(E => F) => G\n!(A => B) => H\n=AC\n?DGH");
+    assert!(result.is_ok());
+    assert_eq!(resolve(&input, 'D'), Resolve::False);
+    assert_eq!(resolve(&input, 'G'), Resolve::True);
+    assert_eq!(resolve(&input, 'H'), Resolve::True);
+    assert_eq!(resolve(&input, 'B'), Resolve::False);
+}
+
+#[test]
+fn nested_implication_conclusion() {
+    // The conclusion of a nested implication only applies when its premise is true
+    for (initial_facts, c, e) in [
+        ("A", Resolve::False, Resolve::True),
+        ("AB", Resolve::True, Resolve::True),
+        ("B", Resolve::False, Resolve::False),
+    ] {
+        let mut input = Input::new();
+        let content = format!("A => (B => C)\nA => (D => F) + E\n={}\n?CEF", initial_facts);
+        assert!(input.parse_content(&content).is_ok());
+        assert_eq!(resolve(&input, 'C'), c, "{}", content);
+        assert_eq!(resolve(&input, 'E'), e, "{}", content);
+        assert_eq!(resolve(&input, 'F'), Resolve::False, "{}", content);
+    }
+    // The premise of the nested implication is not concluded by the rule
+    let mut input = Input::new();
+    assert!(input.parse_content("A => (B => C)\n=A\n?B").is_ok());
+    assert!(RefCell::borrow(input.facts.get(&'B').unwrap())
+        .rules
+        .is_empty());
+    // An equivalence searches the values of all its facts
+    let mut input = Input::new();
+    assert!(input.parse_content("C <=> (A => B)\n=A\n?C").is_ok());
+    assert_eq!(resolve(&input, 'C'), Resolve::False);
+}
+
+#[test]
+fn nested_implication_output() {
+    let mut input = Input::new();
+    let result = input.parse_content("(A => B) + C => D\nA => (B <=> C)\n=\n?D");
+    assert!(result.is_ok());
+    let rule = |index: usize| input.rules[index].as_ref().borrow().to_string();
+    let short = |index: usize| input.rules[index].as_ref().borrow().short();
+    assert_eq!(short(0), "((A => B) + C) => D");
+    assert_eq!(rule(0), "(A implies B) and C implies D");
+    assert_eq!(short(1), "A => (B <=> C)");
+    assert_eq!(
+        input.rules[1].as_ref().borrow().short_in(Notation::Unicode),
+        "A → (B ↔ C)"
+    );
+}
+
+#[test]
+fn explicit_false_conflict() {
+    // A rule that concludes an explicitly false fact is reported, the fact stays false
+    let mut input = Input::new();
+    let result = input.parse_content("A => B + C\nD <=> A\n=A!B!D\n?C");
+    assert!(result.is_ok());
+    let conflict = |input: &Input, query: char| {
+        input
+            .facts
+            .get(&query)
+            .unwrap()
+            .as_ref()
+            .borrow()
+            .resolve(&mut vec![])
+            .unwrap_err()
+            .to_string()
+    };
+    assert_eq!(
+        conflict(&input, 'C'),
+        "Conflict in rule A implies B and C at 1:1, B is explicitly false"
+    );
+    input.reset();
+    assert_eq!(
+        conflict(&input, 'B'),
+        "Conflict in rule A implies B and C at 1:1, B is explicitly false"
+    );
+    input.reset();
+    assert_eq!(
+        conflict(&input, 'D'),
+        "Conflict in rule D if and only if A at 2:1, D is explicitly false"
+    );
+}
+
+#[test]
+fn explicit_unknown_refined() {
+    // An unknown fact stays unknown unless a rule concludes it
+    let mut input = Input::new();
+    assert!(input.parse_content("A => B\n=?B\n?B").is_ok());
+    assert_eq!(resolve(&input, 'B'), Resolve::Ambiguous);
+    let mut input = Input::new();
+    assert!(input.parse_content("A => B\n=A?B\n?B").is_ok());
+    let mut path = vec![];
+    let result = RefCell::borrow(input.facts.get(&'B').unwrap()).resolve(&mut path);
+    assert_eq!(result, Ok(Resolve::True));
+    assert!(path.last().unwrap().ends_with("because A"));
+    // Also when it is concluded with another fact or by an equivalence
+    let mut input = Input::new();
+    assert!(input
+        .parse_content("A => B + C\nD <=> A\n=A?B?D\n?CBD")
+        .is_ok());
+    assert_eq!(resolve(&input, 'C'), Resolve::True);
+    assert_eq!(resolve(&input, 'B'), Resolve::True);
+    assert_eq!(resolve(&input, 'D'), Resolve::True);
+    // A rule that doesn't conclude it true keeps it unknown
+    let mut input = Input::new();
+    assert!(input.parse_content("A => !B\nA => B | C\n=A?B\n?B").is_ok());
+    assert_eq!(resolve(&input, 'B'), Resolve::Ambiguous);
+}
+
+#[test]
+fn disabled_and_removed_rules() {
+    // A disabled rule concludes nothing, a removed rule is unlinked from its facts
+    let mut input = Input::new();
+    let result = input.parse_content("[overheat] T + !F => O\n[fan] P => F\nO => X\n=TP\n?OX");
+    assert!(result.is_ok());
+    let resolve = |input: &Input, query| {
+        RefCell::borrow(input.facts.get(&query).unwrap())
+            .resolve(&mut vec![])
+            .unwrap()
+    };
+    assert_eq!(resolve(&input, 'X'), Resolve::False);
+    assert_eq!(input.toggle_rule("fan"), Ok(true));
+    input.reset();
+    assert_eq!(resolve(&input, 'X'), Resolve::True);
+    assert_eq!(input.toggle_rule("fan"), Ok(false));
+    input.reset();
+    assert_eq!(resolve(&input, 'X'), Resolve::False);
+    assert!(input.remove_rule("fan").is_ok());
+    assert_eq!(input.rules.len(), 2);
+    assert!(RefCell::borrow(input.facts.get(&'F').unwrap())
+        .rules
+        .is_empty());
+    input.reset();
+    assert_eq!(resolve(&input, 'X'), Resolve::True);
+    assert_eq!(
+        input.remove_rule("fan").unwrap_err(),
+        "No rule labelled `fan`"
+    );
+    // The label is shown in the conflicts
+    let mut input = Input::new();
+    let result = input.parse_content("[fan] P => F\n=P!F\n?F");
+    assert!(result.is_ok());
+    assert_eq!(
+        RefCell::borrow(input.facts.get(&'F').unwrap())
+            .resolve(&mut vec![])
+            .unwrap_err()
+            .to_string(),
+        "Conflict in rule [fan] P implies F at 1:1, F is explicitly false"
+    );
+}
+
+#[test]
+fn runtime_error_spans() {
+    // The runtime errors and the rules of the explanations point to where the rule is written
+    let mut input = Input::new();
+    let result = input.parse_content("A => B\n  B => A\n=\n?B");
+    assert!(result.is_ok());
+    let mut path: Vec<String> = vec![];
+    let result = RefCell::borrow(input.facts.get(&'B').unwrap()).resolve(&mut path);
+    assert_eq!(
+        result,
+        Err(ResolveError::InfiniteRule("A implies B at 1:1".to_string()))
+    );
+    assert_eq!(path, vec!["A implies B at 1:1", "B implies A at 2:3"]);
+}
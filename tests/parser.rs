@@ -0,0 +1,217 @@
+use expert_system::{
+    error::ErrorKind,
+    input::Input,
+    node::{Operator, Resolve, ResolveError},
+    parser::{parse_block, Expression},
+};
+use std::cell::RefCell;
+
+fn fact(symbol: char) -> Box<Expression> {
+    Box::new(Expression::Fact(symbol))
+}
+
+fn binary(operator: Operator, left: Box<Expression>, right: Box<Expression>) -> Box<Expression> {
+    Box::new(Expression::Binary(operator, left, right))
+}
+
+#[test]
+fn precedence_1() {
+    let result = parse_block("A|B+C");
+    assert_eq!(
+        result,
+        Ok(*binary(
+            Operator::Or,
+            fact('A'),
+            binary(Operator::And, fact('B'), fact('C'))
+        ))
+    );
+}
+
+#[test]
+fn precedence_2() {
+    let result = parse_block("A|B+C^D");
+    assert_eq!(
+        result,
+        Ok(*binary(
+            Operator::Xor,
+            binary(
+                Operator::Or,
+                fact('A'),
+                binary(Operator::And, fact('B'), fact('C'))
+            ),
+            fact('D')
+        ))
+    );
+}
+
+#[test]
+fn precedence_3() {
+    let result = parse_block("!A+(B^C)");
+    assert_eq!(
+        result,
+        Ok(*binary(
+            Operator::And,
+            Box::new(Expression::Not(fact('A'))),
+            Box::new(Expression::Group(binary(
+                Operator::Xor,
+                fact('B'),
+                fact('C')
+            )))
+        ))
+    );
+}
+
+#[test]
+fn associativity() {
+    let result = parse_block("A^B^C");
+    assert_eq!(
+        result,
+        Ok(*binary(
+            Operator::Xor,
+            binary(Operator::Xor, fact('A'), fact('B')),
+            fact('C')
+        ))
+    );
+}
+
+#[test]
+fn negations_before_operator() {
+    let result = parse_block("!!!A+B");
+    assert!(result.is_ok());
+}
+
+#[test]
+fn missing_operator() {
+    let result = parse_block("AB");
+    assert!(result.is_err());
+    let error = result.unwrap_err();
+    assert_eq!(error.kind, ErrorKind::MissingOperator);
+    assert_eq!(error.column, Some(2));
+}
+
+#[test]
+fn unclosed_context() {
+    let result = parse_block("A+((B)");
+    assert!(result.is_err());
+    let error = result.unwrap_err();
+    assert_eq!(error.kind, ErrorKind::InvalidContext);
+    assert_eq!(error.column, Some(3));
+}
+
+fn resolve(content: &str, query: char) -> Result<Resolve, ResolveError> {
+    let mut input = Input::new();
+    assert!(input.parse_content(content).is_ok(), "{}", content);
+    let result = RefCell::borrow(input.facts.get(&query).unwrap()).resolve(&mut vec![]);
+    result
+}
+
+// The behavior that changed with the precedence climbing parser, the old result is in the comments
+
+#[test]
+fn changed_juxtaposed_facts() {
+    // `AB => C` was read as `A => C`, B was silently dropped
+    let mut input = Input::new();
+    let error = input.parse_content("AB => C\n=AB\n?C").unwrap_err();
+    assert_eq!(error.kind, ErrorKind::MissingOperator);
+    assert_eq!(error.message, "Missing operator between symbols");
+    assert_eq!(error.column, Some(2));
+}
+
+#[test]
+fn changed_mixed_operators() {
+    // `A | B + C ^ D` was grouped as `A | ((B + C) ^ D)` and E was true
+    assert_eq!(
+        resolve("A | B + C ^ D => E\n=AD\n?E", 'E'),
+        Ok(Resolve::False)
+    );
+    assert_eq!(
+        resolve("(A | B + C) ^ D => E\n=AD\n?E", 'E'),
+        Ok(Resolve::False)
+    );
+    assert_eq!(
+        resolve("A | ((B + C) ^ D) => E\n=AD\n?E", 'E'),
+        Ok(Resolve::True)
+    );
+}
+
+#[test]
+fn changed_chained_negations() {
+    // `!!!A + B` was rejected at its operator
+    assert_eq!(resolve("!!!A + B => C\n=B\n?C", 'C'), Ok(Resolve::True));
+    assert_eq!(resolve("!!!A + B => C\n=AB\n?C", 'C'), Ok(Resolve::False));
+}
+
+#[test]
+fn changed_invalid_block_facts() {
+    // The facts of an invalid block were created
+    let mut input = Input::new();
+    assert!(input.parse_rule("A + (B => C").is_err());
+    assert!(input.facts.is_empty());
+}
+
+#[test]
+fn negated_operators() {
+    // A negated operator binds like its operator
+    let result = parse_block("A!+B|C!^D");
+    assert_eq!(
+        result,
+        Ok(*binary(
+            Operator::Xnor,
+            binary(
+                Operator::Or,
+                binary(Operator::Nand, fact('A'), fact('B')),
+                fact('C')
+            ),
+            fact('D')
+        ))
+    );
+    let result = parse_block("A!|B+!C");
+    assert_eq!(
+        result,
+        Ok(*binary(
+            Operator::Nor,
+            fact('A'),
+            binary(
+                Operator::And,
+                fact('B'),
+                Box::new(Expression::Not(fact('C')))
+            )
+        ))
+    );
+}
+
+#[test]
+fn nested_implications() {
+    // The implications bind looser than the other operators, `<=>` looser than `=>`
+    let result = parse_block("A+B=>C<=>D|E=>F");
+    assert_eq!(
+        result,
+        Ok(*binary(
+            Operator::IfAndOnlyIf,
+            binary(
+                Operator::Implies,
+                binary(Operator::And, fact('A'), fact('B')),
+                fact('C')
+            ),
+            binary(
+                Operator::Implies,
+                binary(Operator::Or, fact('D'), fact('E')),
+                fact('F')
+            )
+        ))
+    );
+    // `=>` is right associative, the sides of `<=` are swapped
+    let result = parse_block("A=>B=>C<=D");
+    assert_eq!(
+        result,
+        Ok(*binary(
+            Operator::Implies,
+            fact('A'),
+            binary(
+                Operator::Implies,
+                fact('B'),
+                binary(Operator::Implies, fact('D'), fact('C'))
+            )
+        ))
+    );
+}
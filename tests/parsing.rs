@@ -0,0 +1,108 @@
+use expert_system::input::Input;
+use std::{env, fs};
+
+// Render the loading errors of an example, or the result of its queries if it loads
+fn render(file_path: &str) -> String {
+    let mut input = Input::new();
+    if input.load_file(file_path).is_err() {
+        let mut output = String::new();
+        for error in input.errors.iter() {
+            output.push_str(&format!("{}\n\n", error));
+        }
+        output.push_str(&format!("{}\n", input.summary()));
+        return output;
+    }
+    let mut output = String::new();
+    for query in input.queries.iter() {
+        let mut path: Vec<String> = vec![];
+        let result = input
+            .facts
+            .get(query)
+            .unwrap()
+            .as_ref()
+            .borrow()
+            .resolve(&mut path);
+        match result {
+            Ok(result) => output.push_str(&format!("?{} {}\n", query, result)),
+            Err(error) => output.push_str(&format!("?{} {}\n", query, error)),
+        }
+    }
+    output
+}
+
+// Compare with the snapshot in tests/snapshots, set UPDATE_SNAPSHOTS to write it instead
+fn assert_snapshot(name: &str) {
+    let output = render(&format!("examples/parsing/{}.txt", name));
+    let snapshot_path = format!("tests/snapshots/parsing/{}.txt", name);
+    if env::var("UPDATE_SNAPSHOTS").is_ok() {
+        fs::write(&snapshot_path, &output).unwrap();
+        return;
+    }
+    let snapshot = fs::read_to_string(&snapshot_path).unwrap();
+    assert_eq!(output, snapshot, "snapshot {}", snapshot_path);
+}
+
+#[test]
+fn error_1() {
+    assert_snapshot("error_1");
+}
+
+#[test]
+fn error_2() {
+    assert_snapshot("error_2");
+}
+
+#[test]
+fn error_3() {
+    assert_snapshot("error_3");
+}
+
+#[test]
+fn error_4() {
+    assert_snapshot("error_4");
+}
+
+#[test]
+fn error_5() {
+    assert_snapshot("error_5");
+}
+
+#[test]
+fn error_6() {
+    assert_snapshot("error_6");
+}
+
+#[test]
+fn error_7() {
+    assert_snapshot("error_7");
+}
+
+#[test]
+fn error_8() {
+    assert_snapshot("error_8");
+}
+
+#[test]
+fn error_9() {
+    assert_snapshot("error_9");
+}
+
+#[test]
+fn error_10() {
+    assert_snapshot("error_10");
+}
+
+#[test]
+fn error_11() {
+    assert_snapshot("error_11");
+}
+
+#[test]
+fn error_12() {
+    assert_snapshot("error_12");
+}
+
+#[test]
+fn error_13() {
+    assert_snapshot("error_13");
+}
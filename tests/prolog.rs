@@ -0,0 +1,133 @@
+use expert_system::{
+    error::ErrorKind,
+    input::Input,
+    node::Resolve,
+    prolog::{parse_prolog, to_prolog},
+};
+use std::cell::RefCell;
+
+fn input(content: &str) -> Input {
+    let mut input = Input::new();
+    assert!(input.parse_content(content).is_ok());
+    input
+}
+
+fn resolve(input: &Input, query: char) -> Resolve {
+    RefCell::borrow(input.facts.get(&query).unwrap())
+        .resolve(&mut vec![])
+        .unwrap()
+}
+
+#[test]
+fn prolog_export() {
+    let input = input("@C \"the conclusion\"\nA + !B => C + D\nC | !E => F\nF ^ A => G\n=A\n?CG");
+    assert_eq!(
+        to_prolog(&input),
+        "% expert-system knowledge base
+% c: the conclusion
+:- dynamic a/0, b/0, c/0, d/0, e/0, f/0, g/0.
+
+% A + !B => C + D
+c :- a, \\+ b.
+d :- a, \\+ b.
+
+% C | !E => F
+f :- c.
+f :- \\+ e.
+
+% F ^ A => G
+g :- f, \\+ a.
+g :- \\+ f, a.
+
+a.
+
+?- forall(member(Query, [c, g]), (call(Query) -> format(\"~w true~n\", [Query]) ; format(\"~w false~n\", [Query]))).
+"
+    );
+}
+
+#[test]
+fn prolog_expansion() {
+    // A negated group is expanded with De Morgan's laws, the contradictions are removed
+    let input = input("!(A | B) + (C ^ A) => D\nE <=> F + !G\nA | B => C | D\n=\n?D");
+    let prolog = to_prolog(&input);
+    assert!(prolog.contains("\nd :- \\+ a, \\+ b, c.\n"));
+    assert_eq!(prolog.matches("d :- ").count(), 1);
+    // Both directions of an equivalence, the reverse direction concludes a conjunction
+    assert!(prolog.contains("\ne :- f, \\+ g.\n"));
+    assert!(prolog.contains("% Not a Horn clause: E <=> F + !G\n"));
+    assert!(prolog.contains("% Not a Horn clause: A | B => C | D\n"));
+}
+
+#[test]
+fn prolog_roundtrip() {
+    for content in [
+        "A + !B => C\nC | D => E\nE ^ A => F\n=AD\n?CEF",
+        "@A \"the first fact\"\nA => B + C\nB + !D => E\n!(A + E) => G\n=A\n?BCEG",
+    ] {
+        let input = input(content);
+        let mut imported = Input::new();
+        assert!(parse_prolog(&mut imported, &to_prolog(&input)).is_ok());
+        assert_eq!(imported.initial_facts, input.initial_facts);
+        for fact in input.facts.values() {
+            let fact = RefCell::borrow(fact);
+            assert_eq!(
+                RefCell::borrow(imported.facts.get(&fact.repr).unwrap()).description,
+                fact.description
+            );
+        }
+        for query in input.queries.iter() {
+            assert_eq!(
+                resolve(&imported, *query),
+                resolve(&input, *query),
+                "{}",
+                content
+            );
+        }
+    }
+}
+
+#[test]
+fn prolog_1() {
+    let mut input = Input::new();
+    assert!(input.load_file("examples/prolog/prolog_1.pl").is_ok());
+    assert_eq!(input.rules.len(), 5);
+    assert_eq!(input.initial_facts, vec!['A', 'C']);
+    assert_eq!(input.queries, vec!['F', 'M', 'B', 'D']);
+    // The single letter atoms keep their letter, the others take their first unused letter
+    assert_eq!(
+        RefCell::borrow(input.facts.get(&'A').unwrap()).description,
+        Some("fever".to_string())
+    );
+    assert_eq!(resolve(&input, 'F'), Resolve::True);
+    assert_eq!(resolve(&input, 'M'), Resolve::False);
+    assert_eq!(resolve(&input, 'B'), Resolve::True);
+    assert_eq!(resolve(&input, 'D'), Resolve::False);
+}
+
+#[test]
+fn prolog_errors() {
+    let mut input = Input::new();
+    let error = parse_prolog(&mut input, "a.\n% comment.\nc :- a ; b.\nd :- a, .\n").unwrap_err();
+    assert_eq!(error.kind, ErrorKind::InvalidProlog);
+    assert_eq!((error.line, error.column), (Some(3), Some(8)));
+    assert_eq!(
+        error.message,
+        "Only Horn clauses are supported, like `c :- a, \\+ b.`"
+    );
+    assert_eq!(input.errors.len(), 2);
+    assert_eq!(
+        (input.errors[1].line, input.errors[1].column),
+        (Some(4), Some(7))
+    );
+
+    let mut content: String = ('a'..='z').map(|atom| format!("{}.\n", atom)).collect();
+    content.push_str("z :- extra.\n");
+    let mut input = Input::new();
+    let error = parse_prolog(&mut input, &content).unwrap_err();
+    assert_eq!((error.line, error.column), (Some(27), Some(6)));
+    assert_eq!(
+        error.message,
+        "Too many atoms, `extra` can't be a fact of the 26 uppercase letters"
+    );
+}
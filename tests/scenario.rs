@@ -0,0 +1,123 @@
+use expert_system::{error::ErrorKind, input::Input, node::Resolve, scenario::run_scenario};
+
+#[test]
+fn scenarios_1() {
+    let mut input = Input::new();
+    let result = input.load_file("examples/scenarios/scenario_1.txt");
+    assert!(result.is_ok());
+    assert_eq!(input.scenarios.len(), 3);
+    // The first scenario is used without top-level initial facts and queries
+    assert_eq!(input.initial_facts, vec!['C']);
+    assert_eq!(input.queries, vec!['E', 'W', 'L']);
+    let scenarios = std::mem::take(&mut input.scenarios);
+    for scenario in scenarios.iter() {
+        let outcomes = run_scenario(&mut input, scenario);
+        assert!(outcomes.iter().all(|outcome| outcome.passed()));
+    }
+    assert_eq!(input.initial_facts, vec!['C']);
+}
+
+#[test]
+fn scenarios_2() {
+    let mut input = Input::new();
+    let result = input.load_file("examples/scenarios/scenario_2.txt");
+    assert!(result.is_ok());
+    assert_eq!(input.initial_facts, vec!['A', 'B', 'G']);
+    assert_eq!(input.scenarios.len(), 2);
+    assert_eq!(input.scenarios[0].name, "default");
+    assert_eq!(input.scenarios[0].initial_facts, vec!['A', 'B', 'G']);
+}
+
+#[test]
+fn scenarios_3() {
+    let mut input = Input::new();
+    let result = input.load_file("examples/scenarios/scenario_3.txt");
+    assert!(result.is_ok());
+    let scenarios = std::mem::take(&mut input.scenarios);
+    let outcomes = run_scenario(&mut input, &scenarios[0]);
+    assert!(outcomes.iter().all(|outcome| outcome.passed()));
+    let outcomes = run_scenario(&mut input, &scenarios[1]);
+    assert_eq!(outcomes.len(), 1);
+    assert!(!outcomes[0].passed());
+    assert_eq!(outcomes[0].expected, Resolve::True);
+    assert_eq!(outcomes[0].actual, Ok(Resolve::False));
+}
+
+#[test]
+fn scenario_missing_queries() {
+    let mut input = Input::new();
+    let result = input.parse_content(
+        "A => B\nscenario \"first\"\n=A\nscenario \"second\"\n=A\n?B\nexpect B true",
+    );
+    assert!(result.is_err());
+    assert_eq!(input.errors.len(), 1);
+    assert_eq!(input.errors[0].kind, ErrorKind::MissingQueries);
+    assert_eq!(input.errors[0].line, Some(2));
+    assert_eq!(input.scenarios.len(), 1);
+}
+
+#[test]
+fn scenario_missing_expectations() {
+    let mut input = Input::new();
+    let result = input.parse_content(
+        "A => B\nscenario \"first\"\n=A\n?B\nscenario \"second\"\n=A\n?B\nexpect B true",
+    );
+    assert!(result.is_err());
+    assert_eq!(input.errors.len(), 1);
+    assert_eq!(input.errors[0].kind, ErrorKind::MissingExpectations);
+    assert_eq!(input.errors[0].code(), "E0018");
+    assert_eq!(input.errors[0].line, Some(2));
+    assert_eq!(input.scenarios.len(), 1);
+}
+
+#[test]
+fn scenario_missing_fact() {
+    let mut input = Input::new();
+    let result = input.parse_content("A => B\nscenario \"first\"\n=A\n?B\nexpect B true, Z false");
+    assert!(result.is_ok());
+    let scenarios = std::mem::take(&mut input.scenarios);
+    let outcomes = run_scenario(&mut input, &scenarios[0]);
+    assert_eq!(outcomes.len(), 2);
+    assert!(outcomes[0].passed());
+    // The expected value does not matter for a fact that is not in the knowledge base
+    assert!(!outcomes[1].passed());
+    assert_eq!(
+        outcomes[1].actual,
+        Err("No fact Z in the knowledge base".to_string())
+    );
+}
+
+#[test]
+fn scenario_rule_after() {
+    let mut input = Input::new();
+    let result = input.parse_content("A => B\nscenario \"first\"\nB => C\n=A\n?B");
+    assert!(result.is_err());
+    assert_eq!(input.errors[0].kind, ErrorKind::MisplacedLine);
+    assert_eq!(input.errors[0].line, Some(3));
+}
+
+#[test]
+fn scenario_invalid_expectations() {
+    let mut input = Input::new();
+    let result = input.parse_content("A => B\n=A\n?B\nexpect B yes");
+    assert!(result.is_err());
+    assert_eq!(input.errors[0].kind, ErrorKind::InvalidDirective);
+    assert_eq!(input.errors[0].column, Some(10));
+}
+
+#[test]
+fn scenario_initial_facts_values() {
+    let mut input = Input::new();
+    let result = input.parse_content("A => B\n=\n?B\nscenario \"first\"\n=A\n?B\nexpect B true");
+    assert!(result.is_ok());
+    // The initial facts of a scenario are not set on the Input
+    assert!(input.initial_facts.is_empty());
+    assert!(input
+        .facts
+        .get(&'A')
+        .unwrap()
+        .borrow()
+        .value
+        .borrow()
+        .is_false());
+}
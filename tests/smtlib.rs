@@ -0,0 +1,140 @@
+use expert_system::{input::Input, node::Resolve, smtlib::to_smtlib};
+use std::{cell::RefCell, collections::HashMap};
+
+fn input(content: &str) -> Input {
+    let mut input = Input::new();
+    assert!(input.parse_content(content).is_ok());
+    input
+}
+
+// Evaluate a term of the export, with its tokens in reverse order
+fn evaluate(tokens: &mut Vec<String>, values: &HashMap<String, bool>) -> bool {
+    let token = tokens.pop().unwrap();
+    if token != "(" {
+        return values[&token];
+    }
+    let function = tokens.pop().unwrap();
+    let mut arguments = vec![];
+    while tokens.last().unwrap() != ")" {
+        arguments.push(evaluate(tokens, values));
+    }
+    tokens.pop();
+    match function.as_str() {
+        "not" => !arguments[0],
+        "and" => arguments[0] && arguments[1],
+        "or" => arguments[0] || arguments[1],
+        "xor" => arguments[0] != arguments[1],
+        "=>" => !arguments[0] || arguments[1],
+        "=" => arguments[0] == arguments[1],
+        function => panic!("Unknown function {}", function),
+    }
+}
+
+fn term(values: &HashMap<String, bool>, term: &str) -> bool {
+    let mut tokens: Vec<String> = term
+        .replace('(', " ( ")
+        .replace(')', " ) ")
+        .split_whitespace()
+        .rev()
+        .map(str::to_string)
+        .collect();
+    evaluate(&mut tokens, values)
+}
+
+// Answer of each entailment check of the export, found with all the assignments of the facts
+fn answers(smtlib: &str) -> Vec<(String, Resolve)> {
+    let mut symbols: Vec<String> = vec![];
+    let mut assertions: Vec<String> = vec![];
+    let mut answers = vec![];
+    let mut query = String::new();
+    for line in smtlib.lines() {
+        let line = line.split(" ;").next().unwrap();
+        if let Some(symbol) = line.strip_prefix("(declare-const ") {
+            symbols.push(symbol.trim_end_matches(" Bool)").to_string());
+        } else if let Some(assertion) = line.strip_prefix("(assert ") {
+            assertions.push(assertion[..assertion.len() - 1].to_string());
+        } else if let Some(echo) = line.strip_prefix("(echo \"?") {
+            query = echo.trim_end_matches("\")").to_string();
+        } else if let Some(assumption) = line.strip_prefix("(check-sat-assuming ((not ") {
+            let queried = assumption.trim_end_matches(")))");
+            let models: Vec<bool> = (0..(1_usize << symbols.len()))
+                .map(|model| {
+                    symbols
+                        .iter()
+                        .enumerate()
+                        .map(|(index, symbol)| (symbol.clone(), model & (1 << index) != 0))
+                        .collect::<HashMap<String, bool>>()
+                })
+                .filter(|values| assertions.iter().all(|assertion| term(values, assertion)))
+                .map(|values| term(&values, queried))
+                .collect();
+            let answer = if models.iter().all(|value| *value) {
+                Resolve::True
+            } else if models.iter().all(|value| !*value) {
+                Resolve::False
+            } else {
+                Resolve::Ambiguous
+            };
+            answers.push((query.clone(), answer));
+        }
+    }
+    answers
+}
+
+#[test]
+fn smtlib_export() {
+    let input = input("@C \"the conclusion\"\nA + !B => C\nC <=> D ^ E\n=A\n?C, !C | D");
+    assert_eq!(
+        to_smtlib(&input),
+        "; expert-system knowledge base
+; a query is true if the first check-sat-assuming is unsat, false if the second is unsat
+(set-option :produce-models true)
+(set-logic QF_UF)
+(declare-const A Bool)
+(declare-const B Bool)
+(declare-const C Bool) ; the conclusion
+(declare-const D Bool)
+(declare-const E Bool)
+; A + !B => C
+(assert (=> (and A (not B)) C))
+; C <=> D ^ E
+(assert (= C (xor D E)))
+(assert A) ; initial fact
+(assert (not B)) ; closed world
+(echo \"?C\")
+(check-sat)
+(get-value (C))
+(check-sat-assuming ((not C)))
+(check-sat-assuming (C))
+(declare-const query_1 Bool)
+(assert (= query_1 (or (not C) D)))
+(echo \"?!C | D\")
+(check-sat)
+(get-value (query_1))
+(check-sat-assuming ((not query_1)))
+(check-sat-assuming (query_1))
+"
+    );
+}
+
+// The entailed answers are the answers of the engine
+#[test]
+fn smtlib_entailment() {
+    let input = input("A + B => C\nC => D\nD | E => F\n!G => H\nH + C => I\n=AB\n?CDFHI, A + !G");
+    let results: Vec<(String, Resolve)> = input
+        .queries
+        .iter()
+        .map(|query| {
+            let fact = RefCell::borrow(input.facts.get(query).unwrap());
+            (query.to_string(), fact.resolve(&mut vec![]).unwrap())
+        })
+        .chain(input.query_expressions.iter().map(|expression| {
+            let expression = RefCell::borrow(expression);
+            (
+                "A + !G".to_string(),
+                expression.resolve_query(&mut vec![]).unwrap(),
+            )
+        }))
+        .collect();
+    assert_eq!(answers(&to_smtlib(&input)), results);
+}